@@ -0,0 +1,608 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::{Scope, UserAuth},
+    client::{Error, ErrorKind, ErrorRepr, Method, Request},
+    limit::LimitInfo,
+    list::ListId,
+    request_data::{JsonData, RequestData},
+    tweet::TweetId,
+    user::UserId,
+    AsyncClient,
+};
+
+#[derive(Serialize)]
+struct TweetIdBody {
+    tweet_id: TweetId,
+}
+
+#[derive(Serialize)]
+struct TargetUserIdBody {
+    target_user_id: UserId,
+}
+
+#[derive(Serialize)]
+struct UserIdBody {
+    user_id: UserId,
+}
+
+#[derive(Serialize)]
+struct CreateListBody<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    private: bool,
+}
+
+/// Changes to make to a List with [`AsyncClient::update_list`]. Fields which are not set are
+/// left unchanged.
+#[derive(Serialize, Clone, Default, Debug)]
+pub struct ListUpdate<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    private: Option<bool>,
+}
+
+impl<'a> ListUpdate<'a> {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn name(self, name: &'a str) -> Self {
+        Self {
+            name: Some(name),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn description(self, description: &'a str) -> Self {
+        Self {
+            description: Some(description),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn private(self, private: bool) -> Self {
+        Self {
+            private: Some(private),
+            ..self
+        }
+    }
+}
+
+impl<A: UserAuth> AsyncClient<A> {
+    /// Likes a Tweet on behalf of the user with the given id, who must be the authenticating user.
+    pub async fn like_tweet(
+        &self,
+        user_id: UserId,
+        tweet_id: TweetId,
+    ) -> Result<LikeResponse, Error>
+    {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::LikeWrite];
+
+        let url = format!("https://api.twitter.com/2/users/{}/likes", user_id);
+        let body = TweetIdBody { tweet_id };
+
+        let (data, limit_info) = self.action_request::<_, LikedData>(
+            Request::new_with_data(Method::Post, &url, JsonData::new(&body))
+                .with_scopes(SCOPES)
+        ).await?;
+
+        Ok(LikeResponse {
+            liked: data.liked,
+            limit_info,
+        })
+    }
+
+    /// Removes a like from a Tweet on behalf of the user with the given id, who must be the
+    /// authenticating user.
+    pub async fn unlike_tweet(
+        &self,
+        user_id: UserId,
+        tweet_id: TweetId,
+    ) -> Result<LikeResponse, Error>
+    {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::LikeWrite];
+
+        let url = format!("https://api.twitter.com/2/users/{}/likes/{}", user_id, tweet_id);
+
+        let (data, limit_info) = self.action_request::<_, LikedData>(
+            Request::new(Method::Delete, &url).with_scopes(SCOPES)
+        ).await?;
+
+        Ok(LikeResponse {
+            liked: data.liked,
+            limit_info,
+        })
+    }
+
+    /// Retweets a Tweet on behalf of the user with the given id, who must be the authenticating
+    /// user.
+    pub async fn retweet(
+        &self,
+        user_id: UserId,
+        tweet_id: TweetId,
+    ) -> Result<RetweetResponse, Error>
+    {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::TweetWrite, Scope::UsersRead];
+
+        let url = format!("https://api.twitter.com/2/users/{}/retweets", user_id);
+        let body = TweetIdBody { tweet_id };
+
+        let (data, limit_info) = self.action_request::<_, RetweetedData>(
+            Request::new_with_data(Method::Post, &url, JsonData::new(&body))
+                .with_scopes(SCOPES)
+        ).await?;
+
+        Ok(RetweetResponse {
+            retweeted: data.retweeted,
+            limit_info,
+        })
+    }
+
+    /// Undoes a Retweet on behalf of the user with the given id, who must be the authenticating
+    /// user. `source_tweet_id` is the id of the Tweet which was Retweeted, not of the Retweet
+    /// itself.
+    pub async fn unretweet(
+        &self,
+        user_id: UserId,
+        source_tweet_id: TweetId,
+    ) -> Result<RetweetResponse, Error>
+    {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::TweetWrite, Scope::UsersRead];
+
+        let url = format!(
+            "https://api.twitter.com/2/users/{}/retweets/{}",
+            user_id, source_tweet_id
+        );
+
+        let (data, limit_info) = self.action_request::<_, RetweetedData>(
+            Request::new(Method::Delete, &url).with_scopes(SCOPES)
+        ).await?;
+
+        Ok(RetweetResponse {
+            retweeted: data.retweeted,
+            limit_info,
+        })
+    }
+
+    /// Follows a user on behalf of the user with the given id, who must be the authenticating
+    /// user. If the target user is protected, the follow request must be approved, so
+    /// [`FollowResponse::pending_follow`] is `true` rather than
+    /// [`FollowResponse::following`].
+    pub async fn follow_user(
+        &self,
+        source_user_id: UserId,
+        target_user_id: UserId,
+    ) -> Result<FollowResponse, Error>
+    {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::FollowsWrite];
+
+        let url = format!("https://api.twitter.com/2/users/{}/following", source_user_id);
+        let body = TargetUserIdBody { target_user_id };
+
+        let (data, limit_info) = self.action_request::<_, FollowingData>(
+            Request::new_with_data(Method::Post, &url, JsonData::new(&body))
+                .with_scopes(SCOPES)
+        ).await?;
+
+        Ok(FollowResponse {
+            following: data.following,
+            pending_follow: data.pending_follow,
+            limit_info,
+        })
+    }
+
+    /// Unfollows a user on behalf of the user with the given id, who must be the authenticating
+    /// user.
+    pub async fn unfollow_user(
+        &self,
+        source_user_id: UserId,
+        target_user_id: UserId,
+    ) -> Result<FollowResponse, Error>
+    {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::FollowsWrite];
+
+        let url = format!(
+            "https://api.twitter.com/2/users/{}/following/{}",
+            source_user_id, target_user_id
+        );
+
+        let (data, limit_info) = self.action_request::<_, FollowingData>(
+            Request::new(Method::Delete, &url).with_scopes(SCOPES)
+        ).await?;
+
+        Ok(FollowResponse {
+            following: data.following,
+            pending_follow: data.pending_follow,
+            limit_info,
+        })
+    }
+
+    /// Blocks a user on behalf of the user with the given id, who must be the authenticating
+    /// user.
+    pub async fn block_user(
+        &self,
+        source_user_id: UserId,
+        target_user_id: UserId,
+    ) -> Result<BlockResponse, Error>
+    {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::BlockWrite];
+
+        let url = format!("https://api.twitter.com/2/users/{}/blocking", source_user_id);
+        let body = TargetUserIdBody { target_user_id };
+
+        let (data, limit_info) = self.action_request::<_, BlockingData>(
+            Request::new_with_data(Method::Post, &url, JsonData::new(&body))
+                .with_scopes(SCOPES)
+        ).await?;
+
+        Ok(BlockResponse {
+            blocking: data.blocking,
+            limit_info,
+        })
+    }
+
+    /// Unblocks a user on behalf of the user with the given id, who must be the authenticating
+    /// user.
+    pub async fn unblock_user(
+        &self,
+        source_user_id: UserId,
+        target_user_id: UserId,
+    ) -> Result<BlockResponse, Error>
+    {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::BlockWrite];
+
+        let url = format!(
+            "https://api.twitter.com/2/users/{}/blocking/{}",
+            source_user_id, target_user_id
+        );
+
+        let (data, limit_info) = self.action_request::<_, BlockingData>(
+            Request::new(Method::Delete, &url).with_scopes(SCOPES)
+        ).await?;
+
+        Ok(BlockResponse {
+            blocking: data.blocking,
+            limit_info,
+        })
+    }
+
+    /// Likes a Tweet on behalf of the user this client acts on behalf of. The user's id is
+    /// looked up with [`resolve_acting_user_id`](Self::resolve_acting_user_id) if it is not
+    /// already known.
+    pub async fn like_tweet_as_acting_user(
+        &self,
+        tweet_id: TweetId,
+    ) -> Result<LikeResponse, Error>
+    {
+        self.like_tweet(self.resolve_acting_user_id().await?, tweet_id).await
+    }
+
+    /// Removes a like from a Tweet on behalf of the user this client acts on behalf of.
+    pub async fn unlike_tweet_as_acting_user(
+        &self,
+        tweet_id: TweetId,
+    ) -> Result<LikeResponse, Error>
+    {
+        self.unlike_tweet(self.resolve_acting_user_id().await?, tweet_id).await
+    }
+
+    /// Retweets a Tweet on behalf of the user this client acts on behalf of.
+    pub async fn retweet_as_acting_user(
+        &self,
+        tweet_id: TweetId,
+    ) -> Result<RetweetResponse, Error>
+    {
+        self.retweet(self.resolve_acting_user_id().await?, tweet_id).await
+    }
+
+    /// Undoes a Retweet on behalf of the user this client acts on behalf of.
+    pub async fn unretweet_as_acting_user(
+        &self,
+        source_tweet_id: TweetId,
+    ) -> Result<RetweetResponse, Error>
+    {
+        self.unretweet(self.resolve_acting_user_id().await?, source_tweet_id).await
+    }
+
+    /// Follows a user on behalf of the user this client acts on behalf of.
+    pub async fn follow_user_as_acting_user(
+        &self,
+        target_user_id: UserId,
+    ) -> Result<FollowResponse, Error>
+    {
+        self.follow_user(self.resolve_acting_user_id().await?, target_user_id).await
+    }
+
+    /// Unfollows a user on behalf of the user this client acts on behalf of.
+    pub async fn unfollow_user_as_acting_user(
+        &self,
+        target_user_id: UserId,
+    ) -> Result<FollowResponse, Error>
+    {
+        self.unfollow_user(self.resolve_acting_user_id().await?, target_user_id).await
+    }
+
+    /// Blocks a user on behalf of the user this client acts on behalf of.
+    pub async fn block_user_as_acting_user(
+        &self,
+        target_user_id: UserId,
+    ) -> Result<BlockResponse, Error>
+    {
+        self.block_user(self.resolve_acting_user_id().await?, target_user_id).await
+    }
+
+    /// Unblocks a user on behalf of the user this client acts on behalf of.
+    pub async fn unblock_user_as_acting_user(
+        &self,
+        target_user_id: UserId,
+    ) -> Result<BlockResponse, Error>
+    {
+        self.unblock_user(self.resolve_acting_user_id().await?, target_user_id).await
+    }
+
+    /// Creates a new List owned by the authenticating user.
+    pub async fn create_list(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        private: bool,
+    ) -> Result<CreateListResponse, Error>
+    {
+        const URL: &str = "https://api.twitter.com/2/lists";
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::ListWrite];
+
+        let body = CreateListBody { name, description, private };
+
+        let (data, limit_info) = self.action_request::<_, CreatedListData>(
+            Request::new_with_data(Method::Post, URL, JsonData::new(&body))
+                .with_scopes(SCOPES)
+        ).await?;
+
+        Ok(CreateListResponse {
+            id: data.id,
+            name: data.name,
+            limit_info,
+        })
+    }
+
+    /// Updates the name, description or privacy of a List owned by the authenticating user.
+    pub async fn update_list(
+        &self,
+        id: ListId,
+        update: &ListUpdate<'_>,
+    ) -> Result<UpdateListResponse, Error>
+    {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::ListWrite];
+
+        let url = format!("https://api.twitter.com/2/lists/{}", id);
+
+        let (data, limit_info) = self.action_request::<_, UpdatedData>(
+            Request::new_with_data(Method::Put, &url, JsonData::new(update))
+                .with_scopes(SCOPES)
+        ).await?;
+
+        Ok(UpdateListResponse {
+            updated: data.updated,
+            limit_info,
+        })
+    }
+
+    /// Deletes a List owned by the authenticating user.
+    pub async fn delete_list(&self, id: ListId) -> Result<DeleteListResponse, Error> {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::ListWrite];
+
+        let url = format!("https://api.twitter.com/2/lists/{}", id);
+
+        let (data, limit_info) = self.action_request::<_, DeletedData>(
+            Request::new(Method::Delete, &url).with_scopes(SCOPES)
+        ).await?;
+
+        Ok(DeleteListResponse {
+            deleted: data.deleted,
+            limit_info,
+        })
+    }
+
+    /// Adds a user to a List owned by the authenticating user.
+    pub async fn add_list_member(
+        &self,
+        list_id: ListId,
+        user_id: UserId,
+    ) -> Result<ListMemberResponse, Error>
+    {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::ListWrite];
+
+        let url = format!("https://api.twitter.com/2/lists/{}/members", list_id);
+        let body = UserIdBody { user_id };
+
+        let (data, limit_info) = self.action_request::<_, IsMemberData>(
+            Request::new_with_data(Method::Post, &url, JsonData::new(&body))
+                .with_scopes(SCOPES)
+        ).await?;
+
+        Ok(ListMemberResponse {
+            is_member: data.is_member,
+            limit_info,
+        })
+    }
+
+    /// Removes a user from a List owned by the authenticating user.
+    pub async fn remove_list_member(
+        &self,
+        list_id: ListId,
+        user_id: UserId,
+    ) -> Result<ListMemberResponse, Error>
+    {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::ListWrite];
+
+        let url = format!("https://api.twitter.com/2/lists/{}/members/{}", list_id, user_id);
+
+        let (data, limit_info) = self.action_request::<_, IsMemberData>(
+            Request::new(Method::Delete, &url).with_scopes(SCOPES)
+        ).await?;
+
+        Ok(ListMemberResponse {
+            is_member: data.is_member,
+            limit_info,
+        })
+    }
+
+    /// Makes a request to an endpoint which performs an action and returns its outcome in
+    /// `data`.
+    async fn action_request<D, T>(&self, request: Request<'_, D>) -> Result<(T, LimitInfo), Error>
+    where
+        D: RequestData + Clone,
+        T: for<'de> Deserialize<'de>,
+    {
+        let (response, limit_info) = self.apiv2_request::<_, T>(request).await?;
+
+        let data = response
+            .data
+            .ok_or_else(|| ErrorRepr {
+                kind: ErrorKind::NoData,
+                limit_info: Some(limit_info.clone()),
+            }.boxed())?;
+
+        Ok((data, limit_info))
+    }
+}
+
+#[derive(Deserialize)]
+struct LikedData {
+    liked: bool,
+}
+
+#[derive(Debug)]
+pub struct LikeResponse {
+    /// Whether the user likes the Tweet after the request.
+    pub liked: bool,
+    pub limit_info: LimitInfo,
+}
+
+#[derive(Deserialize)]
+struct RetweetedData {
+    retweeted: bool,
+}
+
+#[derive(Debug)]
+pub struct RetweetResponse {
+    /// Whether the user has Retweeted the Tweet after the request.
+    pub retweeted: bool,
+    pub limit_info: LimitInfo,
+}
+
+// The unfollow endpoint only returns `following`.
+#[derive(Deserialize)]
+struct FollowingData {
+    following: bool,
+    #[serde(default)]
+    pending_follow: bool,
+}
+
+#[derive(Debug)]
+pub struct FollowResponse {
+    /// Whether the user follows the target user after the request.
+    pub following: bool,
+    /// Whether a request to follow the target user is awaiting their approval, which is the case
+    /// when following a protected user.
+    pub pending_follow: bool,
+    pub limit_info: LimitInfo,
+}
+
+#[derive(Deserialize)]
+struct BlockingData {
+    blocking: bool,
+}
+
+#[derive(Debug)]
+pub struct BlockResponse {
+    /// Whether the user blocks the target user after the request.
+    pub blocking: bool,
+    pub limit_info: LimitInfo,
+}
+
+#[derive(Deserialize)]
+struct CreatedListData {
+    id: ListId,
+    name: Box<str>,
+}
+
+#[derive(Debug)]
+pub struct CreateListResponse {
+    pub id: ListId,
+    pub name: Box<str>,
+    pub limit_info: LimitInfo,
+}
+
+#[derive(Deserialize)]
+struct UpdatedData {
+    updated: bool,
+}
+
+#[derive(Debug)]
+pub struct UpdateListResponse {
+    pub updated: bool,
+    pub limit_info: LimitInfo,
+}
+
+#[derive(Deserialize)]
+struct DeletedData {
+    deleted: bool,
+}
+
+#[derive(Debug)]
+pub struct DeleteListResponse {
+    pub deleted: bool,
+    pub limit_info: LimitInfo,
+}
+
+#[derive(Deserialize)]
+struct IsMemberData {
+    is_member: bool,
+}
+
+#[derive(Debug)]
+pub struct ListMemberResponse {
+    /// Whether the user is a member of the List after the request.
+    pub is_member: bool,
+    pub limit_info: LimitInfo,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FollowingData, ListUpdate};
+
+    #[test]
+    fn test_following_data() {
+        let data = serde_json::from_str::<FollowingData>(
+            r#"{"following": false, "pending_follow": true}"#
+        ).unwrap();
+        assert!(!data.following);
+        assert!(data.pending_follow);
+
+        let data = serde_json::from_str::<FollowingData>(r#"{"following": false}"#).unwrap();
+        assert!(!data.pending_follow);
+    }
+
+    #[test]
+    fn test_list_update_body() {
+        let update = ListUpdate::new().description("Rust news").private(true);
+        assert_eq!(
+            serde_json::to_string(&update).unwrap(),
+            r#"{"description":"Rust news","private":true}"#
+        );
+    }
+}
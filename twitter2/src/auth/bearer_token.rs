@@ -2,7 +2,7 @@ use std::borrow::Cow;
 
 use crate::{client::Request, request_data::RequestData};
 
-use super::{AppAuth, Auth};
+use super::{oauth20::basic_auth_header, AppAuth, Auth};
 
 /// An app-only bearer token which can be used to authenticate requests made on behalf of a
 /// [Twitter App](https://developer.twitter.com/en/docs/apps/overview). For example, this can be
@@ -49,3 +49,24 @@ impl Auth for BearerToken {
 }
 
 impl AppAuth for BearerToken {}
+
+/// An app's API key and API key secret, which can be exchanged for an app-only [`BearerToken`]
+/// using [`get_bearer_token`](crate::AsyncClient::get_bearer_token).
+#[derive(Clone)]
+pub struct ClientCredentials {
+    auth_header: Box<str>,
+}
+
+impl ClientCredentials {
+    pub fn new(api_key: &str, api_key_secret: &str) -> Self {
+        Self {
+            auth_header: basic_auth_header(api_key, api_key_secret),
+        }
+    }
+}
+
+impl Auth for ClientCredentials {
+    fn auth_header<D: RequestData>(&self, _request: &Request<D>) -> Cow<str> {
+        Cow::Borrowed(&self.auth_header)
+    }
+}
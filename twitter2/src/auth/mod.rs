@@ -1,15 +1,35 @@
 pub mod bearer_token;
 pub mod oauth10a;
+pub mod oauth20;
+pub mod scope;
 
-pub use bearer_token::BearerToken;
+pub use bearer_token::{BearerToken, ClientCredentials};
 pub use oauth10a::OAuth10a;
+pub use oauth20::{OAuth2, OAuth2Pkce};
+pub use scope::Scope;
 
 use std::borrow::Cow;
 
-use crate::{client::Request, request_data::RequestData};
+use crate::{client::Request, request_data::RequestData, user::UserId};
 
 pub trait Auth: sealed::Sealed {
+    /// The value of the `Authorization` header for the request. If this is empty, no
+    /// `Authorization` header is sent.
     fn auth_header<D: RequestData>(&self, request: &Request<D>) -> Cow<str>;
+
+    /// The OAuth 2.0 scopes these credentials have been granted, or `None` if the credentials are
+    /// not restricted by scope (as is the case for OAuth 1.0a and app-only bearer tokens).
+    ///
+    /// Requests which need a scope missing from this list fail before being sent.
+    fn granted_scopes(&self) -> Option<&[Scope]> {
+        None
+    }
+
+    /// Returns `false` if these credentials must not be used to authenticate a request to the
+    /// given URL. Such requests fail before being sent.
+    fn can_authenticate(&self, _base_url: &str) -> bool {
+        true
+    }
 }
 
 /// A trait for credentials that can be used to authenticate requests made on behalf of a
@@ -18,12 +38,20 @@ pub trait AppAuth: Auth {}
 
 /// A trait for credentials that can be used to authenticate requests made on behalf of a specific
 /// user.
-pub trait UserAuth: AppAuth {}
+pub trait UserAuth: AppAuth {
+    /// The id of the user these credentials act on behalf of, if it is known.
+    fn acting_user_id(&self) -> Option<UserId> {
+        None
+    }
+}
 
 mod sealed {
     pub trait Sealed {}
 
     impl Sealed for super::bearer_token::BearerToken {}
+    impl Sealed for super::bearer_token::ClientCredentials {}
     impl Sealed for super::oauth10a::OAuth10a {}
     impl Sealed for super::oauth10a::OAuth10aRequest {}
+    impl Sealed for super::oauth20::OAuth2 {}
+    impl Sealed for super::oauth20::OAuth2Pkce {}
 }
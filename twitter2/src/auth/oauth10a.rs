@@ -7,7 +7,7 @@ use libshire::{encoding::url::percent_encode, strings::CappedString};
 use rand::{distributions::Alphanumeric, CryptoRng, Rng};
 use sha1::Sha1;
 
-use crate::{client::Request, request_data::RequestData};
+use crate::{client::Request, request_data::{encode_param, RequestData}, user::UserId};
 
 use super::{AppAuth, Auth, UserAuth};
 
@@ -20,6 +20,8 @@ pub struct OAuth10a {
     api_key_encoded: Box<str>,
     access_token_encoded: Box<str>,
     signing_key: Box<str>,
+    signature_method: SignatureMethod,
+    user_id: Option<UserId>,
 }
 
 impl OAuth10a {
@@ -50,6 +52,38 @@ impl OAuth10a {
             api_key_encoded: percent_encode(api_key).into(),
             access_token_encoded: percent_encode(access_token).into(),
             signing_key,
+            signature_method: SignatureMethod::HmacSha1,
+            user_id: None,
+        }
+    }
+
+    /// Returns a new `OAuth10a` which records that its access token belongs to the user with the
+    /// given id. The id can then be retrieved with
+    /// [`acting_user_id`](crate::AsyncClient::acting_user_id), which is useful for endpoints that
+    /// need the authenticating user's id in their path.
+    #[must_use]
+    pub fn with_user_id(self, user_id: UserId) -> Self {
+        Self {
+            user_id: Some(user_id),
+            ..self
+        }
+    }
+
+    /// Returns a new `OAuth10a` which signs requests using the `PLAINTEXT` signature method, so
+    /// the signature is simply the signing key. This makes requests easy to verify by hand when
+    /// testing against a local mock server, which requests can be sent to with
+    /// [`AsyncClientBuilder::base_url`](crate::AsyncClientBuilder::base_url).
+    ///
+    /// `PLAINTEXT` signatures expose the API key secret and access token secret, so these
+    /// credentials refuse to authenticate requests to `twitter.com` or any of its subdomains.
+    /// Such requests fail with
+    /// [`ErrorKind::AuthNotAllowed`](crate::client::ErrorKind::AuthNotAllowed) without being
+    /// sent.
+    #[must_use]
+    pub fn with_plaintext_signature(self) -> Self {
+        Self {
+            signature_method: SignatureMethod::Plaintext,
+            ..self
         }
     }
 
@@ -82,9 +116,42 @@ impl OAuth10a {
             api_key_encoded: self.api_key_encoded.clone(),
             access_token_encoded: percent_encode(access_token).into(),
             signing_key,
+            signature_method: self.signature_method,
+            // The new access token may belong to a different user.
+            user_id: None,
         }
     }
 
+    /// Computes the signature for a request using the given nonce and timestamp, exactly as it
+    /// would be computed when the request is sent. This can be used to debug signature mismatches
+    /// by comparing against the signature computed by another tool.
+    ///
+    /// The nonce must already be percent-encoded.
+    pub fn debug_signature<D: RequestData>(
+        &self,
+        request: &Request<D>,
+        nonce_encoded: &str,
+        timestamp: i64,
+    ) -> String
+    {
+        self.signature(request, nonce_encoded, timestamp).into_string()
+    }
+
+    /// Computes the signature base string for a request using the given nonce and timestamp. This
+    /// is the string which is signed to produce the signature returned by
+    /// [`debug_signature`](Self::debug_signature).
+    ///
+    /// The nonce must already be percent-encoded.
+    pub fn debug_signature_base<D: RequestData>(
+        &self,
+        request: &Request<D>,
+        nonce_encoded: &str,
+        timestamp: i64,
+    ) -> String
+    {
+        self.signature_base(request, nonce_encoded, timestamp).into_string()
+    }
+
     fn parameter_string<D: RequestData>(
         &self,
         request: &Request<D>,
@@ -102,7 +169,7 @@ impl OAuth10a {
         params.insert((Cow::Borrowed("oauth_nonce"), Cow::Borrowed(nonce_encoded)));
         params.insert((
             Cow::Borrowed("oauth_signature_method"),
-            Cow::Borrowed("HMAC-SHA1"),
+            Cow::Borrowed(self.signature_method.as_str()),
         ));
         params.insert((
             Cow::Borrowed("oauth_timestamp"),
@@ -115,9 +182,17 @@ impl OAuth10a {
         params.insert((Cow::Borrowed("oauth_version"), Cow::Borrowed("1.0")));
 
         request.data().for_each_param(|key, val| {
-            params.insert((percent_encode(key), percent_encode(val)));
+            params.insert((encode_param(key), encode_param(val)));
         });
 
+        // Any parameters in the URL's query string are sent too, so they must also be signed.
+        for (key, val) in query_params(request.base_url()) {
+            params.insert((
+                Cow::Owned(encode_param(&key).into_owned()),
+                Cow::Owned(encode_param(&val).into_owned()),
+            ));
+        }
+
         let mut buf = String::new();
         for (key, val) in params {
             if !buf.is_empty() {
@@ -139,7 +214,7 @@ impl OAuth10a {
     ) -> Box<str>
     {
         let method = request.method_str();
-        let base_url_encoded = percent_encode(request.base_url());
+        let base_url_encoded = percent_encode(signing_base_url(request.base_url()));
         let parameter_string = self.parameter_string(request, nonce_encoded, timestamp);
         let parameter_string_encoded = percent_encode(&*parameter_string);
 
@@ -162,6 +237,10 @@ impl OAuth10a {
     {
         const BASE64_ENGINE: GeneralPurpose = base64::engine::general_purpose::STANDARD;
 
+        if self.signature_method == SignatureMethod::Plaintext {
+            return self.signing_key.clone();
+        }
+
         let base = self.signature_base(request, nonce_encoded, timestamp);
 
         // Compute `hmac_sha1(signing_key, base)`.
@@ -185,19 +264,28 @@ impl Auth for OAuth10a {
         let signature = self.signature(request, &nonce, timestamp);
 
         Cow::Owned(format!(
-            r#"OAuth oauth_consumer_key="{}", oauth_nonce="{}", oauth_signature="{}", oauth_signature_method="HMAC-SHA1", oauth_timestamp="{}", oauth_token="{}", oauth_version="1.0""#,
+            r#"OAuth oauth_consumer_key="{}", oauth_nonce="{}", oauth_signature="{}", oauth_signature_method="{}", oauth_timestamp="{}", oauth_token="{}", oauth_version="1.0""#,
             self.api_key_encoded,
             nonce,
             percent_encode(&*signature),
+            self.signature_method.as_str(),
             timestamp,
             self.access_token_encoded
         ))
     }
+
+    fn can_authenticate(&self, base_url: &str) -> bool {
+        self.signature_method != SignatureMethod::Plaintext || !is_twitter_url(base_url)
+    }
 }
 
 impl AppAuth for OAuth10a {}
 
-impl UserAuth for OAuth10a {}
+impl UserAuth for OAuth10a {
+    fn acting_user_id(&self) -> Option<UserId> {
+        self.user_id
+    }
+}
 
 #[derive(Clone)]
 pub struct OAuth10aRequest {
@@ -216,6 +304,63 @@ impl Auth for OAuth10aRequest {
     fn auth_header<D: RequestData>(&self, request: &Request<D>) -> Cow<str> {
         self.inner.auth_header(request)
     }
+
+    fn can_authenticate(&self, base_url: &str) -> bool {
+        self.inner.can_authenticate(base_url)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum SignatureMethod {
+    HmacSha1,
+    Plaintext,
+}
+
+impl SignatureMethod {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::HmacSha1 => "HMAC-SHA1",
+            Self::Plaintext => "PLAINTEXT",
+        }
+    }
+}
+
+/// Returns `true` if the URL's host is `twitter.com` or one of its subdomains. URLs which cannot
+/// be parsed are treated as Twitter URLs, to be safe.
+pub(crate) fn is_twitter_url(url: &str) -> bool {
+    const TWITTER_DOMAIN: &str = "twitter.com";
+
+    let url = match reqwest::Url::parse(url) {
+        Ok(url) => url,
+        Err(_) => return true,
+    };
+
+    url.host_str().map_or(true, |host| {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        host == TWITTER_DOMAIN || host.ends_with(&format!(".{}", TWITTER_DOMAIN))
+    })
+}
+
+/// Returns the URL without its query string or fragment, which is the form used in the signature
+/// base string.
+fn signing_base_url(url: &str) -> &str {
+    let end = url.find(['?', '#']).unwrap_or(url.len());
+    &url[..end]
+}
+
+/// Returns the decoded parameters in the URL's query string.
+fn query_params(url: &str) -> Vec<(String, String)> {
+    if !url.contains('?') {
+        return Vec::new();
+    }
+
+    match reqwest::Url::parse(url) {
+        Ok(url) => url
+            .query_pairs()
+            .map(|(key, val)| (key.into_owned(), val.into_owned()))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
 }
 
 fn gen_alphanumeric_nonce<R>(rng: &mut R) -> CappedString<NONCE_LEN>
@@ -236,7 +381,11 @@ where
 mod tests {
     use std::borrow::Cow;
 
-    use crate::{client::{Method, Request}, request_data::FormData};
+    use crate::{
+        auth::Auth,
+        client::{Method, Request},
+        request_data::{FormData, RequestData},
+    };
 
     use super::OAuth10a;
 
@@ -271,5 +420,101 @@ mod tests {
             &*auth.signature(&request, nonce, timestamp),
             "hCtSmYh+iHYCEqBWrE7C7hYmtUk="
         );
+
+        assert_eq!(
+            auth.debug_signature(&request, nonce, timestamp),
+            "hCtSmYh+iHYCEqBWrE7C7hYmtUk="
+        );
+    }
+
+    #[test]
+    fn test_signature_query_string() {
+        let api_key = "xvz1evFS4wEEPTGEFPHBog";
+        let api_key_secret = "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw";
+        let access_token = "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb";
+        let access_token_secret = "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE";
+
+        let auth = OAuth10a::new(api_key, api_key_secret, access_token, access_token_secret);
+
+        // The same request as in `test_signature`, but with `include_entities` in the URL's query
+        // string rather than the body, as in the original example from the Twitter documentation.
+        let base_url = "https://api.twitter.com/1.1/statuses/update.json?include_entities=true";
+        let data = FormData::new(&[
+            (
+                Cow::Borrowed("status"),
+                Cow::Borrowed("Hello Ladies + Gentlemen, a signed OAuth request!"),
+            ),
+        ]);
+
+        let request = Request::new_with_data(Method::Post, base_url, data);
+
+        let nonce = "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg";
+        let timestamp = 1318622958;
+
+        assert!(auth
+            .signature_base(&request, nonce, timestamp)
+            .starts_with("POST&https%3A%2F%2Fapi.twitter.com%2F1.1%2Fstatuses%2Fupdate.json&"));
+
+        assert_eq!(
+            &*auth.signature(&request, nonce, timestamp),
+            "hCtSmYh+iHYCEqBWrE7C7hYmtUk="
+        );
+    }
+
+    #[test]
+    fn test_signed_params_match_body() {
+        let auth = OAuth10a::new("api_key", "api_key_secret", "access_token", "access_token_secret");
+
+        let base_url = "https://api.twitter.com/1.1/statuses/update.json";
+
+        // The keys are given in sorted order so that the body is in the same order as the
+        // parameter string.
+        let params = [
+            (Cow::Borrowed("a key"), Cow::Borrowed("spaces, + plus & ampersand = equals")),
+            (Cow::Borrowed("b+key"), Cow::Borrowed("robo 🤖 ünïcödé")),
+            (Cow::Borrowed("status"), Cow::Borrowed("100% ~reserved~ *chars* /?#[]@!$'()")),
+        ];
+
+        let request = Request::new_with_data(Method::Post, base_url, FormData::new(&params));
+
+        let parameter_string = auth.parameter_string(&request, "nonce", 1318622958);
+
+        let signed_params = parameter_string
+            .split('&')
+            .filter(|param| !param.starts_with("oauth_"))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let http_request = FormData::new(&params)
+            .build_http_request(reqwest::Client::new().post(base_url))
+            .unwrap();
+
+        let body = http_request.body().and_then(|body| body.as_bytes()).unwrap();
+
+        assert_eq!(signed_params.as_bytes(), body);
+    }
+
+    #[test]
+    fn test_plaintext_signature() {
+        let auth = OAuth10a::new("api_key", "api+secret", "access_token", "access&secret")
+            .with_plaintext_signature();
+
+        let request = Request::new(Method::Get, "http://localhost:8080/2/users/me");
+
+        assert_eq!(
+            auth.debug_signature(&request, "nonce", 1318622958),
+            "api%2Bsecret&access%26secret"
+        );
+        assert!(auth.auth_header(&request).contains(r#"oauth_signature_method="PLAINTEXT""#));
+
+        assert!(auth.can_authenticate("http://localhost:8080/2/users/me"));
+        assert!(!auth.can_authenticate("https://api.twitter.com/2/users/me"));
+        assert!(!auth.can_authenticate("https://API.Twitter.com./2/users/me"));
+        assert!(!auth.can_authenticate("https://twitter.com/"));
+        assert!(!auth.can_authenticate("not a url"));
+        assert!(auth.can_authenticate("https://nottwitter.com/"));
+
+        let hmac_auth = OAuth10a::new("api_key", "api_secret", "access_token", "access_secret");
+        assert!(hmac_auth.can_authenticate("https://api.twitter.com/2/users/me"));
     }
 }
@@ -0,0 +1,350 @@
+use std::{borrow::Cow, fmt};
+
+use base64::{engine::GeneralPurpose, Engine};
+use chrono::{DateTime, Duration, Utc};
+use enumscribe::TryUnscribe;
+use rand::{distributions::Alphanumeric, CryptoRng, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    client::Request,
+    limit::LimitInfo,
+    request_data::{encode_param, RequestData},
+    user::UserId,
+};
+
+use super::{AppAuth, Auth, Scope, UserAuth};
+
+const VERIFIER_LEN: usize = 64;
+
+/// The credentials of an app using the
+/// [OAuth 2.0 authorization code flow with PKCE](https://developer.twitter.com/en/docs/authentication/oauth-2-0/authorization-code)
+/// to obtain user-context access tokens.
+///
+/// These credentials can only be used to obtain tokens, using
+/// [`authorize_url`](crate::AsyncClient::authorize_url) and
+/// [`exchange_code`](crate::AsyncClient::exchange_code). The resulting [`OAuth2`] credentials are
+/// used to make requests on behalf of the user.
+#[derive(Clone)]
+pub struct OAuth2Pkce {
+    client_id: Box<str>,
+    auth_header: Option<Box<str>>,
+}
+
+impl OAuth2Pkce {
+    /// Returns credentials for a public client, such as a native or single-page app, which has a
+    /// client id but cannot keep a client secret.
+    pub fn new_public(client_id: &str) -> Self {
+        Self {
+            client_id: client_id.into(),
+            auth_header: None,
+        }
+    }
+
+    /// Returns credentials for a confidential client, which authenticates to the token endpoint
+    /// with its client secret.
+    pub fn new_confidential(client_id: &str, client_secret: &str) -> Self {
+        Self {
+            client_id: client_id.into(),
+            auth_header: Some(basic_auth_header(client_id, client_secret)),
+        }
+    }
+
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+}
+
+impl Auth for OAuth2Pkce {
+    fn auth_header<D: RequestData>(&self, _request: &Request<D>) -> Cow<str> {
+        // Public clients identify themselves with the `client_id` parameter instead.
+        Cow::Borrowed(self.auth_header.as_deref().unwrap_or_default())
+    }
+}
+
+/// An OAuth 2.0 user-context access token, which can be used to authenticate requests made on
+/// behalf of a specific user.
+#[derive(Clone)]
+pub struct OAuth2 {
+    auth_header: Box<str>,
+    scopes: Option<Box<[Scope]>>,
+    user_id: Option<UserId>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl OAuth2 {
+    /// Returns new `OAuth2` credentials using the given access token. The token is assumed to have
+    /// every scope needed, so requests are sent without checking; use
+    /// [`with_scopes`](Self::with_scopes) if the granted scopes are known.
+    pub fn new(access_token: &str) -> Self {
+        Self {
+            auth_header: format!("Bearer {}", access_token).into_boxed_str(),
+            scopes: None,
+            user_id: None,
+            expires_at: None,
+        }
+    }
+
+    /// Returns new `OAuth2` credentials which record the scopes granted to the access token.
+    /// Requests to endpoints needing any other scope will fail with
+    /// [`ErrorKind::MissingScope`](crate::client::ErrorKind::MissingScope) without being sent.
+    #[must_use]
+    pub fn with_scopes<I>(self, scopes: I) -> Self
+    where
+        I: IntoIterator<Item = Scope>,
+    {
+        Self {
+            scopes: Some(scopes.into_iter().collect()),
+            ..self
+        }
+    }
+
+    /// Returns new `OAuth2` credentials which record that the access token belongs to the user
+    /// with the given id.
+    #[must_use]
+    pub fn with_user_id(self, user_id: UserId) -> Self {
+        Self {
+            user_id: Some(user_id),
+            ..self
+        }
+    }
+
+    /// Returns new `OAuth2` credentials which record when the access token expires.
+    #[must_use]
+    pub fn with_expires_at(self, expires_at: DateTime<Utc>) -> Self {
+        Self {
+            expires_at: Some(expires_at),
+            ..self
+        }
+    }
+
+    /// The time at which the access token expires, if it is known.
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+    }
+
+    /// Returns `true` if the access token is known to have expired, in which case it should be
+    /// replaced using [`refresh`](crate::AsyncClient::refresh).
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.map_or(false, |expires_at| expires_at <= Utc::now())
+    }
+}
+
+impl Auth for OAuth2 {
+    fn auth_header<D: RequestData>(&self, _request: &Request<D>) -> Cow<str> {
+        Cow::Borrowed(&self.auth_header)
+    }
+
+    fn granted_scopes(&self) -> Option<&[Scope]> {
+        self.scopes.as_deref()
+    }
+}
+
+impl AppAuth for OAuth2 {}
+
+impl UserAuth for OAuth2 {
+    fn acting_user_id(&self) -> Option<UserId> {
+        self.user_id
+    }
+}
+
+/// The PKCE code verifier for an authorization request. It must be kept until the user is
+/// redirected back to the app, then passed to
+/// [`exchange_code`](crate::AsyncClient::exchange_code) along with the authorization code. It can
+/// be serialised to store it in the meantime.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct PkceVerifier(Box<str>);
+
+impl PkceVerifier {
+    /// Generates a new random code verifier.
+    pub fn new_random() -> Self {
+        Self::generate(&mut rand::thread_rng())
+    }
+
+    fn generate<R>(rng: &mut R) -> Self
+    where
+        R: Rng + CryptoRng + ?Sized,
+    {
+        let verifier = (0..VERIFIER_LEN)
+            .map(|_| char::from(rng.sample(Alphanumeric)))
+            .collect::<String>();
+
+        Self(verifier.into_boxed_str())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The `S256` code challenge for this verifier, which is sent in the authorization URL.
+    pub fn challenge(&self) -> String {
+        const BASE64_ENGINE: GeneralPurpose = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        BASE64_ENGINE.encode(Sha256::digest(self.0.as_bytes()))
+    }
+}
+
+// The verifier is a secret, so don't print it.
+impl fmt::Debug for PkceVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("PkceVerifier(..)")
+    }
+}
+
+/// An access token obtained from the OAuth 2.0 token endpoint.
+#[derive(Clone)]
+pub struct OAuth2Token {
+    pub access_token: Box<str>,
+    /// A token which can be used to obtain a new access token once this one expires. Only
+    /// returned if the [`Scope::OfflineAccess`] scope was requested.
+    pub refresh_token: Option<Box<str>>,
+    /// The time at which the access token expires.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// The scopes granted to the access token, or `None` if Twitter did not say which scopes were
+    /// granted.
+    pub scopes: Option<Box<[Scope]>>,
+    pub limit_info: LimitInfo,
+}
+
+impl OAuth2Token {
+    /// Returns credentials which use the access token to authenticate requests.
+    pub fn to_auth(&self) -> OAuth2 {
+        let auth = OAuth2::new(&self.access_token);
+
+        let auth = match self.scopes.as_deref() {
+            Some(scopes) => auth.with_scopes(scopes.iter().copied()),
+            None => auth,
+        };
+
+        match self.expires_at {
+            Some(expires_at) => auth.with_expires_at(expires_at),
+            None => auth,
+        }
+    }
+
+    pub(crate) fn from_response(response: TokenResponse, limit_info: LimitInfo) -> Self {
+        let expires_at = response
+            .expires_in
+            .and_then(|secs| i64::try_from(secs).ok())
+            .map(|secs| Utc::now() + Duration::seconds(secs));
+
+        // Ignore any scopes this version of the library doesn't know about.
+        let scopes = response.scope.as_deref().map(|scope| {
+            scope
+                .split_whitespace()
+                .filter_map(Scope::try_unscribe)
+                .collect()
+        });
+
+        Self {
+            access_token: response.access_token,
+            refresh_token: response.refresh_token,
+            expires_at,
+            scopes,
+            limit_info,
+        }
+    }
+}
+
+// The tokens are secrets, so don't print them.
+impl fmt::Debug for OAuth2Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OAuth2Token")
+            .field("expires_at", &self.expires_at)
+            .field("scopes", &self.scopes)
+            .field("limit_info", &self.limit_info)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct TokenResponse {
+    access_token: Box<str>,
+    refresh_token: Option<Box<str>>,
+    expires_in: Option<u64>,
+    scope: Option<Box<str>>,
+}
+
+/// The error body returned by the OAuth 2.0 token endpoint.
+#[derive(Deserialize)]
+pub(crate) struct TokenErrorResponse {
+    pub error: Box<str>,
+    pub error_description: Option<Box<str>>,
+}
+
+/// Builds an HTTP Basic `Authorization` header from a client id and secret.
+pub(crate) fn basic_auth_header(client_id: &str, client_secret: &str) -> Box<str> {
+    const BASE64_ENGINE: GeneralPurpose = base64::engine::general_purpose::STANDARD;
+
+    // RFC 6749 requires the id and secret to be form-urlencoded before they are joined.
+    let credentials = format!("{}:{}", encode_param(client_id), encode_param(client_secret));
+
+    format!("Basic {}", BASE64_ENGINE.encode(credentials)).into_boxed_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::{OAuth2, OAuth2Token, PkceVerifier, TokenResponse};
+    use crate::{auth::{Auth, Scope}, limit::LimitInfo};
+
+    #[test]
+    fn test_pkce_challenge() {
+        // The example from RFC 7636, appendix B.
+        let verifier = PkceVerifier("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".into());
+        assert_eq!(verifier.challenge(), "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+
+        let verifier = PkceVerifier::new_random();
+        assert_eq!(verifier.as_str().len(), 64);
+        assert!(verifier.as_str().bytes().all(|b| b.is_ascii_alphanumeric()));
+    }
+
+    #[test]
+    fn test_token_response() {
+        let json = r#"{
+            "token_type": "bearer",
+            "expires_in": 7200,
+            "access_token": "abc",
+            "scope": "tweet.read users.read offline.access unknown.scope",
+            "refresh_token": "def"
+        }"#;
+
+        let response = serde_json::from_str::<TokenResponse>(json).unwrap();
+        let token = OAuth2Token::from_response(response, LimitInfo::empty());
+        assert_eq!(&*token.access_token, "abc");
+        assert_eq!(token.refresh_token.as_deref(), Some("def"));
+        assert!(token.expires_at.is_some());
+        assert_eq!(
+            token.scopes.as_deref(),
+            Some(&[Scope::TweetRead, Scope::UsersRead, Scope::OfflineAccess][..])
+        );
+        assert!(!token.to_auth().is_expired());
+    }
+
+    #[test]
+    fn test_token_response_without_scope() {
+        let json = r#"{"token_type": "bearer", "access_token": "abc"}"#;
+
+        let response = serde_json::from_str::<TokenResponse>(json).unwrap();
+        let token = OAuth2Token::from_response(response, LimitInfo::empty());
+        assert_eq!(token.scopes, None);
+
+        // Without a scope string the granted scopes are unknown, so requests are not checked
+        // against them.
+        assert_eq!(token.to_auth().granted_scopes(), None);
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let auth = OAuth2::new("abc");
+        assert!(!auth.is_expired());
+
+        let auth = auth.with_expires_at(Utc::now() - Duration::seconds(1));
+        assert!(auth.is_expired());
+
+        let auth = auth.with_expires_at(Utc::now() + Duration::hours(2));
+        assert!(!auth.is_expired());
+    }
+}
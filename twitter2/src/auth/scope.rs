@@ -0,0 +1,55 @@
+use enumscribe::{ScribeStaticStr, TryUnscribe};
+
+/// An [OAuth 2.0 scope](https://developer.twitter.com/en/docs/authentication/oauth-2-0/authorization-code)
+/// which grants a user-context access token permission to use a particular set of endpoints.
+#[derive(ScribeStaticStr, TryUnscribe, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Scope {
+    #[enumscribe(str = "tweet.read")]
+    TweetRead,
+    #[enumscribe(str = "tweet.write")]
+    TweetWrite,
+    #[enumscribe(str = "tweet.moderate.write")]
+    TweetModerateWrite,
+    #[enumscribe(str = "users.read")]
+    UsersRead,
+    #[enumscribe(str = "follows.read")]
+    FollowsRead,
+    #[enumscribe(str = "follows.write")]
+    FollowsWrite,
+    #[enumscribe(str = "offline.access")]
+    OfflineAccess,
+    #[enumscribe(str = "space.read")]
+    SpaceRead,
+    #[enumscribe(str = "mute.read")]
+    MuteRead,
+    #[enumscribe(str = "mute.write")]
+    MuteWrite,
+    #[enumscribe(str = "like.read")]
+    LikeRead,
+    #[enumscribe(str = "like.write")]
+    LikeWrite,
+    #[enumscribe(str = "list.read")]
+    ListRead,
+    #[enumscribe(str = "list.write")]
+    ListWrite,
+    #[enumscribe(str = "block.read")]
+    BlockRead,
+    #[enumscribe(str = "block.write")]
+    BlockWrite,
+    #[enumscribe(str = "bookmark.read")]
+    BookmarkRead,
+    #[enumscribe(str = "bookmark.write")]
+    BookmarkWrite,
+    #[enumscribe(str = "dm.read")]
+    DmRead,
+    #[enumscribe(str = "dm.write")]
+    DmWrite,
+    #[enumscribe(str = "media.write")]
+    MediaWrite,
+}
+
+impl Scope {
+    pub fn as_str(self) -> &'static str {
+        self.scribe()
+    }
+}
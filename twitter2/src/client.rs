@@ -1,42 +1,99 @@
-use std::{borrow::Cow, str, sync::Arc, time::Duration};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    error,
+    fmt,
+    str,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use enumscribe::ScribeStaticStr;
-use libshire::{
-    encoding::url::{percent_decode_utf8, percent_encode, FormDecode},
-};
+use libshire::encoding::url::percent_encode;
 use reqwest::{header::{HeaderValue, AUTHORIZATION}, StatusCode};
-use serde::Deserialize;
+use serde::{de::IgnoredAny, Deserialize};
 
 use crate::{
-    auth::{oauth10a::OAuth10aRequest, Auth, OAuth10a, AppAuth},
+    auth::{
+        BearerToken,
+        ClientCredentials,
+        oauth10a::{is_twitter_url, OAuth10aRequest},
+        oauth20::{OAuth2Token, PkceVerifier, TokenErrorResponse, TokenResponse},
+        Auth,
+        OAuth10a,
+        OAuth2Pkce,
+        AppAuth,
+        UserAuth,
+        Scope,
+    },
     response::{ApiV2Response, ResponseError},
     limit::LimitInfo,
-    request_data::{FormData, RequestData}
+    query,
+    request_data::{FormData, RequestData},
+    user::UserId,
+    username_cache::UsernameCache,
 };
 
 #[derive(Clone)]
 pub struct AsyncClient<A> {
     http_client: reqwest::Client,
     auth: Arc<A>,
+    on_response: Option<ResponseHook>,
+    username_cache: Option<Arc<Mutex<UsernameCache>>>,
+    // The id of the authenticating user, once it has been looked up with `/2/users/me`.
+    acting_user_id: Arc<Mutex<Option<UserId>>>,
+    timeout: Option<Duration>,
+    rate_limit_retries: u32,
+    // Replaces `https://api.twitter.com` in request URLs, if set.
+    base_url: Option<Arc<str>>,
+}
+
+type ResponseHook = Arc<dyn Fn(&ResponseMetrics<'_>) + Send + Sync>;
+
+/// Information about a completed request, which is passed to the callback set with
+/// [`AsyncClientBuilder::on_response`].
+#[derive(Debug)]
+pub struct ResponseMetrics<'a> {
+    /// The URL of the endpoint, without any query string.
+    pub endpoint: &'a str,
+    pub method: Method,
+    /// The HTTP status of the response, or `None` if no response was received.
+    pub status: Option<StatusCode>,
+    /// The time taken to receive the response headers.
+    pub duration: Duration,
+    /// The rate limit information from the response, or `None` if no response was received.
+    pub limit_info: Option<&'a LimitInfo>,
 }
 
 impl<A: Auth> AsyncClient<A> {
     pub fn new(auth: A, timeout: Option<Duration>) -> Result<Self, reqwest::Error> {
-        let builder = reqwest::Client::builder()
-            .min_tls_version(reqwest::tls::Version::TLS_1_2)
-            .https_only(true);
+        let builder = Self::builder(auth);
 
         let builder = match timeout {
             Some(timeout) => builder.timeout(timeout),
             None => builder,
         };
 
-        let http_client = builder.build()?;
+        builder.build()
+    }
 
-        Ok(Self {
-            http_client,
-            auth: Arc::new(auth),
-        })
+    /// Returns a builder which can be used to configure the underlying HTTP client before
+    /// creating a new `AsyncClient`.
+    #[inline]
+    #[must_use]
+    pub fn builder(auth: A) -> AsyncClientBuilder<A> {
+        AsyncClientBuilder::new(auth)
+    }
+
+    /// Returns a client which retries rate-limited requests up to `max_retries` times. See
+    /// [`AsyncClientBuilder::rate_limit_retry`] for details.
+    #[inline]
+    #[must_use]
+    pub fn with_rate_limit_retry(self, max_retries: u32) -> Self {
+        Self {
+            rate_limit_retries: max_retries,
+            ..self
+        }
     }
 
     /// Consumes this client and returns a new client using the given authentication credentials.
@@ -44,6 +101,12 @@ impl<A: Auth> AsyncClient<A> {
         AsyncClient {
             http_client: self.http_client,
             auth: Arc::new(auth),
+            on_response: self.on_response,
+            username_cache: self.username_cache,
+            acting_user_id: Arc::new(Mutex::new(None)),
+            timeout: self.timeout,
+            rate_limit_retries: self.rate_limit_retries,
+            base_url: self.base_url,
         }
     }
 
@@ -53,6 +116,12 @@ impl<A: Auth> AsyncClient<A> {
         AsyncClient {
             http_client: self.http_client.clone(),
             auth: Arc::new(auth),
+            on_response: self.on_response.clone(),
+            username_cache: self.username_cache.clone(),
+            acting_user_id: Arc::new(Mutex::new(None)),
+            timeout: self.timeout,
+            rate_limit_retries: self.rate_limit_retries,
+            base_url: self.base_url.clone(),
         }
     }
 
@@ -63,44 +132,418 @@ impl<A: Auth> AsyncClient<A> {
     where
         ReqData: RequestData,
     {
+        let url = match &self.base_url {
+            Some(base_url) => rebase_url(request.base_url, base_url),
+            None => Cow::Borrowed(request.base_url),
+        };
+        let request = Request { base_url: &url, ..request };
+
+        // Fail early if the credentials are scoped and are missing a scope the endpoint needs,
+        // rather than letting Twitter respond with a 403.
+        if let Some(granted_scopes) = self.auth.granted_scopes() {
+            let missing_scope = request
+                .scopes
+                .iter()
+                .copied()
+                .find(|scope| !granted_scopes.contains(scope));
+
+            if let Some(missing_scope) = missing_scope {
+                return Err(ErrorRepr {
+                    kind: ErrorKind::MissingScope(missing_scope),
+                    limit_info: None,
+                }.boxed());
+            }
+        }
+
+        if !self.auth.can_authenticate(request.base_url) {
+            return Err(ErrorRepr {
+                kind: ErrorKind::AuthNotAllowed,
+                limit_info: None,
+            }.boxed());
+        }
+
         let auth_header = {
             let auth_string = self.auth.auth_header(&request);
-            // FIXME: might be better to just panic if this fails
-            let mut auth_header = HeaderValue::from_str(&auth_string)
-                .map_err(|_| ErrorRepr {
-                    kind: ErrorKind::BadAuthHeader,
-                    limit_info: None,
-                }.boxed())?;
-            auth_header.set_sensitive(true);
-            auth_header
+            if auth_string.is_empty() {
+                None
+            } else {
+                // FIXME: might be better to just panic if this fails
+                let mut auth_header = HeaderValue::from_str(&auth_string)
+                    .map_err(|_| ErrorRepr {
+                        kind: ErrorKind::BadAuthHeader,
+                        limit_info: None,
+                    }.boxed())?;
+                auth_header.set_sensitive(true);
+                Some(auth_header)
+            }
         };
 
+        let (method, endpoint) = (request.method, request.base_url);
+
         let request = {
             let builder = self
                 .http_client
-                .request(request.method.to_reqwest_method(), request.base_url)
-                .header(AUTHORIZATION, auth_header);
+                .request(request.method.to_reqwest_method(), request.base_url);
+
+            let builder = match auth_header {
+                Some(auth_header) => builder.header(AUTHORIZATION, auth_header),
+                None => builder,
+            };
 
             request
                 .data
                 .build_http_request(builder)
                 .map_err(|err| ErrorRepr {
-                    kind: ErrorKind::Transfer(err),
+                    kind: ErrorKind::from_reqwest(err),
                     limit_info: None,
                 }.boxed())?
         };
 
-        self.http_client
-            .execute(request)
-            .await
-            .map_err(|err| ErrorRepr {
-                kind: ErrorKind::Transfer(err),
-                limit_info: None,
-            }.boxed())
-            .map(|resp| {
-                let limit_info = LimitInfo::from_headers(resp.headers());
-                (resp, limit_info)
-            })
+        let start = Instant::now();
+        let result = self.http_client.execute(request).await;
+        let duration = start.elapsed();
+
+        let result = result.map(|resp| {
+            let limit_info = LimitInfo::from_headers(resp.headers());
+            (resp, limit_info)
+        });
+
+        if let Some(on_response) = &self.on_response {
+            let (status, limit_info) = match &result {
+                Ok((resp, limit_info)) => (Some(resp.status()), Some(limit_info)),
+                Err(_) => (None, None),
+            };
+
+            on_response(&ResponseMetrics {
+                endpoint,
+                method,
+                status,
+                duration,
+                limit_info,
+            });
+        }
+
+        result.map_err(|err| ErrorRepr {
+            kind: ErrorKind::from_reqwest(err),
+            limit_info: None,
+        }.boxed())
+    }
+}
+
+/// Credentials for many accounts which all share a single HTTP connection pool.
+///
+/// Each account's credentials are stored once, and [`client_for`](Self::client_for) returns an
+/// [`AsyncClient`] for an account without allocating, so requests can be fanned out across many
+/// accounts cheaply.
+pub struct MultiAuthClient<A> {
+    http_client: reqwest::Client,
+    accounts: HashMap<UserId, Arc<A>>,
+    on_response: Option<ResponseHook>,
+    username_cache: Option<Arc<Mutex<UsernameCache>>>,
+    timeout: Option<Duration>,
+    rate_limit_retries: u32,
+    base_url: Option<Arc<str>>,
+}
+
+impl<A: Auth> MultiAuthClient<A> {
+    /// Returns a new `MultiAuthClient` with no accounts, which uses the same HTTP connection pool
+    /// as the given client.
+    pub fn new<B>(client: &AsyncClient<B>) -> Self {
+        Self {
+            http_client: client.http_client.clone(),
+            accounts: HashMap::new(),
+            on_response: client.on_response.clone(),
+            username_cache: client.username_cache.clone(),
+            timeout: client.timeout,
+            rate_limit_retries: client.rate_limit_retries,
+            base_url: client.base_url.clone(),
+        }
+    }
+
+    /// Adds the credentials for an account, returning `true` if they replaced existing credentials
+    /// for the same account.
+    pub fn insert(&mut self, user_id: UserId, auth: A) -> bool {
+        self.accounts.insert(user_id, Arc::new(auth)).is_some()
+    }
+
+    /// Removes the credentials for an account, returning `true` if there were any.
+    pub fn remove(&mut self, user_id: UserId) -> bool {
+        self.accounts.remove(&user_id).is_some()
+    }
+
+    pub fn contains(&self, user_id: UserId) -> bool {
+        self.accounts.contains_key(&user_id)
+    }
+
+    /// Returns a client which makes requests on behalf of the given account, or `None` if there
+    /// are no credentials for the account.
+    pub fn client_for(&self, user_id: UserId) -> Option<AsyncClient<A>> {
+        self.accounts.get(&user_id).map(|auth| AsyncClient {
+            http_client: self.http_client.clone(),
+            auth: auth.clone(),
+            on_response: self.on_response.clone(),
+            username_cache: self.username_cache.clone(),
+            acting_user_id: Arc::new(Mutex::new(Some(user_id))),
+            timeout: self.timeout,
+            rate_limit_retries: self.rate_limit_retries,
+            base_url: self.base_url.clone(),
+        })
+    }
+}
+
+pub struct AsyncClientBuilder<A> {
+    auth: A,
+    timeout: Option<Duration>,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Duration>,
+    http2_keep_alive_interval: Option<Duration>,
+    http2_keep_alive_timeout: Option<Duration>,
+    on_response: Option<ResponseHook>,
+    username_cache_capacity: Option<usize>,
+    rate_limit_retries: u32,
+    base_url: Option<Arc<str>>,
+}
+
+impl<A: Auth> AsyncClientBuilder<A> {
+    #[inline]
+    #[must_use]
+    pub fn new(auth: A) -> Self {
+        Self {
+            auth,
+            timeout: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+            http2_keep_alive_interval: None,
+            http2_keep_alive_timeout: None,
+            on_response: None,
+            username_cache_capacity: None,
+            rate_limit_retries: 0,
+            base_url: None,
+        }
+    }
+
+    /// Sets the timeout for each request, from when the connection is first made to when the
+    /// response body has been read. By default, there is no timeout.
+    #[inline]
+    #[must_use]
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of idle connections per host that will be kept in the connection
+    /// pool. By default, there is no maximum.
+    #[inline]
+    #[must_use]
+    pub fn pool_max_idle_per_host(self, max: usize) -> Self {
+        Self {
+            pool_max_idle_per_host: Some(max),
+            ..self
+        }
+    }
+
+    /// Sets how long an idle connection will be kept in the connection pool before it is closed.
+    /// By default, this is 90 seconds.
+    #[inline]
+    #[must_use]
+    pub fn pool_idle_timeout(self, timeout: Duration) -> Self {
+        Self {
+            pool_idle_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Sets the interval at which HTTP/2 keep-alive pings are sent. This allows a dropped
+    /// connection, such as a stalled stream, to be detected without waiting for the request
+    /// timeout. By default, no pings are sent.
+    #[inline]
+    #[must_use]
+    pub fn http2_keep_alive_interval(self, interval: Duration) -> Self {
+        Self {
+            http2_keep_alive_interval: Some(interval),
+            ..self
+        }
+    }
+
+    /// Sets how long to wait for an acknowledgement of an HTTP/2 keep-alive ping before the
+    /// connection is closed. This has no effect unless
+    /// [`http2_keep_alive_interval`](Self::http2_keep_alive_interval) is also set. By default,
+    /// this is 20 seconds.
+    #[inline]
+    #[must_use]
+    pub fn http2_keep_alive_timeout(self, timeout: Duration) -> Self {
+        Self {
+            http2_keep_alive_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Sets a callback which is called after every request the client makes, which can be used to
+    /// record metrics. The callback should return quickly, as it is called before the response is
+    /// processed.
+    #[inline]
+    #[must_use]
+    pub fn on_response<F>(self, on_response: F) -> Self
+    where
+        F: Fn(&ResponseMetrics<'_>) + Send + Sync + 'static,
+    {
+        Self {
+            on_response: Some(Arc::new(on_response)),
+            ..self
+        }
+    }
+
+    /// Enables caching of the user ids looked up by
+    /// [`AsyncClient::resolve_username`](AsyncClient::resolve_username), keeping at most
+    /// `capacity` usernames. When the cache is full, the least recently used username is evicted.
+    /// By default, nothing is cached.
+    #[inline]
+    #[must_use]
+    pub fn username_cache(self, capacity: usize) -> Self {
+        Self {
+            username_cache_capacity: Some(capacity),
+            ..self
+        }
+    }
+
+    /// Retries requests which are rejected with HTTP 429 up to `max_retries` times, waiting until
+    /// the rate limit resets before each retry. A request is not retried if Twitter did not say
+    /// when the rate limit resets, or if the limit would not reset until after the
+    /// [`timeout`](Self::timeout) has elapsed since the first attempt, so the timeout bounds the
+    /// request and all of its retries together. Without a timeout, a request is not retried past
+    /// 15 minutes after the first attempt. By default, rate-limited requests are not retried.
+    #[inline]
+    #[must_use]
+    pub fn rate_limit_retry(self, max_retries: u32) -> Self {
+        Self {
+            rate_limit_retries: max_retries,
+            ..self
+        }
+    }
+
+    /// Sends requests for `https://api.twitter.com` to the given base URL instead, such as a local
+    /// mock server when testing. For example, with a base URL of `http://localhost:8080`, a request
+    /// for `https://api.twitter.com/2/users/me` is sent to `http://localhost:8080/2/users/me`.
+    /// Requests are signed for the URL they are sent to.
+    ///
+    /// Returns an error if the base URL is not an `https` URL. Plain `http` is only allowed for
+    /// hosts other than `twitter.com` and its subdomains, so that credentials are never sent to
+    /// Twitter unencrypted.
+    pub fn base_url(self, base_url: &str) -> Result<Self, BaseUrlError> {
+        let url = reqwest::Url::parse(base_url).map_err(|_| BaseUrlError::new(base_url))?;
+
+        let allowed = match url.scheme() {
+            "https" => true,
+            "http" => !is_twitter_url(base_url),
+            _ => false,
+        };
+
+        if !allowed || url.query().is_some() || url.fragment().is_some() {
+            return Err(BaseUrlError::new(base_url));
+        }
+
+        Ok(Self {
+            // The parsed URL has a lowercase scheme, which `build` relies on.
+            base_url: Some(url.as_str().trim_end_matches('/').into()),
+            ..self
+        })
+    }
+
+    pub fn build(self) -> Result<AsyncClient<A>, reqwest::Error> {
+        // `base_url` only accepts plain `http` for hosts other than Twitter's.
+        let https_only = match self.base_url.as_deref() {
+            Some(base_url) => base_url.starts_with("https:"),
+            None => true,
+        };
+
+        let builder = reqwest::Client::builder()
+            .min_tls_version(reqwest::tls::Version::TLS_1_2)
+            .https_only(https_only);
+
+        let builder = match self.timeout {
+            Some(timeout) => builder.timeout(timeout),
+            None => builder,
+        };
+
+        let builder = match self.pool_max_idle_per_host {
+            Some(max) => builder.pool_max_idle_per_host(max),
+            None => builder,
+        };
+
+        let builder = match self.pool_idle_timeout {
+            Some(timeout) => builder.pool_idle_timeout(timeout),
+            None => builder,
+        };
+
+        let builder = match self.http2_keep_alive_interval {
+            Some(interval) => builder.http2_keep_alive_interval(interval),
+            None => builder,
+        };
+
+        let builder = match self.http2_keep_alive_timeout {
+            Some(timeout) => builder.http2_keep_alive_timeout(timeout),
+            None => builder,
+        };
+
+        let http_client = builder.build()?;
+
+        Ok(AsyncClient {
+            http_client,
+            auth: Arc::new(self.auth),
+            on_response: self.on_response,
+            username_cache: self.username_cache_capacity
+                .map(|capacity| Arc::new(Mutex::new(UsernameCache::new(capacity)))),
+            acting_user_id: Arc::new(Mutex::new(None)),
+            timeout: self.timeout,
+            rate_limit_retries: self.rate_limit_retries,
+            base_url: self.base_url,
+        })
+    }
+}
+
+/// An error indicating that a URL cannot be used as the base URL of an [`AsyncClient`]. See
+/// [`AsyncClientBuilder::base_url`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BaseUrlError {
+    base_url: Box<str>,
+}
+
+impl BaseUrlError {
+    fn new(base_url: &str) -> Self {
+        Self { base_url: base_url.into() }
+    }
+
+    /// The base URL that was rejected.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+}
+
+impl fmt::Display for BaseUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid base URL {:?}: must be an https URL, or an http URL for a host other than \
+            twitter.com",
+            self.base_url
+        )
+    }
+}
+
+impl error::Error for BaseUrlError {}
+
+/// Replaces the `https://api.twitter.com` prefix of a request URL with the client's base URL.
+/// URLs for other hosts, such as `upload.twitter.com`, are left unchanged.
+fn rebase_url<'a>(url: &'a str, base_url: &str) -> Cow<'a, str> {
+    const TWITTER_API_BASE_URL: &str = "https://api.twitter.com";
+
+    match url.strip_prefix(TWITTER_API_BASE_URL) {
+        Some(path) if path.is_empty() || path.starts_with(['/', '?']) => {
+            Cow::Owned(format!("{}{}", base_url, path))
+        },
+        _ => Cow::Borrowed(url),
     }
 }
 
@@ -109,6 +552,34 @@ impl<A: AppAuth> AsyncClient<A> {
         &self,
         request: Request<'req, ReqData>
     ) -> Result<(ApiV2Response<RespData>, LimitInfo), Error>
+    where
+        ReqData: RequestData + Clone,
+        RespData: for<'de> Deserialize<'de>,
+    {
+        // The deadline covers every attempt and every wait between them, so that retrying does
+        // not multiply the time a request can take.
+        let deadline = Instant::now() + self.timeout.unwrap_or(DEFAULT_MAX_RATE_LIMIT_WAIT);
+        let mut retries = 0;
+
+        loop {
+            let err = match self.apiv2_request_once(request.clone()).await {
+                Err(err) if retries < self.rate_limit_retries && err.is_rate_limited() => err,
+                result => return result,
+            };
+
+            match rate_limit_wait(&err, deadline) {
+                Some(wait) => tokio::time::sleep(wait).await,
+                None => return Err(err),
+            }
+
+            retries += 1;
+        }
+    }
+
+    async fn apiv2_request_once<'req, ReqData, RespData>(
+        &self,
+        request: Request<'req, ReqData>
+    ) -> Result<(ApiV2Response<RespData>, LimitInfo), Error>
     where
         ReqData: RequestData,
         RespData: for<'de> Deserialize<'de>,
@@ -121,28 +592,312 @@ impl<A: AppAuth> AsyncClient<A> {
             .bytes()
             .await
             .map_err(|err| ErrorRepr {
-                kind: ErrorKind::Transfer(err),
+                kind: ErrorKind::from_reqwest(err),
                 limit_info: Some(limit_info.clone()),
             }.boxed())?;
-        
-        // Attempt to deserialise the response body from JSON.
-        let apiv2_response = serde_json::from_slice::<ApiV2Response<RespData>>(&body)
+
+        parse_apiv2_response(status, &body, limit_info)
+    }
+
+    /// Makes a request to a v1.1 endpoint, which returns its result as a plain JSON object rather
+    /// than in a `data` field. Returns `None` if a successful response has no body, as is the case
+    /// for some endpoints such as the media upload `APPEND` command.
+    pub(crate) async fn apiv1_request<'req, ReqData, RespData>(
+        &self,
+        request: Request<'req, ReqData>
+    ) -> Result<(Option<RespData>, LimitInfo), Error>
+    where
+        ReqData: RequestData,
+        RespData: for<'de> Deserialize<'de>,
+    {
+        let (resp, limit_info) = self.raw_request(request).await?;
+
+        let status = resp.status();
+
+        let body = resp
+            .bytes()
+            .await
             .map_err(|err| ErrorRepr {
+                kind: ErrorKind::from_reqwest(err),
+                limit_info: Some(limit_info.clone()),
+            }.boxed())?;
+
+        // v1.1 errors use the same `errors` list as v2.
+        if !status.is_success() {
+            let errors = serde_json::from_slice::<ApiV2Response<IgnoredAny>>(&body)
+                .map(ApiV2Response::into_errors)
+                .unwrap_or_default();
+
+            return Err(ErrorRepr {
+                kind: ErrorKind::ErrorResponse { status, errors },
+                limit_info: Some(limit_info),
+            }.boxed());
+        }
+
+        if body.is_empty() {
+            return Ok((None, limit_info));
+        }
+
+        match serde_json::from_slice::<RespData>(&body) {
+            Ok(data) => Ok((Some(data), limit_info)),
+            Err(err) => Err(ErrorRepr {
                 kind: ErrorKind::InvalidResponse(err),
+                limit_info: Some(limit_info),
+            }.boxed()),
+        }
+    }
+
+    /// Makes a request to a streaming endpoint, returning the response as soon as its headers
+    /// have been received so that the body can be read incrementally. The body of an unsuccessful
+    /// response is read in full and returned as an [`ErrorKind::ErrorResponse`].
+    pub(crate) async fn stream_request<'req, ReqData>(
+        &self,
+        request: Request<'req, ReqData>
+    ) -> Result<(reqwest::Response, LimitInfo), Error>
+    where
+        ReqData: RequestData,
+    {
+        let (resp, limit_info) = self.raw_request(request).await?;
+
+        let status = resp.status();
+
+        if status.is_success() {
+            return Ok((resp, limit_info));
+        }
+
+        let body = resp
+            .bytes()
+            .await
+            .map_err(|err| ErrorRepr {
+                kind: ErrorKind::from_reqwest(err),
                 limit_info: Some(limit_info.clone()),
             }.boxed())?;
 
-        // Return an error if we got a non-2XX HTTP response code or a non-empty errors list.
-        if !status.is_success()
-            || (apiv2_response.data.is_none() && !apiv2_response.errors.is_empty())
-        {
+        let errors = serde_json::from_slice::<ApiV2Response<IgnoredAny>>(&body)
+            .map(ApiV2Response::into_errors)
+            .unwrap_or_default();
+
+        Err(ErrorRepr {
+            kind: ErrorKind::ErrorResponse { status, errors },
+            limit_info: Some(limit_info),
+        }.boxed())
+    }
+}
+
+impl<A: AppAuth> AsyncClient<A> {
+    /// Checks that Twitter can be reached and that the client's credentials are accepted, by
+    /// looking up the given user via `/2/users/:id` with no additional fields. This is intended
+    /// for readiness probes.
+    ///
+    /// The user should be one which is known to exist, such as the account which owns the app,
+    /// since a user which does not exist or is suspended causes the check to fail with
+    /// [`HealthCheckError::Unavailable`].
+    pub async fn health_check(&self, user_id: UserId) -> Result<(), HealthCheckError> {
+        const SCOPES: &[Scope] = &[Scope::UsersRead];
+
+        let url = format!("https://api.twitter.com/2/users/{}", user_id);
+
+        self.health_check_request(Request::new(Method::Get, &url).with_scopes(SCOPES))
+            .await
+    }
+
+    /// Looks up the id of the user with the given username, which may optionally start with `@`.
+    /// If the client was built with a [username cache](AsyncClientBuilder::username_cache), the
+    /// id is cached so that resolving the same username again does not make another request.
+    pub async fn resolve_username(&self, username: &str) -> Result<UserId, Error> {
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead];
+
+        #[derive(Deserialize)]
+        struct UserIdData {
+            id: UserId,
+        }
+
+        let username = query::check_username(username)
+            .map_err(|err| ErrorRepr {
+                kind: ErrorKind::Custom(err.to_string().into()),
+                limit_info: None,
+            }.boxed())?;
+
+        if let Some(user_id) = self.cached_user_id(username) {
+            return Ok(user_id);
+        }
+
+        let url = format!("https://api.twitter.com/2/users/by/username/{}", username);
+
+        let (response, limit_info) = self.apiv2_request::<_, UserIdData>(
+            Request::new(Method::Get, &url).with_scopes(SCOPES)
+        ).await?;
+
+        let user_id = response
+            .data
+            .ok_or_else(|| ErrorRepr {
+                kind: ErrorKind::NoData,
+                limit_info: Some(limit_info),
+            }.boxed())?
+            .id;
+
+        if let Some(cache) = &self.username_cache {
+            cache.lock().unwrap().insert(username, user_id);
+        }
+
+        Ok(user_id)
+    }
+
+    fn cached_user_id(&self, username: &str) -> Option<UserId> {
+        self.username_cache.as_ref()?.lock().unwrap().get(username)
+    }
+
+    async fn health_check_request(&self, request: Request<'_, ()>) -> Result<(), HealthCheckError> {
+        match self.apiv2_request::<_, IgnoredAny>(request).await {
+            Ok(_) => Ok(()),
+            Err(err) => Err(HealthCheckError::from_error(err)),
+        }
+    }
+}
+
+impl<A: UserAuth> AsyncClient<A> {
+    /// The id of the user this client acts on behalf of, if it is known. This can be used for
+    /// endpoints which need the authenticating user's id in their path.
+    ///
+    /// The id is known if the credentials were created with one, such as with
+    /// [`OAuth10a::with_user_id`], or once it has been looked up with
+    /// [`resolve_acting_user_id`](Self::resolve_acting_user_id).
+    pub fn acting_user_id(&self) -> Option<UserId> {
+        self.auth
+            .acting_user_id()
+            .or_else(|| *self.acting_user_id.lock().unwrap())
+    }
+
+    /// Returns the id of the user this client acts on behalf of. If it is not already known, it
+    /// is looked up via `/2/users/me` and cached, so later calls do not make another request.
+    ///
+    /// Returns [`ErrorKind::UnknownActingUser`] if Twitter does not return the user's id.
+    pub async fn resolve_acting_user_id(&self) -> Result<UserId, Error> {
+        const URL: &str = "https://api.twitter.com/2/users/me";
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead];
+
+        #[derive(Deserialize)]
+        struct UserIdData {
+            id: UserId,
+        }
+
+        if let Some(user_id) = self.acting_user_id() {
+            return Ok(user_id);
+        }
+
+        let (response, limit_info) = self.apiv2_request::<_, UserIdData>(
+            Request::new(Method::Get, URL).with_scopes(SCOPES)
+        ).await?;
+
+        let user_id = response
+            .data
+            .ok_or_else(|| ErrorRepr {
+                kind: ErrorKind::UnknownActingUser,
+                limit_info: Some(limit_info),
+            }.boxed())?
+            .id;
+
+        *self.acting_user_id.lock().unwrap() = Some(user_id);
+
+        Ok(user_id)
+    }
+
+    /// Checks that Twitter can be reached and that the client's user-context credentials are
+    /// accepted, by looking up the authenticating user via `/2/users/me`.
+    pub async fn user_health_check(&self) -> Result<(), HealthCheckError> {
+        const URL: &str = "https://api.twitter.com/2/users/me";
+        const SCOPES: &[Scope] = &[Scope::TweetRead, Scope::UsersRead];
+
+        self.health_check_request(Request::new(Method::Get, URL).with_scopes(SCOPES))
+            .await
+    }
+}
+
+/// Deserialises the body of a response from a v2 endpoint, returning an error if the status was
+/// unsuccessful or if the body has no `data` but does have `errors`.
+fn parse_apiv2_response<RespData>(
+    status: StatusCode,
+    body: &[u8],
+    limit_info: LimitInfo
+) -> Result<(ApiV2Response<RespData>, LimitInfo), Error>
+where
+    RespData: for<'de> Deserialize<'de>,
+{
+    // Attempt to deserialise the response body from JSON.
+    let apiv2_response = match serde_json::from_slice::<ApiV2Response<RespData>>(body) {
+        Ok(apiv2_response) => apiv2_response,
+
+        // An unsuccessful response may not have a JSON body at all, in which case the status is
+        // more useful to the caller than the deserialisation error.
+        Err(_) if !status.is_success() => {
+            return Err(ErrorRepr {
+                kind: ErrorKind::ErrorResponse { status, errors: Box::new([]) },
+                limit_info: Some(limit_info),
+            }.boxed());
+        },
+
+        Err(err) => {
             return Err(ErrorRepr {
-                kind: ErrorKind::ErrorResponse { status, errors: apiv2_response.errors },
+                kind: ErrorKind::InvalidResponse(err),
                 limit_info: Some(limit_info),
             }.boxed());
+        },
+    };
+
+    // Return an error if we got a non-2XX HTTP response code or a non-empty errors list. Lookups
+    // of a single object which does not exist, such as a user by username, fail here, since
+    // Twitter reports them in `errors` of a successful response with no `data`.
+    if !status.is_success()
+        || (apiv2_response.data.is_none() && !apiv2_response.errors.is_empty())
+    {
+        return Err(ErrorRepr {
+            kind: ErrorKind::ErrorResponse { status, errors: apiv2_response.into_errors() },
+            limit_info: Some(limit_info),
+        }.boxed());
+    }
+
+    Ok((apiv2_response, limit_info))
+}
+
+/// The reason a health check failed.
+#[derive(Debug)]
+pub enum HealthCheckError {
+    /// Twitter was reached, but rejected the client's credentials (HTTP 401 or 403), or the
+    /// credentials are missing a scope needed for the check.
+    Unauthorized(Error),
+    /// Any other failure, such as a connection error, a timeout or a server error.
+    Unavailable(Error),
+}
+
+impl HealthCheckError {
+    fn from_error(err: Error) -> Self {
+        let unauthorized = matches!(err.kind(), ErrorKind::MissingScope(_))
+            || matches!(
+                err.status(),
+                Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+            );
+
+        if unauthorized {
+            Self::Unauthorized(err)
+        } else {
+            Self::Unavailable(err)
         }
+    }
 
-        Ok((apiv2_response, limit_info))
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self, Self::Unauthorized(_))
+    }
+
+    pub fn error(&self) -> &Error {
+        match self {
+            Self::Unauthorized(err) | Self::Unavailable(err) => err,
+        }
+    }
+
+    pub fn into_error(self) -> Error {
+        match self {
+            Self::Unauthorized(err) | Self::Unavailable(err) => err,
+        }
     }
 }
 
@@ -181,7 +936,7 @@ impl AsyncClient<OAuth10a> {
             .bytes()
             .await
             .map_err(|err| ErrorRepr {
-                kind: ErrorKind::Transfer(err),
+                kind: ErrorKind::from_reqwest(err),
                 limit_info: Some(limit_info.clone()),
             }.boxed())?;
 
@@ -205,97 +960,340 @@ impl AsyncClient<OAuth10a> {
                 limit_info: Some(limit_info.clone()),
             }.boxed())?;
 
-            let token_secret = token_secret.ok_or_else(|| ErrorRepr {
-                kind: ErrorKind::Custom("no oauth_token_secret in response".into()),
+            let token_secret = token_secret.ok_or_else(|| ErrorRepr {
+                kind: ErrorKind::Custom("no oauth_token_secret in response".into()),
+                limit_info: Some(limit_info.clone()),
+            }.boxed())?;
+
+            (token, token_secret)
+        };
+
+        let redirect_url = format!(
+            "https://api.twitter.com/oauth/authorize?oauth_token={}",
+            percent_encode(&*request_token)
+        )
+        .into_boxed_str();
+
+        let request_auth = OAuth10aRequest::new(
+            self.auth
+                .with_access_token(&request_token, &request_token_secret),
+        );
+
+        Ok((self.clone_reauthenticate(request_auth), redirect_url))
+    }
+}
+
+impl AsyncClient<OAuth10aRequest> {
+    pub async fn get_access_token(
+        self,
+        verifier: &str,
+    ) -> Result<AccessToken, Error>
+    {
+        const ENDPOINT: &str = "https://api.twitter.com/oauth/access_token";
+
+        let data = [
+            (Cow::Borrowed("oauth_verifier"), Cow::Borrowed(verifier))
+        ];
+
+        // FIXME: return limit info
+        let (response, limit_info) = self
+            .raw_request(Request::new_with_data(
+                Method::Post,
+                ENDPOINT,
+                FormData::new(&data),
+            ))
+            .await?;
+
+        // FIXME: better error
+        if !response.status().is_success() {
+            return Err(ErrorRepr {
+                kind: ErrorKind::Custom(
+                    format!("{}", response.status()).into(),
+                ),
+                limit_info: Some(limit_info),
+            }.boxed());
+        }
+
+        let body = response
+            .bytes()
+            .await
+            .map_err(|err| ErrorRepr {
+                kind: ErrorKind::from_reqwest(err),
+                limit_info: Some(limit_info.clone()),
+            }.boxed())?;
+
+        let (mut token, mut token_secret, mut user_id, mut screen_name) = (None, None, None, None);
+
+        for (key, val) in FormDecoder::new(&body) {
+            match &*key {
+                "oauth_token" => {
+                    token = Some(val);
+                }
+                "oauth_token_secret" => {
+                    token_secret = Some(val);
+                }
+                "user_id" => {
+                    user_id = val.parse::<UserId>().ok();
+                }
+                "screen_name" => {
+                    screen_name = Some(val.into());
+                }
+                _ => (),
+            }
+        }
+
+        let token = token.ok_or_else(|| ErrorRepr {
+            kind: ErrorKind::Custom("no oauth_token in response".into()),
+            limit_info: Some(limit_info.clone()),
+        }.boxed())?.into();
+
+        let token_secret = token_secret.ok_or_else(|| ErrorRepr {
+            kind: ErrorKind::Custom("no oauth_token_secret in response".into()),
+            limit_info: Some(limit_info.clone()),
+        }.boxed())?.into();
+
+        Ok(AccessToken {
+            token,
+            token_secret,
+            user_id,
+            screen_name,
+        })
+    }
+}
+
+impl AsyncClient<ClientCredentials> {
+    /// Obtains an app-only bearer token for the app, using the
+    /// [client credentials flow](https://developer.twitter.com/en/docs/authentication/api-reference/token).
+    /// Twitter returns the same token each time until it is invalidated.
+    pub async fn get_bearer_token(&self) -> Result<BearerToken, Error> {
+        const ENDPOINT: &str = "https://api.twitter.com/oauth2/token";
+
+        #[derive(Deserialize)]
+        struct Response {
+            token_type: Box<str>,
+            access_token: Box<str>,
+        }
+
+        let data = [
+            (Cow::Borrowed("grant_type"), Cow::Borrowed("client_credentials"))
+        ];
+
+        let (response, limit_info) = self
+            .raw_request(Request::new_with_data(Method::Post, ENDPOINT, FormData::new(&data)))
+            .await?;
+
+        let status = response.status();
+
+        let body = response
+            .bytes()
+            .await
+            .map_err(|err| ErrorRepr {
+                kind: ErrorKind::from_reqwest(err),
+                limit_info: Some(limit_info.clone()),
+            }.boxed())?;
+
+        // Errors use the same `errors` list as the v2 API.
+        if !status.is_success() {
+            let errors = serde_json::from_slice::<ApiV2Response<IgnoredAny>>(&body)
+                .map(ApiV2Response::into_errors)
+                .unwrap_or_default();
+
+            return Err(ErrorRepr {
+                kind: ErrorKind::ErrorResponse { status, errors },
+                limit_info: Some(limit_info),
+            }.boxed());
+        }
+
+        let response = serde_json::from_slice::<Response>(&body)
+            .map_err(|err| ErrorRepr {
+                kind: ErrorKind::InvalidResponse(err),
                 limit_info: Some(limit_info.clone()),
             }.boxed())?;
 
-            (token, token_secret)
-        };
+        if !response.token_type.eq_ignore_ascii_case("bearer") {
+            return Err(ErrorRepr {
+                kind: ErrorKind::Custom(
+                    format!("unexpected token type \"{}\"", response.token_type).into()
+                ),
+                limit_info: Some(limit_info),
+            }.boxed());
+        }
 
-        let redirect_url = format!(
-            "https://api.twitter.com/oauth/authorize?oauth_token={}",
-            percent_encode(&*request_token)
+        Ok(BearerToken::new(response.access_token))
+    }
+}
+
+impl AsyncClient<OAuth2Pkce> {
+    /// Returns the URL to send the user to in order to authorise the app with the given scopes,
+    /// along with the PKCE code verifier which must be passed to
+    /// [`exchange_code`](Self::exchange_code) once the user has been redirected back to
+    /// `redirect_uri`. `state` is returned unchanged in the redirect, and should be checked to
+    /// protect against CSRF.
+    pub fn authorize_url(
+        &self,
+        redirect_uri: &str,
+        scopes: &[Scope],
+        state: &str,
+    ) -> (Box<str>, PkceVerifier)
+    {
+        const ENDPOINT: &str = "https://twitter.com/i/oauth2/authorize";
+
+        let verifier = PkceVerifier::new_random();
+
+        let scopes = scopes
+            .iter()
+            .map(|scope| scope.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let url = format!(
+            "{}?response_type=code&client_id={}&redirect_uri={}&scope={}&state={}\
+                &code_challenge={}&code_challenge_method=S256",
+            ENDPOINT,
+            percent_encode(self.auth.client_id()),
+            percent_encode(redirect_uri),
+            percent_encode(&scopes),
+            percent_encode(state),
+            verifier.challenge()
         )
         .into_boxed_str();
 
-        let request_auth = OAuth10aRequest::new(
-            self.auth
-                .with_access_token(&request_token, &request_token_secret),
-        );
-
-        Ok((self.clone_reauthenticate(request_auth), redirect_url))
+        (url, verifier)
     }
-}
 
-impl AsyncClient<OAuth10aRequest> {
-    pub async fn get_access_token(
-        self,
-        verifier: &str,
-    ) -> Result<(Box<str>, Box<str>), Error>
+    /// Exchanges the authorization code from the redirect to `redirect_uri` for an access token.
+    /// `redirect_uri` must be the same as the one passed to
+    /// [`authorize_url`](Self::authorize_url).
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        verifier: &PkceVerifier,
+        redirect_uri: &str,
+    ) -> Result<OAuth2Token, Error>
     {
-        const ENDPOINT: &str = "https://api.twitter.com/oauth/access_token";
+        let data = [
+            (Cow::Borrowed("grant_type"), Cow::Borrowed("authorization_code")),
+            (Cow::Borrowed("code"), Cow::Borrowed(code)),
+            (Cow::Borrowed("client_id"), Cow::Borrowed(self.auth.client_id())),
+            (Cow::Borrowed("redirect_uri"), Cow::Borrowed(redirect_uri)),
+            (Cow::Borrowed("code_verifier"), Cow::Borrowed(verifier.as_str())),
+        ];
+
+        self.oauth2_token_request(&data).await
+    }
 
+    /// Exchanges a refresh token for a new access token and refresh token. Refresh tokens are only
+    /// issued if the [`Scope::OfflineAccess`] scope was requested, and each one can only be used
+    /// once, so the new refresh token must be stored in place of the old one.
+    ///
+    /// A client's credentials cannot be changed in place, so to start using the new access token,
+    /// create a client for it with
+    /// [`clone_reauthenticate`](AsyncClient::clone_reauthenticate):
+    ///
+    /// ```ignore
+    /// let token = pkce_client.refresh(&refresh_token).await?;
+    /// let user_client = pkce_client.clone_reauthenticate(token.to_auth());
+    /// ```
+    pub async fn refresh(&self, refresh_token: &str) -> Result<OAuth2Token, Error> {
         let data = [
-            (Cow::Borrowed("oauth_verifier"), Cow::Borrowed(verifier))
+            (Cow::Borrowed("grant_type"), Cow::Borrowed("refresh_token")),
+            (Cow::Borrowed("refresh_token"), Cow::Borrowed(refresh_token)),
+            (Cow::Borrowed("client_id"), Cow::Borrowed(self.auth.client_id())),
         ];
 
-        // FIXME: return limit info
+        self.oauth2_token_request(&data).await
+    }
+}
+
+impl<A: Auth> AsyncClient<A> {
+    /// Makes a request to the OAuth 2.0 token endpoint, which returns errors in the form
+    /// described by RFC 6749 rather than the usual API error format.
+    async fn oauth2_token_request(
+        &self,
+        data: &[(Cow<'_, str>, Cow<'_, str>)],
+    ) -> Result<OAuth2Token, Error>
+    {
+        const ENDPOINT: &str = "https://api.twitter.com/2/oauth2/token";
+
         let (response, limit_info) = self
-            .raw_request(Request::new_with_data(
-                Method::Post,
-                ENDPOINT,
-                FormData::new(&data),
-            ))
+            .raw_request(Request::new_with_data(Method::Post, ENDPOINT, FormData::new(data)))
             .await?;
 
-        // FIXME: better error
-        if !response.status().is_success() {
-            return Err(ErrorRepr {
-                kind: ErrorKind::Custom(
-                    format!("{}", response.status()).into(),
-                ),
-                limit_info: Some(limit_info),
-            }.boxed());
-        }
+        let status = response.status();
 
         let body = response
             .bytes()
             .await
             .map_err(|err| ErrorRepr {
-                kind: ErrorKind::Transfer(err),
+                kind: ErrorKind::from_reqwest(err),
                 limit_info: Some(limit_info.clone()),
             }.boxed())?;
 
-        let (mut token, mut token_secret) = (None, None);
+        if !status.is_success() {
+            let errors = serde_json::from_slice::<TokenErrorResponse>(&body)
+                .map(|err| {
+                    let message = match err.error_description {
+                        Some(description) => format!("{}: {}", err.error, description),
+                        None => err.error.into(),
+                    };
 
-        for (key, val) in FormDecoder::new(&body) {
-            match &*key {
-                "oauth_token" => {
-                    token = Some(val);
-                }
-                "oauth_token_secret" => {
-                    token_secret = Some(val);
-                }
-                _ => (),
-            }
+                    vec![ResponseError {
+                        parameters: None,
+                        code: None,
+                        message: Some(message.into_boxed_str()),
+                        error_type: None,
+                    }]
+                })
+                .unwrap_or_default();
+
+            return Err(ErrorRepr {
+                kind: ErrorKind::ErrorResponse { status, errors: errors.into_boxed_slice() },
+                limit_info: Some(limit_info),
+            }.boxed());
         }
 
-        let token = token.ok_or_else(|| ErrorRepr {
-            kind: ErrorKind::Custom("no oauth_token in response".into()),
-            limit_info: Some(limit_info.clone()),
-        }.boxed())?.into();
+        match serde_json::from_slice::<TokenResponse>(&body) {
+            Ok(response) => Ok(OAuth2Token::from_response(response, limit_info)),
+            Err(err) => Err(ErrorRepr {
+                kind: ErrorKind::InvalidResponse(err),
+                limit_info: Some(limit_info),
+            }.boxed()),
+        }
+    }
+}
 
-        let token_secret = token_secret.ok_or_else(|| ErrorRepr {
-            kind: ErrorKind::Custom("no oauth_token_secret in response".into()),
-            limit_info: Some(limit_info.clone()),
-        }.boxed())?.into();
+/// An OAuth 1.0a access token pair obtained using
+/// [`get_access_token`](AsyncClient::get_access_token).
+#[derive(Debug)]
+pub struct AccessToken {
+    pub token: Box<str>,
+    pub token_secret: Box<str>,
+    /// The id of the user who authorised the access token.
+    pub user_id: Option<UserId>,
+    /// The username of the user who authorised the access token.
+    pub screen_name: Option<Box<str>>,
+}
+
+impl AccessToken {
+    /// Returns credentials which authenticate requests with this access token, using the API key
+    /// pair of `app`. The id of the user who authorised the token is recorded in the credentials
+    /// if Twitter returned it, so [`AsyncClient::acting_user_id`] is known without a lookup.
+    #[must_use]
+    pub fn credentials(&self, app: &OAuth10a) -> OAuth10a {
+        let auth = app.with_access_token(&self.token, &self.token_secret);
 
-        Ok((token, token_secret))
+        match self.user_id {
+            Some(user_id) => auth.with_user_id(user_id),
+            None => auth,
+        }
     }
 }
 
 // FIXME: move into libshire
+/// An iterator over the key-value pairs of an `application/x-www-form-urlencoded` string.
+///
+/// Malformed input is decoded as gracefully as possible rather than ending the iteration early; see
+/// [`form_decode`] for details.
 struct FormDecoder<'a> {
     bytes: &'a [u8],
 }
@@ -321,12 +1319,60 @@ impl<'a> Iterator for FormDecoder<'a> {
         let (pair, remainder) = split_on_byte(self.bytes, b'&');
         self.bytes = remainder;
         let (key, val) = split_on_byte(pair, b'=');
-        let key = percent_decode_utf8(key, FormDecode);
-        let val = percent_decode_utf8(val, FormDecode);
+        let key = form_decode(key);
+        let val = form_decode(val);
         Some((key, val))
     }
 }
 
+/// Decodes a form-urlencoded key or value, where `+` represents a space and `%XX` represents a
+/// percent-encoded byte.
+///
+/// - A `%` which is not followed by two hex digits is kept as a literal `%`.
+/// - Any invalid UTF-8 in the decoded bytes (including a multi-byte sequence cut short) is replaced
+///   with U+FFFD REPLACEMENT CHARACTER.
+fn form_decode(bytes: &[u8]) -> Cow<str> {
+    if !bytes.iter().any(|&byte| byte == b'%' || byte == b'+') {
+        return String::from_utf8_lossy(bytes);
+    }
+
+    let mut buf = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => {
+                buf.push(b' ');
+                i += 1;
+            },
+            b'%' => {
+                let hi = bytes.get(i + 1).copied().and_then(hex_digit_value);
+                let lo = bytes.get(i + 2).copied().and_then(hex_digit_value);
+                match (hi, lo) {
+                    (Some(hi), Some(lo)) => {
+                        buf.push((hi << 4) | lo);
+                        i += 3;
+                    },
+                    _ => {
+                        buf.push(b'%');
+                        i += 1;
+                    },
+                }
+            },
+            byte => {
+                buf.push(byte);
+                i += 1;
+            },
+        }
+    }
+
+    Cow::Owned(String::from_utf8_lossy(&buf).into_owned())
+}
+
+fn hex_digit_value(byte: u8) -> Option<u8> {
+    char::from(byte).to_digit(16).map(|digit| digit as u8)
+}
+
 fn split_on_byte(bytes: &[u8], delim: u8) -> (&[u8], &[u8]) {
     match bytes.iter().copied().position(|byte| byte == delim) {
         Some(index) => {
@@ -346,7 +1392,309 @@ fn split_on_byte(bytes: &[u8], delim: u8) -> (&[u8], &[u8]) {
 
 #[cfg(test)]
 mod tests {
-    use super::FormDecoder;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    };
+
+    use reqwest::StatusCode;
+    use serde::de::IgnoredAny;
+
+    use crate::{
+        auth::{OAuth10a, OAuth2, Scope},
+        limit::LimitInfo,
+        response::ResponseError,
+        user::{User, UserId},
+    };
+
+    use super::{
+        parse_apiv2_response,
+        rate_limit_wait,
+        rebase_url,
+        AccessToken,
+        AsyncClient,
+        Error,
+        ErrorKind,
+        ErrorRepr,
+        FormDecoder,
+        Method,
+        MultiAuthClient,
+        Request,
+    };
+
+    fn error_response(status: StatusCode, errors: Vec<ResponseError>) -> Error {
+        ErrorRepr {
+            kind: ErrorKind::ErrorResponse {
+                status,
+                errors: errors.into_boxed_slice(),
+            },
+            limit_info: None,
+        }.boxed()
+    }
+
+    fn response_error(code: Option<u32>, error_type: Option<&str>) -> ResponseError {
+        ResponseError {
+            parameters: None,
+            code,
+            message: None,
+            error_type: error_type.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn test_parse_apiv2_response_missing_user() {
+        // Twitter responds to a lookup of a user which does not exist with a 200 and no `data`.
+        let body = br#"{
+            "errors": [{
+                "value": "nobody",
+                "detail": "Could not find user with username: [nobody].",
+                "title": "Not Found Error",
+                "resource_type": "user",
+                "parameter": "username",
+                "resource_id": "nobody",
+                "type": "https://api.twitter.com/2/problems/resource-not-found"
+            }]
+        }"#;
+
+        let err = parse_apiv2_response::<User>(StatusCode::OK, body, LimitInfo::empty())
+            .unwrap_err();
+        assert!(err.is_not_found());
+        assert_eq!(err.status(), Some(StatusCode::OK));
+    }
+
+    #[test]
+    fn test_rate_limit_wait() {
+        fn rate_limited(reset: SystemTime) -> Error {
+            let reset = reset.duration_since(UNIX_EPOCH).unwrap().as_secs();
+            ErrorRepr {
+                kind: ErrorKind::ErrorResponse {
+                    status: StatusCode::TOO_MANY_REQUESTS,
+                    errors: Box::new([]),
+                },
+                limit_info: Some(LimitInfo::new(Some(15), Some(0), Some(reset))),
+            }.boxed()
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let now = SystemTime::now();
+
+        let wait = rate_limit_wait(&rate_limited(now + Duration::from_secs(30)), deadline).unwrap();
+        assert!(wait <= Duration::from_secs(30));
+        assert!(wait > Duration::from_secs(25));
+
+        // A reset in the past means the request can be retried immediately.
+        let wait = rate_limit_wait(&rate_limited(now - Duration::from_secs(30)), deadline);
+        assert_eq!(wait, Some(Duration::ZERO));
+
+        // Waiting past the deadline is not allowed.
+        assert!(rate_limit_wait(&rate_limited(now + Duration::from_secs(600)), deadline).is_none());
+
+        // Once the deadline has passed, even a short wait is not allowed.
+        let passed = Instant::now() - Duration::from_secs(1);
+        assert!(rate_limit_wait(&rate_limited(now + Duration::from_secs(1)), passed).is_none());
+
+        let err = error_response(StatusCode::TOO_MANY_REQUESTS, Vec::new());
+        assert!(rate_limit_wait(&err, deadline).is_none());
+    }
+
+    #[test]
+    fn test_acting_user_id() {
+        let client = AsyncClient::new(OAuth2::new("token"), None).unwrap();
+        assert_eq!(client.acting_user_id(), None);
+
+        let client = client.reauthenticate(OAuth2::new("token").with_user_id(UserId(12)));
+        assert_eq!(client.acting_user_id(), Some(UserId(12)));
+
+        let mut multi = MultiAuthClient::new(&client);
+        multi.insert(UserId(34), OAuth2::new("other"));
+        assert_eq!(multi.client_for(UserId(34)).unwrap().acting_user_id(), Some(UserId(34)));
+
+        let app = OAuth10a::new("key", "key_secret", "", "");
+        let access_token = AccessToken {
+            token: "token".into(),
+            token_secret: "token_secret".into(),
+            user_id: Some(UserId(56)),
+            screen_name: None,
+        };
+        let client = client.reauthenticate(access_token.credentials(&app));
+        assert_eq!(client.acting_user_id(), Some(UserId(56)));
+
+        let err = ErrorRepr {
+            kind: ErrorKind::UnknownActingUser,
+            limit_info: None,
+        }.boxed();
+        assert_eq!(err.to_string(), "the id of the authenticating user is not known");
+    }
+
+    #[test]
+    fn test_error_display() {
+        let err = error_response(StatusCode::NOT_FOUND, vec![
+            ResponseError {
+                message: Some("Sorry, that page does not exist".into()),
+                ..response_error(Some(34), None)
+            },
+            response_error(None, Some("https://api.twitter.com/2/problems/resource-not-found")),
+        ]);
+        assert_eq!(
+            err.to_string(),
+            "error response from Twitter with status 404 Not Found: \
+                Sorry, that page does not exist (code 34); \
+                https://api.twitter.com/2/problems/resource-not-found"
+        );
+
+        let err = ErrorRepr {
+            kind: ErrorKind::MissingScope(Scope::TweetRead),
+            limit_info: None,
+        }.boxed();
+        assert_eq!(err.to_string(), "credentials do not have the required scope tweet.read");
+    }
+
+    #[test]
+    fn test_error_categories() {
+        let err = error_response(StatusCode::UNAUTHORIZED, Vec::new());
+        assert!(err.is_auth_error());
+        assert!(!err.is_not_found());
+        assert!(!err.is_rate_limited());
+
+        let err = error_response(StatusCode::BAD_REQUEST, vec![response_error(Some(89), None)]);
+        assert!(err.is_auth_error());
+
+        let err = ErrorRepr {
+            kind: ErrorKind::MissingScope(Scope::TweetRead),
+            limit_info: None,
+        }.boxed();
+        assert!(err.is_auth_error());
+
+        let err = error_response(StatusCode::NOT_FOUND, Vec::new());
+        assert!(err.is_not_found());
+        assert!(!err.is_auth_error());
+
+        let err = error_response(StatusCode::OK, vec![response_error(
+            None,
+            Some("https://api.twitter.com/2/problems/resource-not-found")
+        )]);
+        assert!(err.is_not_found());
+
+        let err = error_response(StatusCode::TOO_MANY_REQUESTS, Vec::new());
+        assert!(err.is_rate_limited());
+        assert!(err.is_retryable());
+        assert!(!err.is_timeout());
+    }
+
+    #[test]
+    fn test_invalid_pagination_token() {
+        let body = br#"{
+            "errors": [{
+                "parameters": {"pagination_token": ["7140dibdnow9c7btw3w29"]},
+                "message": "The `pagination_token` query parameter value is not valid"
+            }],
+            "title": "Invalid Request",
+            "detail": "One or more parameters to your request was invalid.",
+            "type": "https://api.twitter.com/2/problems/invalid-request"
+        }"#;
+
+        let err = parse_apiv2_response::<IgnoredAny>(
+            StatusCode::BAD_REQUEST,
+            body,
+            LimitInfo::empty()
+        ).unwrap_err();
+        assert!(err.is_invalid_pagination_token());
+
+        let body = br#"{
+            "errors": [{
+                "parameters": {"max_results": ["1000"]},
+                "message": "The `max_results` query parameter value [1000] is not between 5 and 100"
+            }],
+            "title": "Invalid Request",
+            "detail": "One or more parameters to your request was invalid.",
+            "type": "https://api.twitter.com/2/problems/invalid-request"
+        }"#;
+
+        let err = parse_apiv2_response::<IgnoredAny>(
+            StatusCode::BAD_REQUEST,
+            body,
+            LimitInfo::empty()
+        ).unwrap_err();
+        assert!(!err.is_invalid_pagination_token());
+    }
+
+    #[test]
+    fn test_base_url() {
+        let builder = || AsyncClient::builder(OAuth2::new("abc"));
+
+        assert!(builder().base_url("https://mock.example.com").is_ok());
+        assert!(builder().base_url("http://localhost:8080/").is_ok());
+        assert!(builder().base_url("http://api.twitter.com").is_err());
+        assert!(builder().base_url("http://API.Twitter.com.").is_err());
+        assert!(builder().base_url("ftp://localhost").is_err());
+        assert!(builder().base_url("http://localhost?a=b").is_err());
+        assert!(builder().base_url("localhost:8080").is_err());
+
+        let base_url = "http://localhost:8080";
+        assert_eq!(
+            rebase_url("https://api.twitter.com/2/users/me?a=b", base_url),
+            "http://localhost:8080/2/users/me?a=b"
+        );
+        assert_eq!(
+            rebase_url("https://upload.twitter.com/1.1/media/upload.json", base_url),
+            "https://upload.twitter.com/1.1/media/upload.json"
+        );
+        assert_eq!(
+            rebase_url("https://api.twitter.com.evil.com/2/users/me", base_url),
+            "https://api.twitter.com.evil.com/2/users/me"
+        );
+    }
+
+    #[test]
+    fn test_plaintext_signed_request_to_base_url() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        // A mock server which accepts a single request and returns its head.
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut head = Vec::new();
+            let mut buf = [0; 1024];
+            while !head.ends_with(b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                assert_ne!(n, 0);
+                head.extend_from_slice(&buf[..n]);
+            }
+            stream.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\n{}").unwrap();
+            String::from_utf8(head).unwrap()
+        });
+
+        let auth = OAuth10a::new("api_key", "api_secret", "access_token", "access_secret")
+            .with_plaintext_signature();
+
+        let client = AsyncClient::builder(auth)
+            .base_url(&base_url)
+            .unwrap()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap();
+
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let (response, _) = runtime.block_on(async {
+            client
+                .raw_request(Request::new(Method::Get, "https://api.twitter.com/2/users/me"))
+                .await
+                .unwrap()
+        });
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let head = server.join().unwrap();
+        assert!(head.starts_with("GET /2/users/me HTTP/1.1\r\n"));
+
+        let auth_header = head
+            .lines()
+            .find_map(|line| line.strip_prefix("authorization: "))
+            .unwrap();
+        assert!(auth_header.contains(r#"oauth_signature_method="PLAINTEXT""#));
+        assert!(auth_header.contains(r#"oauth_signature="api_secret%26access_secret""#));
+    }
 
     #[test]
     fn test_form_decoder() {
@@ -365,6 +1713,28 @@ mod tests {
         );
         assert_eq!(decoder.next(), None);
     }
+
+    #[test]
+    fn test_form_decoder_malformed() {
+        let mut decoder = FormDecoder::new("a=%ZZ%4&b=%F0%9F&c=%C3%28+ok&d=100%");
+        assert_eq!(
+            decoder.next().as_ref().map(|(k, v)| (&**k, &**v)),
+            Some(("a", "%ZZ%4"))
+        );
+        assert_eq!(
+            decoder.next().as_ref().map(|(k, v)| (&**k, &**v)),
+            Some(("b", "\u{FFFD}"))
+        );
+        assert_eq!(
+            decoder.next().as_ref().map(|(k, v)| (&**k, &**v)),
+            Some(("c", "\u{FFFD}( ok"))
+        );
+        assert_eq!(
+            decoder.next().as_ref().map(|(k, v)| (&**k, &**v)),
+            Some(("d", "100%"))
+        );
+        assert_eq!(decoder.next(), None);
+    }
 }
 
 #[derive(ScribeStaticStr, Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -375,6 +1745,8 @@ pub enum Method {
     Post,
     #[enumscribe(str = "PUT")]
     Put,
+    #[enumscribe(str = "PATCH")]
+    Patch,
     #[enumscribe(str = "DELETE")]
     Delete,
 }
@@ -389,12 +1761,12 @@ impl Method {
             Method::Get => reqwest::Method::GET,
             Method::Post => reqwest::Method::POST,
             Method::Put => reqwest::Method::PUT,
+            Method::Patch => reqwest::Method::PATCH,
             Method::Delete => reqwest::Method::DELETE,
         }
     }
 }
 
-// FIXME: impl Display and Error
 #[derive(Debug)]
 pub struct Error {
     repr: Box<ErrorRepr>,
@@ -408,6 +1780,138 @@ impl Error {
     pub fn limit_info(&self) -> Option<&LimitInfo> {
         self.repr.limit_info.as_ref()
     }
+
+    /// The HTTP status code of the response which caused this error, or `None` if the error did
+    /// not come from an HTTP response (for example, if the connection failed).
+    pub fn status(&self) -> Option<StatusCode> {
+        match self.kind() {
+            ErrorKind::ErrorResponse { status, .. } => Some(*status),
+            kind => kind.reqwest_error().and_then(reqwest::Error::status),
+        }
+    }
+
+    /// Returns `true` if the request which caused this error may succeed if it is retried later.
+    /// This is the case for timeouts, connection errors, rate limiting (HTTP 429) and server
+    /// errors (HTTP 5XX), but not for other client errors or invalid responses.
+    pub fn is_retryable(&self) -> bool {
+        match self.kind() {
+            ErrorKind::Timeout(_) | ErrorKind::Connect(_) => true,
+            ErrorKind::ErrorResponse { status, .. } => is_retryable_status(*status),
+            kind => kind
+                .reqwest_error()
+                .and_then(reqwest::Error::status)
+                .map_or(false, is_retryable_status),
+        }
+    }
+
+    /// Returns `true` if the request timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self.kind(), ErrorKind::Timeout(_))
+    }
+
+    /// Returns `true` if the request failed because of the client's credentials: Twitter
+    /// responded with HTTP 401 or 403 or an authentication error code, or the credentials could
+    /// not be used for the request at all.
+    pub fn is_auth_error(&self) -> bool {
+        match self.kind() {
+            ErrorKind::BadAuthHeader
+            | ErrorKind::MissingScope(_)
+            | ErrorKind::AuthNotAllowed => true,
+            _ => {
+                matches!(self.status(), Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN))
+                    || self.has_error_code(AUTH_ERROR_CODES)
+            },
+        }
+    }
+
+    /// Returns `true` if the requested resource does not exist: Twitter responded with HTTP 404,
+    /// or reported a "resource not found" problem, which some endpoints do with HTTP 200.
+    pub fn is_not_found(&self) -> bool {
+        if self.status() == Some(StatusCode::NOT_FOUND) || self.has_error_code(NOT_FOUND_CODES) {
+            return true;
+        }
+
+        match self.kind() {
+            ErrorKind::ErrorResponse { errors, .. } => {
+                errors.iter().any(|error| error.is_problem("resource-not-found"))
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the request was rejected because a rate limit was exceeded.
+    pub fn is_rate_limited(&self) -> bool {
+        self.status() == Some(StatusCode::TOO_MANY_REQUESTS)
+            || self.has_error_code(RATE_LIMITED_CODES)
+    }
+
+    fn has_error_code(&self, codes: &[u32]) -> bool {
+        match self.kind() {
+            ErrorKind::ErrorResponse { errors, .. } => errors
+                .iter()
+                .filter_map(|error| error.code)
+                .any(|code| codes.contains(&code)),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if Twitter rejected the pagination token used for the request, for example
+    /// because a stored token has expired. Pagination will have to be restarted from the first
+    /// page.
+    pub fn is_invalid_pagination_token(&self) -> bool {
+        match self.kind() {
+            ErrorKind::ErrorResponse { errors, .. } => errors.iter().any(|error| {
+                error
+                    .parameters
+                    .as_ref()
+                    .map_or(false, |parameters| !parameters.pagination_token.is_empty())
+            }),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.kind(), f)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.kind() {
+            ErrorKind::InvalidResponse(err) => Some(err),
+            kind => kind.reqwest_error().map(|err| err as _),
+        }
+    }
+}
+
+// Twitter's error codes for invalid or expired credentials, bad authentication data and
+// unauthorised apps.
+const AUTH_ERROR_CODES: &[u32] = &[32, 89, 99, 135, 215, 220];
+// Twitter's error codes for a page, user or Tweet which does not exist.
+const NOT_FOUND_CODES: &[u32] = &[34, 50, 144];
+const RATE_LIMITED_CODES: &[u32] = &[88];
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+// The longest a rate-limited request is retried for when the client has no timeout. This is the
+// length of a standard rate limit window, so it is enough to wait out a per-endpoint limit but
+// not the app's 24-hour limit.
+const DEFAULT_MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// Returns how long to wait before retrying a rate-limited request, or `None` if it should not be
+/// retried because Twitter did not say when the limit resets or the reset is after the deadline.
+fn rate_limit_wait(err: &Error, deadline: Instant) -> Option<Duration> {
+    let wait = err.limit_info()?.wait_reset_duration()?;
+
+    if wait > deadline.saturating_duration_since(Instant::now()) {
+        None
+    } else {
+        Some(wait)
+    }
 }
 
 #[derive(Debug)]
@@ -425,7 +1929,17 @@ impl ErrorRepr {
 #[derive(Debug)]
 pub enum ErrorKind {
     BadAuthHeader,
-    // FIXME: separate variant for each of the different `reqwest::Error` variants
+    /// The request timed out.
+    Timeout(reqwest::Error),
+    /// A connection to the server could not be established.
+    Connect(reqwest::Error),
+    /// The request or response body could not be transferred.
+    Body(reqwest::Error),
+    /// The response body could not be decoded, for example because of an invalid compression
+    /// encoding. Invalid JSON is reported as [`ErrorKind::InvalidResponse`] instead.
+    Decode(reqwest::Error),
+    /// Any other error returned by the HTTP client, such as an error building the request or
+    /// following a redirect.
     Transfer(reqwest::Error),
     InvalidResponse(serde_json::Error),
     ErrorResponse {
@@ -433,14 +1947,103 @@ pub enum ErrorKind {
         errors: Box<[ResponseError]>,
     },
     NoData,
+    /// The credentials used do not have an OAuth 2.0 scope required by the endpoint.
+    MissingScope(Scope),
+    /// The credentials refused to authenticate a request to the URL, such as OAuth 1.0a
+    /// credentials using the `PLAINTEXT` signature method for a request to Twitter.
+    AuthNotAllowed,
+    /// The Tweet text is longer than Twitter allows, as measured by
+    /// [`tweet_length`](crate::text::tweet_length). The request was not sent.
+    TweetTooLong { length: usize },
+    /// The id of the user the client acts on behalf of is not known, and could not be looked up.
+    UnknownActingUser,
     // FIXME: replace this temporary variant
     Custom(Cow<'static, str>),
 }
 
+impl ErrorKind {
+    /// Sorts an error returned by the HTTP client into the variant which best describes it.
+    pub(crate) fn from_reqwest(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            ErrorKind::Timeout(err)
+        } else if err.is_connect() {
+            ErrorKind::Connect(err)
+        } else if err.is_body() {
+            ErrorKind::Body(err)
+        } else if err.is_decode() {
+            ErrorKind::Decode(err)
+        } else {
+            ErrorKind::Transfer(err)
+        }
+    }
+
+    fn reqwest_error(&self) -> Option<&reqwest::Error> {
+        match self {
+            ErrorKind::Timeout(err)
+            | ErrorKind::Connect(err)
+            | ErrorKind::Body(err)
+            | ErrorKind::Decode(err)
+            | ErrorKind::Transfer(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::BadAuthHeader => f.write_str("failed to build authorization header"),
+            ErrorKind::Timeout(_) => f.write_str("request timed out"),
+            ErrorKind::Connect(_) => f.write_str("failed to connect to server"),
+            ErrorKind::Body(_) => f.write_str("error transferring request or response body"),
+            ErrorKind::Decode(_) => f.write_str("failed to decode response body"),
+            ErrorKind::Transfer(_) => f.write_str("error sending request or reading response"),
+            ErrorKind::InvalidResponse(_) => f.write_str("invalid response body"),
+            ErrorKind::ErrorResponse { status, errors } => {
+                write!(f, "error response from Twitter with status {}", status)?;
+                for (i, error) in errors.iter().enumerate() {
+                    f.write_str(if i == 0 { ": " } else { "; " })?;
+                    write_response_error(f, error)?;
+                }
+                Ok(())
+            },
+            ErrorKind::NoData => f.write_str("response contained no data"),
+            ErrorKind::MissingScope(scope) => {
+                write!(f, "credentials do not have the required scope {}", scope.scribe())
+            },
+            ErrorKind::AuthNotAllowed => {
+                f.write_str("credentials cannot be used to authenticate a request to this URL")
+            },
+            ErrorKind::TweetTooLong { length } => {
+                write!(f, "tweet is too long ({} characters)", length)
+            },
+            ErrorKind::UnknownActingUser => {
+                f.write_str("the id of the authenticating user is not known")
+            },
+            ErrorKind::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+fn write_response_error(f: &mut fmt::Formatter, error: &ResponseError) -> fmt::Result {
+    match (error.message.as_deref(), error.error_type.as_deref()) {
+        (Some(message), _) => f.write_str(message)?,
+        (None, Some(error_type)) => f.write_str(error_type)?,
+        (None, None) => f.write_str("unknown error")?,
+    }
+
+    match error.code {
+        Some(code) => write!(f, " (code {})", code),
+        None => Ok(()),
+    }
+}
+
+#[derive(Clone)]
 pub struct Request<'a, D> {
     method: Method,
     base_url: &'a str,
     data: D,
+    scopes: &'static [Scope],
 }
 
 impl<'a> Request<'a, ()> {
@@ -449,6 +2052,7 @@ impl<'a> Request<'a, ()> {
             method,
             base_url,
             data: (),
+            scopes: &[],
         }
     }
 }
@@ -459,6 +2063,16 @@ impl<'a, D> Request<'a, D> {
             method,
             base_url,
             data,
+            scopes: &[],
+        }
+    }
+
+    /// Sets the OAuth 2.0 scopes that the endpoint requires.
+    #[must_use]
+    pub fn with_scopes(self, scopes: &'static [Scope]) -> Self {
+        Self {
+            scopes,
+            ..self
         }
     }
 
@@ -1,10 +1,14 @@
-use std::ops::{Range, RangeInclusive};
+use std::{iter, ops::{Range, RangeInclusive}};
 
 use libshire::strings::InliningString23;
 use serde::{Deserialize, Serialize};
 
 use crate::{media::MediaKey, user::UserId};
 
+// Twitter gives the positions of entities as offsets in Unicode code points rather than bytes, so
+// the `range` of an entity cannot be used to index a `str` directly. The `text` methods convert the
+// range to byte offsets instead.
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Annotation {
     start: usize,
@@ -21,6 +25,12 @@ impl Annotation {
         self.start..=self.end_inclusive
     }
 
+    /// Returns the annotated text from the text the annotation belongs to, or `None` if the
+    /// annotation's range is out of bounds for `source`.
+    pub fn text<'a>(&self, source: &'a str) -> Option<&'a str> {
+        slice_code_points(source, self.start, self.end_inclusive.checked_add(1)?)
+    }
+
     pub fn probability(&self) -> f64 {
         self.probability
     }
@@ -46,6 +56,12 @@ impl Tag {
         self.start..self.end
     }
 
+    /// Returns the entity's text from the text the entity belongs to, or `None` if the entity's
+    /// range is out of bounds for `source`.
+    pub fn text<'a>(&self, source: &'a str) -> Option<&'a str> {
+        slice_code_points(source, self.start, self.end)
+    }
+
     pub fn tag(&self) -> &str {
         &self.tag
     }
@@ -64,6 +80,12 @@ impl TweetMention {
         self.start..self.end
     }
 
+    /// Returns the entity's text from the text the entity belongs to, or `None` if the entity's
+    /// range is out of bounds for `source`.
+    pub fn text<'a>(&self, source: &'a str) -> Option<&'a str> {
+        slice_code_points(source, self.start, self.end)
+    }
+
     pub fn username(&self) -> &str {
         &self.username
     }
@@ -85,6 +107,12 @@ impl UserMention {
         self.start..self.end
     }
 
+    /// Returns the entity's text from the text the entity belongs to, or `None` if the entity's
+    /// range is out of bounds for `source`.
+    pub fn text<'a>(&self, source: &'a str) -> Option<&'a str> {
+        slice_code_points(source, self.start, self.end)
+    }
+
     pub fn username(&self) -> &str {
         &self.username
     }
@@ -105,6 +133,12 @@ impl Url {
         self.start..self.end
     }
 
+    /// Returns the entity's text from the text the entity belongs to, or `None` if the entity's
+    /// range is out of bounds for `source`.
+    pub fn text<'a>(&self, source: &'a str) -> Option<&'a str> {
+        slice_code_points(source, self.start, self.end)
+    }
+
     /// A URL to Twitter's "t.co" domain, which redirects to the original URL.
     pub fn t_co_url(&self) -> &str {
         &self.url
@@ -122,3 +156,62 @@ impl Url {
         self.media_key
     }
 }
+
+/// Returns the substring of `source` from code point `start` (inclusive) to code point `end`
+/// (exclusive), or `None` if the range is out of bounds.
+fn slice_code_points(source: &str, start: usize, end: usize) -> Option<&str> {
+    if start > end {
+        return None;
+    }
+
+    // The byte offset of every code point, plus the length of the string for a range which ends
+    // at the end of the string.
+    let mut offsets = source
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain(iter::once(source.len()));
+
+    let start_offset = offsets.nth(start)?;
+    let end_offset = match end - start {
+        0 => start_offset,
+        len => offsets.nth(len - 1)?,
+    };
+
+    source.get(start_offset..end_offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{slice_code_points, Annotation, Tag};
+
+    #[test]
+    fn test_slice_code_points() {
+        let text = "🦀 ünï";
+        assert_eq!(slice_code_points(text, 0, 1), Some("🦀"));
+        assert_eq!(slice_code_points(text, 2, 5), Some("ünï"));
+        assert_eq!(slice_code_points(text, 5, 5), Some(""));
+        assert_eq!(slice_code_points(text, 0, 0), Some(""));
+        assert_eq!(slice_code_points(text, 4, 6), None);
+        assert_eq!(slice_code_points(text, 6, 6), None);
+        assert_eq!(slice_code_points(text, 3, 2), None);
+    }
+
+    #[test]
+    fn test_entity_text() {
+        let text = "🦀🦀 #rustlang is great";
+
+        let tag = serde_json::from_str::<Tag>(r#"{"start": 3, "end": 12, "tag": "rustlang"}"#)
+            .unwrap();
+        assert_eq!(tag.text(text), Some("#rustlang"));
+        assert_eq!(tag.text("too short"), None);
+
+        let annotation = serde_json::from_str::<Annotation>(r#"{
+            "start": 4,
+            "end": 11,
+            "probability": 0.9,
+            "type": "Product",
+            "normalized_text": "rustlang"
+        }"#).unwrap();
+        assert_eq!(annotation.text(text), Some("rustlang"));
+    }
+}
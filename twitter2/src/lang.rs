@@ -0,0 +1,166 @@
+use std::{error, fmt, str::FromStr};
+
+use serde::{
+    de::{Error, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// A language, identified by the BCP 47 language tag Twitter uses for it (for example `en` or
+/// `zh-cn`).
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Lang {
+    /// Twitter could not determine the language (`und`).
+    Undetermined,
+    /// A lowercase BCP 47 language tag.
+    Tag(Box<str>),
+}
+
+impl Lang {
+    const UNDETERMINED: &'static str = "und";
+
+    /// Parses a BCP 47 language tag, returning `None` if it is not well-formed. The tag is
+    /// converted to lowercase.
+    pub fn new(tag: &str) -> Option<Self> {
+        if !is_valid_tag(tag) {
+            return None;
+        }
+
+        Some(Self::from_valid_tag(tag))
+    }
+
+    fn from_valid_tag(tag: &str) -> Self {
+        if tag.eq_ignore_ascii_case(Self::UNDETERMINED) {
+            Self::Undetermined
+        } else {
+            Self::Tag(tag.to_ascii_lowercase().into_boxed_str())
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Undetermined => Self::UNDETERMINED,
+            Self::Tag(tag) => tag,
+        }
+    }
+
+    pub fn is_undetermined(&self) -> bool {
+        matches!(self, Self::Undetermined)
+    }
+}
+
+/// Checks that the tag is a primary language subtag of 2 or 3 letters, optionally followed by
+/// further subtags of 1 to 8 alphanumeric characters, all separated by hyphens.
+fn is_valid_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+
+    let primary_valid = subtags
+        .next()
+        .map_or(false, |primary| {
+            (2..=3).contains(&primary.len())
+                && primary.bytes().all(|b| b.is_ascii_alphabetic())
+        });
+
+    primary_valid
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len())
+                && subtag.bytes().all(|b| b.is_ascii_alphanumeric())
+        })
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Lang {
+    type Err = InvalidLangError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s).ok_or_else(|| InvalidLangError(s.into()))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct InvalidLangError(Box<str>);
+
+impl InvalidLangError {
+    pub fn tag(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for InvalidLangError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid language tag: {}", self.0)
+    }
+}
+
+impl error::Error for InvalidLangError {}
+
+impl Serialize for Lang {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Lang {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(LangVisitor)
+    }
+}
+
+struct LangVisitor;
+
+impl<'de> Visitor<'de> for LangVisitor {
+    type Value = Lang;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a BCP 47 language tag")
+    }
+
+    // Tags from Twitter are accepted without validation, so that an unusual tag does not cause
+    // the whole response to fail to deserialize.
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Lang::from_valid_tag(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lang;
+
+    #[test]
+    fn test_lang_new() {
+        assert_eq!(Lang::new("en"), Some(Lang::Tag("en".into())));
+        assert_eq!(Lang::new("zh-CN"), Some(Lang::Tag("zh-cn".into())));
+        assert_eq!(Lang::new("und"), Some(Lang::Undetermined));
+        assert_eq!(Lang::new("UND").unwrap().as_str(), "und");
+
+        assert_eq!(Lang::new(""), None);
+        assert_eq!(Lang::new("e"), None);
+        assert_eq!(Lang::new("engl"), None);
+        assert_eq!(Lang::new("en-"), None);
+        assert_eq!(Lang::new("en us"), None);
+        assert_eq!(Lang::new("en:us"), None);
+    }
+
+    #[test]
+    fn test_lang_serde() {
+        let lang = serde_json::from_str::<Lang>(r#""und""#).unwrap();
+        assert!(lang.is_undetermined());
+
+        let lang = serde_json::from_str::<Lang>(r#""en""#).unwrap();
+        assert_eq!(lang, Lang::Tag("en".into()));
+        assert_eq!(serde_json::to_string(&lang).unwrap(), r#""en""#);
+    }
+}
@@ -1,16 +1,29 @@
+pub mod actions;
 pub mod auth;
 pub mod client;
 pub mod entity;
 mod id;
+pub mod lang;
 pub mod limit;
+pub mod list;
 pub mod media;
+mod open_enum;
+pub mod place;
+pub mod poll;
+pub mod query;
 pub mod request_data;
 pub mod request_options;
 pub mod response;
 pub mod request;
+pub mod space;
+pub mod stream;
+pub mod text;
 pub mod timeline;
 pub mod tweet;
+pub mod upload;
 pub mod user;
+mod username_cache;
+pub mod withheld;
 
-pub use auth::{BearerToken, OAuth10a};
-pub use client::AsyncClient;
+pub use auth::{BearerToken, OAuth10a, OAuth2};
+pub use client::{AsyncClient, AsyncClientBuilder, MultiAuthClient};
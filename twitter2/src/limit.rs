@@ -1,10 +1,17 @@
-use std::{num::NonZeroU64, str, time::Duration};
+use std::{num::NonZeroU64, str, time::{Duration, SystemTime, UNIX_EPOCH}};
 
+use chrono::{DateTime, TimeZone, Utc};
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 
 const X_RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("x-rate-limit-limit");
 const X_RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("x-rate-limit-remaining");
 const X_RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("x-rate-limit-reset");
+const X_APP_LIMIT_24HOUR_LIMIT: HeaderName =
+    HeaderName::from_static("x-app-limit-24hour-limit");
+const X_APP_LIMIT_24HOUR_REMAINING: HeaderName =
+    HeaderName::from_static("x-app-limit-24hour-remaining");
+const X_APP_LIMIT_24HOUR_RESET: HeaderName =
+    HeaderName::from_static("x-app-limit-24hour-reset");
 
 /// Stores information provided by the Twitter API about the rate limit of the endpoint that was
 /// used.
@@ -13,6 +20,9 @@ pub struct LimitInfo {
     limit: Option<NonZeroU64>,
     remaining: Option<NonZeroU64>,
     reset_secs: Option<NonZeroU64>,
+    app_24h_limit: Option<NonZeroU64>,
+    app_24h_remaining: Option<NonZeroU64>,
+    app_24h_reset_secs: Option<NonZeroU64>,
 }
 
 impl LimitInfo {
@@ -21,6 +31,9 @@ impl LimitInfo {
             limit: None,
             remaining: None,
             reset_secs: None,
+            app_24h_limit: None,
+            app_24h_remaining: None,
+            app_24h_reset_secs: None,
         }
     }
 
@@ -33,6 +46,9 @@ impl LimitInfo {
             limit: opt_u64_encode(limit),
             remaining: opt_u64_encode(remaining),
             reset_secs: opt_u64_encode(reset_secs),
+            app_24h_limit: None,
+            app_24h_remaining: None,
+            app_24h_reset_secs: None,
         }
     }
 
@@ -40,7 +56,17 @@ impl LimitInfo {
         let limit = headers.get(X_RATE_LIMIT_LIMIT).and_then(parse_int_header);
         let remaining = headers.get(X_RATE_LIMIT_REMAINING).and_then(parse_int_header);
         let reset_secs = headers.get(X_RATE_LIMIT_RESET).and_then(parse_int_header);
-        Self::new(limit, remaining, reset_secs)
+
+        let app_24h_limit = headers.get(X_APP_LIMIT_24HOUR_LIMIT).and_then(parse_int_header);
+        let app_24h_remaining = headers.get(X_APP_LIMIT_24HOUR_REMAINING).and_then(parse_int_header);
+        let app_24h_reset_secs = headers.get(X_APP_LIMIT_24HOUR_RESET).and_then(parse_int_header);
+
+        Self {
+            app_24h_limit: opt_u64_encode(app_24h_limit),
+            app_24h_remaining: opt_u64_encode(app_24h_remaining),
+            app_24h_reset_secs: opt_u64_encode(app_24h_reset_secs),
+            ..Self::new(limit, remaining, reset_secs)
+        }
     }
     
     /// The rate limit ceiling for the endpoint that was used. This is the maximum number of times
@@ -51,29 +77,98 @@ impl LimitInfo {
         opt_u64_decode(self.limit)
     }
 
-    /// Returns the number of requests remaining that may be made to the endpoint before
-    /// [`reset_seconds`](Self::reset_seconds) seconds have passed.
+    /// Returns the number of requests remaining that may be made to the endpoint before the rate
+    /// limit resets at [`reset_at`](Self::reset_at).
     /// 
     /// Returns `None` if this information was not provided by the Twitter API.
     pub fn remaining(&self) -> Option<u64> {
         opt_u64_decode(self.remaining)
     }
 
-    /// Returns the number of seconds before the rate limit resets.
+    /// Returns the time at which the rate limit resets, as a Unix timestamp in seconds. Despite
+    /// the name, this is an absolute time rather than a number of seconds from now.
     /// 
     /// Returns `None` if this information was not provided by the Twitter API.
     /// 
-    /// If you want a [`Duration`](std::time::Duration), use
-    /// [`reset_duration`](Self::reset_duration).
+    /// For a [`DateTime`], use [`reset_at`](Self::reset_at). For the time remaining until the
+    /// reset, use [`reset_duration`](Self::reset_duration).
     pub fn reset_seconds(&self) -> Option<u64> {
         opt_u64_decode(self.reset_secs)
     }
 
-    /// Returns the time duration before the rate limit resets.
+    /// Returns the time at which the rate limit resets.
     /// 
     /// Returns `None` if this information was not provided by the Twitter API.
+    pub fn reset_at(&self) -> Option<DateTime<Utc>> {
+        let reset = i64::try_from(self.reset_seconds()?).ok()?;
+        Utc.timestamp_opt(reset, 0).single()
+    }
+
+    /// Returns the time remaining from now until the rate limit resets, which is zero if the reset
+    /// time has already passed.
+    /// 
+    /// Returns `None` if this information was not provided by the Twitter API, or if the reset
+    /// time is too far in the future to be represented.
     pub fn reset_duration(&self) -> Option<Duration> {
-        self.reset_seconds().map(Duration::from_secs)
+        self.reset_seconds().and_then(duration_until)
+    }
+
+    /// Waits until the rate limit resets. Returns immediately if the reset time has already passed
+    /// or was not provided by the Twitter API.
+    pub async fn sleep_until_reset(&self) {
+        if let Some(duration) = self.reset_duration() {
+            tokio::time::sleep(duration).await;
+        }
+    }
+
+    /// The app's 24-hour request cap, which applies across endpoints in addition to each
+    /// endpoint's own rate limit. Only returned by some endpoints, such as posting Tweets.
+    ///
+    /// Returns `None` if this information was not provided by the Twitter API.
+    pub fn app_24h_limit(&self) -> Option<u64> {
+        opt_u64_decode(self.app_24h_limit)
+    }
+
+    /// Returns the number of requests remaining under the app's 24-hour cap.
+    ///
+    /// Returns `None` if this information was not provided by the Twitter API.
+    pub fn app_24h_remaining(&self) -> Option<u64> {
+        opt_u64_decode(self.app_24h_remaining)
+    }
+
+    /// Returns the reset value of the app's 24-hour cap, in the same form as
+    /// [`reset_seconds`](Self::reset_seconds).
+    ///
+    /// Returns `None` if this information was not provided by the Twitter API.
+    pub fn app_24h_reset_seconds(&self) -> Option<u64> {
+        opt_u64_decode(self.app_24h_reset_secs)
+    }
+
+    /// Returns `true` if the app's 24-hour cap has been used up.
+    pub fn app_24h_exhausted(&self) -> bool {
+        self.app_24h_remaining() == Some(0)
+    }
+
+    /// Returns the reset value to wait for before retrying a rate-limited request. If the app's
+    /// 24-hour cap has been used up, this is whichever of the endpoint's reset and the 24-hour
+    /// cap's reset is later; otherwise it is the endpoint's reset.
+    ///
+    /// Returns `None` if the relevant reset was not provided by the Twitter API.
+    pub fn wait_reset_seconds(&self) -> Option<u64> {
+        if !self.app_24h_exhausted() {
+            return self.reset_seconds();
+        }
+
+        match (self.reset_seconds(), self.app_24h_reset_seconds()) {
+            (Some(reset), Some(app_24h_reset)) => Some(reset.max(app_24h_reset)),
+            (reset, app_24h_reset) => app_24h_reset.or(reset),
+        }
+    }
+
+    /// Returns how long to wait from now until [`wait_reset_seconds`](Self::wait_reset_seconds).
+    /// Returns zero if that time has already passed.
+    pub(crate) fn wait_reset_duration(&self) -> Option<Duration> {
+        self.wait_reset_seconds().and_then(duration_until)
     }
 }
 
@@ -83,6 +178,13 @@ impl Default for LimitInfo {
     }
 }
 
+/// Returns the time remaining from now until the given Unix timestamp, or zero if it has passed.
+/// Returns `None` if the timestamp is too large to be represented as a `SystemTime`.
+fn duration_until(timestamp_secs: u64) -> Option<Duration> {
+    let time = UNIX_EPOCH.checked_add(Duration::from_secs(timestamp_secs))?;
+    Some(time.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO))
+}
+
 fn opt_u64_encode(x: Option<u64>) -> Option<NonZeroU64> {
     x.and_then(|x| x.checked_add(1)).and_then(NonZeroU64::new)
 }
@@ -94,3 +196,72 @@ fn opt_u64_decode(x: Option<NonZeroU64>) -> Option<u64> {
 fn parse_int_header(val: &HeaderValue) -> Option<u64> {
     str::from_utf8(val.as_bytes()).ok().and_then(|val| val.parse().ok())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    use super::{
+        LimitInfo,
+        X_APP_LIMIT_24HOUR_LIMIT,
+        X_APP_LIMIT_24HOUR_REMAINING,
+        X_APP_LIMIT_24HOUR_RESET,
+        X_RATE_LIMIT_LIMIT,
+        X_RATE_LIMIT_REMAINING,
+        X_RATE_LIMIT_RESET,
+    };
+
+    fn headers(app_24h_remaining: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(X_RATE_LIMIT_LIMIT, HeaderValue::from_static("200"));
+        headers.insert(X_RATE_LIMIT_REMAINING, HeaderValue::from_static("0"));
+        headers.insert(X_RATE_LIMIT_RESET, HeaderValue::from_static("1670000900"));
+        headers.insert(X_APP_LIMIT_24HOUR_LIMIT, HeaderValue::from_static("500"));
+        headers.insert(X_APP_LIMIT_24HOUR_REMAINING, HeaderValue::from_static(app_24h_remaining));
+        headers.insert(X_APP_LIMIT_24HOUR_RESET, HeaderValue::from_static("1670080000"));
+        headers
+    }
+
+    #[test]
+    fn test_app_24h_limit() {
+        let limit_info = LimitInfo::from_headers(&headers("0"));
+        assert_eq!(limit_info.limit(), Some(200));
+        assert_eq!(limit_info.app_24h_limit(), Some(500));
+        assert_eq!(limit_info.app_24h_remaining(), Some(0));
+        assert!(limit_info.app_24h_exhausted());
+        assert_eq!(limit_info.wait_reset_seconds(), Some(1670080000));
+
+        let limit_info = LimitInfo::from_headers(&headers("12"));
+        assert!(!limit_info.app_24h_exhausted());
+        assert_eq!(limit_info.wait_reset_seconds(), Some(1670000900));
+
+        let limit_info = LimitInfo::from_headers(&HeaderMap::new());
+        assert_eq!(limit_info.app_24h_limit(), None);
+        assert_eq!(limit_info.wait_reset_seconds(), None);
+    }
+
+    #[test]
+    fn test_reset_at() {
+        let limit_info = LimitInfo::from_headers(&headers("12"));
+        assert_eq!(
+            limit_info.reset_at(),
+            Some(Utc.with_ymd_and_hms(2022, 12, 2, 17, 8, 20).unwrap())
+        );
+        // The reset time is in the past.
+        assert_eq!(limit_info.reset_duration(), Some(Duration::ZERO));
+
+        let limit_info = LimitInfo::from_headers(&HeaderMap::new());
+        assert_eq!(limit_info.reset_at(), None);
+        assert_eq!(limit_info.reset_duration(), None);
+
+        // A reset time too large to represent is treated as unknown rather than panicking.
+        let mut headers = HeaderMap::new();
+        headers.insert(X_RATE_LIMIT_RESET, HeaderValue::from_static("18446744073709551614"));
+        let limit_info = LimitInfo::from_headers(&headers);
+        assert_eq!(limit_info.reset_duration(), None);
+        assert_eq!(limit_info.wait_reset_duration(), None);
+    }
+}
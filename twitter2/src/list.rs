@@ -0,0 +1,76 @@
+use std::{fmt, str, num::ParseIntError};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{id::IdU64, user::UserId};
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[serde(from = "IdU64", into = "IdU64")]
+pub struct ListId(pub u64);
+
+impl From<IdU64> for ListId {
+    fn from(IdU64(id): IdU64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<ListId> for IdU64 {
+    fn from(ListId(id): ListId) -> Self {
+        Self(id)
+    }
+}
+
+impl fmt::Display for ListId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        <u64 as fmt::Display>::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for ListId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// A Twitter List. Only the id and name are returned by default; the other fields must be
+/// requested.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct List {
+    pub id: ListId,
+    pub name: Box<str>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub description: Option<Box<str>>,
+    pub follower_count: Option<u64>,
+    pub member_count: Option<u64>,
+    pub private: Option<bool>,
+    pub owner_id: Option<UserId>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::user::UserId;
+
+    use super::{List, ListId};
+
+    #[test]
+    fn test_list() {
+        let json = r#"{
+            "id": "1441162269824405510",
+            "name": "Rustaceans",
+            "follower_count": 12,
+            "private": false,
+            "owner_id": "2244994945"
+        }"#;
+
+        let list = serde_json::from_str::<List>(json).unwrap();
+        assert_eq!(list.id, ListId(1441162269824405510));
+        assert_eq!(&*list.name, "Rustaceans");
+        assert_eq!(list.follower_count, Some(12));
+        assert_eq!(list.member_count, None);
+        assert_eq!(list.private, Some(false));
+        assert_eq!(list.owner_id, Some(UserId(2244994945)));
+    }
+}
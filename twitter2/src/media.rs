@@ -1,4 +1,4 @@
-use std::{error, fmt, str};
+use std::{error, fmt, str, time::Duration};
 
 use enumscribe::{EnumDeserialize, EnumSerialize};
 use serde::{
@@ -108,6 +108,50 @@ pub struct Media {
     pub variants: Box<[MediaVariant]>,
 }
 
+impl Media {
+    /// Returns the width and height of the media in pixels, or `None` if either is missing
+    /// (for example, because the `width` and `height` media fields were not requested) or zero.
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        match (self.width, self.height) {
+            (Some(width), Some(height)) if width > 0 && height > 0 => Some((width, height)),
+            _ => None,
+        }
+    }
+
+    /// Returns the aspect ratio of the media as a `(width, height)` pair in lowest terms, such as
+    /// `(16, 9)`. Returns `None` if the dimensions are unknown.
+    pub fn aspect_ratio(&self) -> Option<(u32, u32)> {
+        self.dimensions().map(|(width, height)| {
+            let divisor = gcd(width, height);
+            (width / divisor, height / divisor)
+        })
+    }
+
+    /// Returns `true` if the media is wider than it is tall. Returns `false` if the dimensions
+    /// are unknown.
+    pub fn is_landscape(&self) -> bool {
+        self.dimensions().map_or(false, |(width, height)| width > height)
+    }
+
+    /// Returns `true` if the media is taller than it is wide. Returns `false` if the dimensions
+    /// are unknown.
+    pub fn is_portrait(&self) -> bool {
+        self.dimensions().map_or(false, |(width, height)| height > width)
+    }
+
+    /// Returns the duration of a video, or `None` if it is unknown or the media is not a video.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration_ms.map(|duration_ms| Duration::from_millis(duration_ms.into()))
+    }
+}
+
+fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
 #[derive(EnumSerialize, EnumDeserialize, Debug)]
 pub enum MediaType {
     #[enumscribe(str = "photo")]
@@ -149,3 +193,42 @@ impl MediaVariant {
         self.bit_rate
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Media;
+
+    fn media(width: Option<u32>, height: Option<u32>) -> Media {
+        let json = serde_json::json!({
+            "media_key": "3_1234",
+            "type": "video",
+            "width": width,
+            "height": height,
+            "duration_ms": 1500
+        });
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_aspect_ratio() {
+        let landscape = media(Some(1920), Some(1080));
+        assert_eq!(landscape.aspect_ratio(), Some((16, 9)));
+        assert!(landscape.is_landscape());
+        assert!(!landscape.is_portrait());
+        assert_eq!(landscape.duration(), Some(Duration::from_millis(1500)));
+
+        let portrait = media(Some(720), Some(1280));
+        assert_eq!(portrait.aspect_ratio(), Some((9, 16)));
+        assert!(portrait.is_portrait());
+
+        let square = media(Some(500), Some(500));
+        assert_eq!(square.aspect_ratio(), Some((1, 1)));
+        assert!(!square.is_landscape() && !square.is_portrait());
+
+        assert_eq!(media(None, Some(1080)).aspect_ratio(), None);
+        assert_eq!(media(Some(0), Some(1080)).aspect_ratio(), None);
+        assert!(!media(None, None).is_landscape());
+    }
+}
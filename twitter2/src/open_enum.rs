@@ -0,0 +1,89 @@
+/// Defines an enum of the string values Twitter is known to return for a field, plus a catch-all
+/// variant holding any other string, so that new values added by Twitter do not cause
+/// deserialisation to fail. The enum gets an `as_str` method, and is serialised and deserialised
+/// as its string, so a value read from the API can be passed back to it unchanged. The catch-all
+/// variant comes last, after a semicolon.
+///
+/// ```ignore
+/// open_enum! {
+///     #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+///     pub enum WithheldScope("a withheld scope string") {
+///         Tweet = "tweet",
+///         User = "user";
+///         /// A scope not yet known to this library.
+///         Other,
+///     }
+/// }
+/// ```
+macro_rules! open_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident($expecting:literal) {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $value:literal
+            ),+;
+            $(#[$other_meta:meta])*
+            $other:ident $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )*
+            $(#[$other_meta])*
+            $other(Box<str>),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)*
+                    Self::$other(value) => value,
+                }
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct OpenEnumVisitor;
+
+                impl<'de> serde::de::Visitor<'de> for OpenEnumVisitor {
+                    type Value = $name;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        formatter.write_str($expecting)
+                    }
+
+                    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        Ok(match v {
+                            $($value => $name::$variant,)*
+                            other => $name::$other(other.into()),
+                        })
+                    }
+                }
+
+                deserializer.deserialize_str(OpenEnumVisitor)
+            }
+        }
+    };
+}
+
+pub(crate) use open_enum;
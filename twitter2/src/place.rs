@@ -0,0 +1,77 @@
+use libshire::strings::InliningString23;
+use serde::{Deserialize, Serialize};
+
+/// A named location which a Tweet can be tagged with. Only the `id` and `full_name` are returned
+/// by default; the other fields must be requested.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Place {
+    pub id: InliningString23,
+    /// A longer name for the place, such as `Manhattan, NY`.
+    pub full_name: Box<str>,
+    /// A short name for the place, such as `Manhattan`.
+    pub name: Option<Box<str>>,
+    pub country: Option<Box<str>>,
+    /// The ISO 3166-1 alpha-2 code of the country the place is in.
+    pub country_code: Option<InliningString23>,
+    /// The kind of place, such as `city` or `poi`.
+    pub place_type: Option<InliningString23>,
+    /// The ids of the places which contain this place.
+    #[serde(default)]
+    pub contained_within: Box<[InliningString23]>,
+    pub geo: Option<PlaceGeo>,
+}
+
+/// The location of a place, given as a GeoJSON feature.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaceGeo {
+    /// The bounding box of the place, as `[west_longitude, south_latitude, east_longitude,
+    /// north_latitude]`.
+    pub bbox: [f64; 4],
+}
+
+impl PlaceGeo {
+    /// Returns `true` if the given point lies within the place's bounding box.
+    pub fn contains(&self, longitude: f64, latitude: f64) -> bool {
+        let [west, south, east, north] = self.bbox;
+        (west..=east).contains(&longitude) && (south..=north).contains(&latitude)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Place;
+
+    #[test]
+    fn test_place() {
+        let json = r#"{
+            "full_name": "Manhattan, NY",
+            "id": "01a9a39529b27f36",
+            "contained_within": [],
+            "country": "United States",
+            "country_code": "US",
+            "geo": {
+                "type": "Feature",
+                "bbox": [-74.026675, 40.683935, -73.910408, 40.877483],
+                "properties": {}
+            },
+            "name": "Manhattan",
+            "place_type": "city"
+        }"#;
+
+        let place = serde_json::from_str::<Place>(json).unwrap();
+        assert_eq!(&*place.id, "01a9a39529b27f36");
+        assert_eq!(&*place.full_name, "Manhattan, NY");
+        assert_eq!(place.country_code.as_deref(), Some("US"));
+        assert_eq!(place.place_type.as_deref(), Some("city"));
+        assert!(place.contained_within.is_empty());
+        let geo = place.geo.unwrap();
+        assert!(geo.contains(-74.0, 40.75));
+        assert!(!geo.contains(-0.1, 51.5));
+
+        let place = serde_json::from_str::<Place>(r#"{
+            "id": "01a9a39529b27f36",
+            "full_name": "Manhattan, NY"
+        }"#).unwrap();
+        assert!(place.geo.is_none());
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use libshire::strings::InliningString23;
+use serde::{Deserialize, Serialize};
+
+use crate::open_enum::open_enum;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Poll {
+    pub id: InliningString23,
+    pub options: Box<[PollOption]>,
+    pub voting_status: Option<PollVotingStatus>,
+    pub duration_minutes: Option<u32>,
+    pub end_datetime: Option<DateTime<Utc>>,
+}
+
+impl Poll {
+    /// Returns `true` if voting on the poll has closed. This requires the `voting_status` field to
+    /// have been requested, otherwise it always returns `false`.
+    pub fn is_closed(&self) -> bool {
+        self.voting_status == Some(PollVotingStatus::Closed)
+    }
+
+    /// Returns the total number of votes across all of the poll's options.
+    pub fn total_votes(&self) -> u64 {
+        self.options.iter().map(PollOption::votes).sum()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PollOption {
+    position: u32,
+    label: Box<str>,
+    votes: u64,
+}
+
+impl PollOption {
+    /// The 1-based position of the option in the poll.
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn votes(&self) -> u64 {
+        self.votes
+    }
+}
+
+open_enum! {
+    #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+    pub enum PollVotingStatus("a poll voting status string") {
+        Open = "open",
+        Closed = "closed";
+        /// A voting status not yet known to this library.
+        Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Poll, PollVotingStatus};
+
+    #[test]
+    fn test_poll() {
+        let json = r#"{
+            "id": "1199786642468413448",
+            "voting_status": "closed",
+            "duration_minutes": 1440,
+            "options": [
+                {"position": 1, "label": "“C Sharp”", "votes": 795},
+                {"position": 2, "label": "“C Hashtag”", "votes": 156}
+            ],
+            "end_datetime": "2019-11-28T20:26:41.000Z"
+        }"#;
+
+        let poll = serde_json::from_str::<Poll>(json).unwrap();
+        assert_eq!(poll.voting_status, Some(PollVotingStatus::Closed));
+        assert!(poll.is_closed());
+        assert_eq!(poll.total_votes(), 951);
+        assert_eq!(poll.options[1].label(), "“C Hashtag”");
+    }
+
+    #[test]
+    fn test_poll_voting_status() {
+        let json = r#"{
+            "id": "1199786642468413448",
+            "voting_status": "suspended",
+            "options": []
+        }"#;
+
+        let poll = serde_json::from_str::<Poll>(json).unwrap();
+        assert_eq!(poll.voting_status, Some(PollVotingStatus::Other("suspended".into())));
+        assert!(!poll.is_closed());
+        assert_eq!(poll.total_votes(), 0);
+    }
+}
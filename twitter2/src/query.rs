@@ -0,0 +1,452 @@
+use std::{convert::Infallible, error, fmt, mem, str::FromStr};
+
+use crate::lang::Lang;
+
+/// The maximum length of a search query or filtered stream rule for most access levels.
+pub const MAX_QUERY_LEN: usize = 512;
+
+/// The maximum length of a search query or filtered stream rule for Academic Research access.
+pub const MAX_QUERY_LEN_ACADEMIC: usize = 1024;
+
+const OR_SEPARATOR: &str = " OR ";
+
+/// Combines the given terms into as few queries as possible by joining them with `OR`, such that
+/// no query is longer than `max_len` characters. Terms are packed greedily in the order they are
+/// given, and empty terms are skipped.
+///
+/// A term which is longer than `max_len` by itself is returned as its own query, which Twitter
+/// will reject.
+pub fn split_query(terms: &[&str], max_len: usize) -> Vec<String> {
+    let mut queries = Vec::new();
+    let mut query = String::new();
+    let mut query_len = 0;
+
+    for term in terms.iter().filter(|term| !term.is_empty()) {
+        // Twitter measures query length in characters rather than bytes.
+        let term_len = term.chars().count();
+
+        if !query.is_empty() {
+            if query_len + OR_SEPARATOR.len() + term_len <= max_len {
+                query.push_str(OR_SEPARATOR);
+                query.push_str(term);
+                query_len += OR_SEPARATOR.len() + term_len;
+                continue;
+            }
+
+            queries.push(mem::take(&mut query));
+        }
+
+        query.push_str(term);
+        query_len = term_len;
+    }
+
+    if !query.is_empty() {
+        queries.push(query);
+    }
+
+    queries
+}
+
+/// A search query or filtered stream rule, built from operators. Operators are combined with
+/// [`and`](Self::and), [`or`](Self::or) and [`negate`](Self::negate), and the query string is
+/// produced by its `Display` implementation or by [`build`](Self::build).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Query {
+    node: QueryNode,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum QueryNode {
+    Operator(String),
+    Term(String),
+    Phrase(String),
+    Not(Box<QueryNode>),
+    And(Vec<QueryNode>),
+    Or(Vec<QueryNode>),
+}
+
+impl Query {
+    fn operator(operator: String) -> Self {
+        Self { node: QueryNode::Operator(operator) }
+    }
+
+    /// Matches Tweets containing the given keyword. The keyword is quoted if it contains
+    /// whitespace or characters which have a special meaning in queries, so untrusted input cannot
+    /// change the structure of the query.
+    pub fn term(term: &str) -> Self {
+        Self { node: QueryNode::Term(term.to_owned()) }
+    }
+
+    /// Matches Tweets containing the given exact phrase. Quotes and backslashes in the phrase are
+    /// escaped.
+    pub fn phrase(phrase: &str) -> Self {
+        Self { node: QueryNode::Phrase(phrase.to_owned()) }
+    }
+
+    /// Uses the given string as a query without any escaping or validation. This should only be
+    /// used for trusted queries.
+    pub fn raw(query: &str) -> Self {
+        Self::operator(query.to_owned())
+    }
+
+    /// Matches Tweets which Twitter has classified as being in the given language.
+    pub fn lang(lang: &Lang) -> Self {
+        Self::operator(format!("lang:{}", lang.as_str()))
+    }
+
+    /// Matches Tweets posted by the user with the given username. The username may optionally
+    /// be prefixed with `@`.
+    pub fn from_user(username: &str) -> Result<Self, QueryError> {
+        let username = check_username(username)?;
+        Ok(Self::operator(format!("from:{}", username)))
+    }
+
+    /// Matches Tweets which reply to the user with the given username. The username may
+    /// optionally be prefixed with `@`.
+    pub fn to_user(username: &str) -> Result<Self, QueryError> {
+        let username = check_username(username)?;
+        Ok(Self::operator(format!("to:{}", username)))
+    }
+
+    /// Matches Retweets. This does not match Quote Tweets.
+    pub fn is_retweet() -> Self {
+        Self::operator("is:retweet".to_owned())
+    }
+
+    /// Matches Tweets which are replies to another Tweet.
+    pub fn is_reply() -> Self {
+        Self::operator("is:reply".to_owned())
+    }
+
+    /// Matches Quote Tweets.
+    pub fn is_quote() -> Self {
+        Self::operator("is:quote".to_owned())
+    }
+
+    /// Matches Tweets which contain media such as photos, GIFs or videos.
+    pub fn has_media() -> Self {
+        Self::operator("has:media".to_owned())
+    }
+
+    /// Matches Tweets which contain links.
+    pub fn has_links() -> Self {
+        Self::operator("has:links".to_owned())
+    }
+
+    /// Matches Tweets which match both this query and `other`.
+    #[must_use]
+    pub fn and(self, other: Query) -> Self {
+        let mut nodes = match self.node {
+            QueryNode::And(nodes) => nodes,
+            node => vec![node],
+        };
+
+        match other.node {
+            QueryNode::And(other_nodes) => nodes.extend(other_nodes),
+            node => nodes.push(node),
+        }
+
+        Self { node: QueryNode::And(nodes) }
+    }
+
+    /// Matches Tweets which match either this query or `other`.
+    #[must_use]
+    pub fn or(self, other: Query) -> Self {
+        let mut nodes = match self.node {
+            QueryNode::Or(nodes) => nodes,
+            node => vec![node],
+        };
+
+        match other.node {
+            QueryNode::Or(other_nodes) => nodes.extend(other_nodes),
+            node => nodes.push(node),
+        }
+
+        Self { node: QueryNode::Or(nodes) }
+    }
+
+    /// Matches Tweets which do not match this query.
+    #[must_use]
+    pub fn negate(self) -> Self {
+        let node = match self.node {
+            QueryNode::Not(node) => *node,
+            node => QueryNode::Not(Box::new(node)),
+        };
+
+        Self { node }
+    }
+
+    /// Checks that the query is no longer than `max_len` characters and contains no empty terms
+    /// or phrases, returning the query string if so.
+    pub fn build(&self, max_len: usize) -> Result<String, QueryError> {
+        if self.node.has_empty_term() {
+            return Err(QueryError::EmptyTerm);
+        }
+
+        let query = self.to_string();
+        let len = query.chars().count();
+
+        if len > max_len {
+            return Err(QueryError::TooLong { len, max_len });
+        }
+
+        Ok(query)
+    }
+}
+
+/// Strips the optional leading `@` from a username and checks that it is valid.
+pub(crate) fn check_username(username: &str) -> Result<&str, QueryError> {
+    let username = username.strip_prefix('@').unwrap_or(username);
+
+    let valid = (1..=15).contains(&username.len())
+        && username.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_');
+
+    if valid {
+        Ok(username)
+    } else {
+        Err(QueryError::InvalidUsername(username.into()))
+    }
+}
+
+impl QueryNode {
+    fn has_empty_term(&self) -> bool {
+        match self {
+            Self::Operator(_) => false,
+            Self::Term(term) | Self::Phrase(term) => term.trim().is_empty(),
+            Self::Not(node) => node.has_empty_term(),
+            Self::And(nodes) | Self::Or(nodes) => nodes.iter().any(Self::has_empty_term),
+        }
+    }
+
+    fn is_single(&self) -> bool {
+        matches!(self, Self::Operator(_) | Self::Term(_) | Self::Phrase(_))
+    }
+}
+
+/// Returns `true` if the term can be used in a query as-is. Terms containing whitespace, quotes,
+/// parentheses or colons, terms starting with an operator prefix such as `-` or `#`, and the
+/// reserved words `OR` and `AND` need to be quoted.
+fn is_plain_term(term: &str) -> bool {
+    const OPERATOR_PREFIXES: &[char] = &['-', '#', '@', '$'];
+    const SPECIAL_CHARS: &[char] = &['"', '(', ')', ':', '\\'];
+
+    !term.is_empty()
+        && !term.starts_with(OPERATOR_PREFIXES)
+        && !term.contains(SPECIAL_CHARS)
+        && !term.contains(char::is_whitespace)
+        && term != "OR"
+        && term != "AND"
+}
+
+fn fmt_phrase(f: &mut fmt::Formatter<'_>, phrase: &str) -> fmt::Result {
+    f.write_str("\"")?;
+
+    for c in phrase.chars() {
+        if c == '"' || c == '\\' {
+            f.write_str("\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+
+    f.write_str("\"")
+}
+
+impl From<&str> for Query {
+    /// Equivalent to [`Query::term`].
+    fn from(term: &str) -> Self {
+        Self::term(term)
+    }
+}
+
+impl FromStr for Query {
+    type Err = Infallible;
+
+    /// Equivalent to [`Query::term`]. Use [`Query::raw`] to use a string as a query without
+    /// escaping it.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::term(s))
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
+impl fmt::Display for QueryNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Operator(operator) => f.write_str(operator),
+
+            Self::Term(term) if is_plain_term(term) => f.write_str(term),
+
+            Self::Term(phrase) | Self::Phrase(phrase) => fmt_phrase(f, phrase),
+
+            Self::Not(node) if node.is_single() => write!(f, "-{}", node),
+
+            Self::Not(node) => write!(f, "-({})", node),
+
+            Self::And(nodes) => fmt_joined(f, nodes, " ", |node| matches!(node, Self::Or(_))),
+
+            // `AND` binds more tightly than `OR`, but the parentheses make the query easier to
+            // read.
+            Self::Or(nodes) => fmt_joined(f, nodes, " OR ", |node| matches!(node, Self::And(_))),
+        }
+    }
+}
+
+fn fmt_joined<F>(
+    f: &mut fmt::Formatter<'_>,
+    nodes: &[QueryNode],
+    separator: &str,
+    needs_parens: F,
+) -> fmt::Result
+where
+    F: Fn(&QueryNode) -> bool,
+{
+    for (i, node) in nodes.iter().enumerate() {
+        if i > 0 {
+            f.write_str(separator)?;
+        }
+
+        if needs_parens(node) {
+            write!(f, "({})", node)?;
+        } else {
+            write!(f, "{}", node)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+pub enum QueryError {
+    /// A username passed to an operator was not a valid Twitter username.
+    InvalidUsername(Box<str>),
+    /// A term or phrase in the query was empty.
+    EmptyTerm,
+    /// The query is longer than the maximum length allowed.
+    TooLong { len: usize, max_len: usize },
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidUsername(username) => write!(f, "invalid username: {}", username),
+            Self::EmptyTerm => f.write_str("query contains an empty term"),
+            Self::TooLong { len, max_len } => write!(
+                f,
+                "query is {} characters long, but the maximum is {}",
+                len, max_len
+            ),
+        }
+    }
+}
+
+impl error::Error for QueryError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::lang::Lang;
+
+    use super::{split_query, Query, QueryError, MAX_QUERY_LEN};
+
+    #[test]
+    fn test_split_query() {
+        let terms = ["rust", "ferris", "cargo", "crab"];
+
+        assert_eq!(split_query(&terms, 512), ["rust OR ferris OR cargo OR crab"]);
+        assert_eq!(split_query(&terms, 17), ["rust OR ferris", "cargo OR crab"]);
+        assert_eq!(split_query(&terms, 14), ["rust OR ferris", "cargo OR crab"]);
+        assert_eq!(split_query(&terms, 13), ["rust", "ferris", "cargo OR crab"]);
+        assert_eq!(split_query(&terms, 3), ["rust", "ferris", "cargo", "crab"]);
+    }
+
+    #[test]
+    fn test_split_query_counts_chars() {
+        let terms = ["🦀🦀", "ü"];
+        assert_eq!(split_query(&terms, 7), ["🦀🦀 OR ü"]);
+        assert_eq!(split_query(&terms, 6), ["🦀🦀", "ü"]);
+    }
+
+    #[test]
+    fn test_split_query_empty() {
+        assert!(split_query(&[], 512).is_empty());
+        assert!(split_query(&["", ""], 512).is_empty());
+    }
+
+    #[test]
+    fn test_query_operators() {
+        let query = Query::from_user("@TwitterDev")
+            .unwrap()
+            .and(Query::lang(&Lang::new("en").unwrap()))
+            .and(Query::is_retweet().negate());
+
+        assert_eq!(query.to_string(), "from:TwitterDev lang:en -is:retweet");
+        assert_eq!(query.build(MAX_QUERY_LEN).unwrap(), query.to_string());
+
+        assert!(matches!(
+            query.build(10),
+            Err(QueryError::TooLong { len: 35, max_len: 10 })
+        ));
+    }
+
+    #[test]
+    fn test_query_grouping() {
+        let query = Query::to_user("a")
+            .unwrap()
+            .or(Query::to_user("b").unwrap())
+            .and(Query::has_media());
+        assert_eq!(query.to_string(), "(to:a OR to:b) has:media");
+
+        let query = Query::has_media()
+            .and(Query::is_quote())
+            .or(Query::has_links())
+            .negate();
+        assert_eq!(query.to_string(), "-((has:media is:quote) OR has:links)");
+
+        assert_eq!(Query::is_reply().negate().negate(), Query::is_reply());
+    }
+
+    #[test]
+    fn test_query_invalid_username() {
+        assert!(Query::from_user("").is_err());
+        assert!(Query::from_user("@").is_err());
+        assert!(Query::from_user("has:media").is_err());
+        assert!(Query::from_user("a_username_too_long").is_err());
+    }
+
+    #[test]
+    fn test_query_terms() {
+        assert_eq!(Query::term("ferris").to_string(), "ferris");
+        assert_eq!(Query::term("ferris crab").to_string(), r#""ferris crab""#);
+        assert_eq!(Query::term("has:media").to_string(), r#""has:media""#);
+        assert_eq!(Query::term("-rust").to_string(), r#""-rust""#);
+        assert_eq!(Query::term("#rust").to_string(), r##""#rust""##);
+        assert_eq!(Query::term("OR").to_string(), r#""OR""#);
+        assert_eq!(Query::term("(a").to_string(), r#""(a""#);
+
+        assert_eq!(
+            Query::phrase(r#"say "hi" \o/"#).to_string(),
+            r#""say \"hi\" \\o/""#
+        );
+
+        let query = Query::from("rust").and("is:retweet".parse::<Query>().unwrap().negate());
+        assert_eq!(query.to_string(), r#"rust -"is:retweet""#);
+
+        assert_eq!(Query::raw("has:media").to_string(), "has:media");
+    }
+
+    #[test]
+    fn test_query_empty_term() {
+        assert!(matches!(
+            Query::term(" ").build(MAX_QUERY_LEN),
+            Err(QueryError::EmptyTerm)
+        ));
+        assert!(matches!(
+            Query::has_media().and(Query::phrase("").negate()).build(MAX_QUERY_LEN),
+            Err(QueryError::EmptyTerm)
+        ));
+    }
+}
@@ -1,25 +1,44 @@
-use std::{fmt, num::NonZeroU8, ops, borrow::Cow};
+use std::{error, fmt, num::{NonZeroU8, NonZeroU16}, ops::{self, RangeInclusive}, borrow::Cow};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use enumscribe::ScribeStaticStr;
+use futures_util::{future::{self, BoxFuture}, stream, Stream, StreamExt};
 use libshire::{sink::{SinkString, StrSink, FmtSink}, convert::result_elim, sink_fmt};
 use serde::{Serialize, Deserialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 use crate::{
-    user::UserId,
+    user::{User, UserId},
     tweet::{TweetId, Tweet, ReplySettings},
     AsyncClient,
-    auth::{AppAuth, UserAuth},
+    auth::{AppAuth, UserAuth, Scope},
     client::{Error, Request, Method, ErrorRepr, ErrorKind},
     limit::LimitInfo,
-    response::Includes,
+    list::{List, ListId},
+    query,
+    response::{ApiV2Response, IdOnly, Includes, ResponseError},
     request_data::{FormData, JsonData},
-    request_options::{TweetPayloadExpansion, TweetField, UserField, MediaField},
-    timeline::PaginationToken
+    space::{Space, SpaceId},
+    stream::TweetStream,
+    request_options::{
+        Granularity,
+        SortOrder,
+        TweetPayloadExpansion,
+        TweetField,
+        UserField,
+        MediaField,
+        PlaceField,
+        PollField,
+        ListField,
+        SpaceField,
+        SpaceStateFilter,
+        UserPayloadExpansion,
+    },
+    text::{tweet_length, MAX_TWEET_LENGTH},
+    timeline::{PaginatedRequest, PaginatedResponse, PaginationToken, Paginator},
+    upload::MediaUploadId,
 };
 
-// FIXME: media, polls, geo, direct_message_deep_link
 #[derive(Serialize)]
 pub struct PostTweet<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -30,11 +49,35 @@ pub struct PostTweet<'a> {
     reply: Option<PostTweetReply<'a>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     quote_tweet_id: Option<TweetId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    media: Option<PostTweetMedia<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    poll: Option<PostTweetPoll<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    geo: Option<PostTweetGeo<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    direct_message_deep_link: Option<&'a str>,
     #[serde(skip_serializing_if = "ops::Not::not")]
     for_super_followers_only: bool,
+    #[serde(skip)]
+    expansions: String,
+    #[serde(skip)]
+    check_length: bool,
 }
 
 impl<'a> PostTweet<'a> {
+    /// The OAuth 2.0 scopes required to post a Tweet.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::TweetWrite, Scope::UsersRead];
+
+    /// The maximum number of pieces of media which can be attached to a Tweet.
+    pub const MAX_MEDIA: usize = 4;
+
+    /// The number of options a poll may have.
+    pub const POLL_OPTIONS_RANGE: RangeInclusive<usize> = 2..=4;
+
+    /// The number of minutes a poll may stay open for, from five minutes up to seven days.
+    pub const POLL_DURATION_RANGE: RangeInclusive<u16> = 5..=10080;
+
     #[inline]
     #[must_use]
     pub fn new_with_text(text: &'a str) -> Self {
@@ -43,7 +86,13 @@ impl<'a> PostTweet<'a> {
             reply_settings: ReplySettings::Everyone,
             reply: None,
             quote_tweet_id: None,
+            media: None,
+            poll: None,
+            geo: None,
+            direct_message_deep_link: None,
             for_super_followers_only: false,
+            expansions: String::new(),
+            check_length: false,
         }
     }
 
@@ -77,6 +126,68 @@ impl<'a> PostTweet<'a> {
         }
     }
 
+    /// Attaches up to [`Self::MAX_MEDIA`] pieces of media uploaded with
+    /// [`MediaUploader`](crate::upload::MediaUploader) to the Tweet, optionally tagging the given
+    /// users in it. Media cannot be attached to a Tweet with a poll.
+    ///
+    /// The number of media ids is checked when the request is executed, and
+    /// [`ErrorKind::Custom`] is returned without making a request if there are none or more than
+    /// [`Self::MAX_MEDIA`].
+    #[inline]
+    #[must_use]
+    pub fn media(self, media_ids: &'a [MediaUploadId], tagged: Option<&'a [UserId]>) -> Self {
+        Self {
+            media: Some(PostTweetMedia {
+                media_ids,
+                tagged_user_ids: tagged.unwrap_or_default(),
+            }),
+            ..self
+        }
+    }
+
+    /// Attaches a poll to the Tweet with the given options, which stays open for
+    /// `duration_minutes`. Polls cannot be attached to a Tweet with media.
+    ///
+    /// The poll is checked when the request is executed, and [`ErrorKind::Custom`] is returned
+    /// without making a request if the number of options is outside of
+    /// [`POLL_OPTIONS_RANGE`](Self::POLL_OPTIONS_RANGE) or the duration is outside of
+    /// [`POLL_DURATION_RANGE`](Self::POLL_DURATION_RANGE).
+    #[inline]
+    #[must_use]
+    pub fn poll<I>(self, options: I, duration_minutes: u16) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        Self {
+            poll: Some(PostTweetPoll {
+                options: options.into_iter().collect(),
+                duration_minutes,
+            }),
+            ..self
+        }
+    }
+
+    /// Tags the Tweet with a location, given as the id of a place such as `df51dec6f4ee2b2c`.
+    #[inline]
+    #[must_use]
+    pub fn geo(self, place_id: &'a str) -> Self {
+        Self {
+            geo: Some(PostTweetGeo { place_id }),
+            ..self
+        }
+    }
+
+    /// Includes a link in the Tweet which opens a Direct Message conversation with the author,
+    /// such as `https://twitter.com/messages/compose?recipient_id=2244994945`.
+    #[inline]
+    #[must_use]
+    pub fn direct_message_deep_link(self, url: &'a str) -> Self {
+        Self {
+            direct_message_deep_link: Some(url),
+            ..self
+        }
+    }
+
     #[inline]
     #[must_use]
     pub fn for_super_followers_only(self) -> Self {
@@ -86,6 +197,38 @@ impl<'a> PostTweet<'a> {
         }
     }
 
+    /// Expansions to resolve for the newly-created Tweet, which will be returned in
+    /// [`PostTweetResponse::includes`]. For example, [`TweetPayloadExpansion::AuthorId`] can be
+    /// used to check which account the Tweet was posted by.
+    ///
+    /// The create Tweet endpoint does not support expansions itself, so setting this causes an
+    /// additional Tweet lookup request to be made after the Tweet is posted. If that lookup
+    /// fails, the Tweet is still returned, with the error in
+    /// [`PostTweetResponse::includes_error`].
+    #[inline]
+    #[must_use]
+    pub fn expansions<I>(self, expansions: I) -> Self
+    where
+        I: IntoIterator<Item = TweetPayloadExpansion>,
+    {
+        Self {
+            expansions: scribe_comma_separated(expansions),
+            ..self
+        }
+    }
+
+    /// Checks the length of the Tweet's text with [`tweet_length`] before sending it, and
+    /// returns [`ErrorKind::TweetTooLong`] without making a request if it is longer than
+    /// [`MAX_TWEET_LENGTH`].
+    #[inline]
+    #[must_use]
+    pub fn check_length(self) -> Self {
+        Self {
+            check_length: true,
+            ..self
+        }
+    }
+
     pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<PostTweetResponse, Error>
     where
         A: UserAuth,
@@ -95,13 +238,25 @@ impl<'a> PostTweet<'a> {
             id: TweetId,
             text: Box<str>,
         }
-        
+
+        if self.check_length {
+            let length = self.text.map_or(0, tweet_length);
+            if length > MAX_TWEET_LENGTH {
+                return Err(ErrorRepr {
+                    kind: ErrorKind::TweetTooLong { length },
+                    limit_info: None,
+                }.boxed());
+            }
+        }
+
+        self.validate()?;
+
         let (response, limit_info)
             = client.apiv2_request::<_, Response>(Request::new_with_data(
                 Method::Post,
                 "https://api.twitter.com/2/tweets",
                 JsonData::new(self)
-            )).await?;
+            ).with_scopes(Self::SCOPES)).await?;
 
         let response_data = response
             .data
@@ -110,11 +265,56 @@ impl<'a> PostTweet<'a> {
                 limit_info: Some(limit_info.clone()),
             }.boxed())?;
 
-        Ok(PostTweetResponse {
-            id: response_data.id,
-            text: response_data.text,
-            limit_info,
-        })
+        // The Tweet has been posted at this point, so a failed lookup must not turn this into an
+        // error; otherwise, a caller retrying on error would post the Tweet twice.
+        let includes = if self.expansions.is_empty() {
+            Ok(Includes::default())
+        } else {
+            let lookup = LookupTweets {
+                ids: response_data.id.to_string(),
+                expansions: self.expansions.clone(),
+                tweet_fields: String::new(),
+                user_fields: String::new(),
+                media_fields: String::new(),
+                poll_fields: String::new(),
+                place_fields: String::new(),
+            };
+
+            lookup.execute(client).await.map(|response| response.includes)
+        };
+
+        Ok(PostTweetResponse::new(response_data.id, response_data.text, includes, limit_info))
+    }
+
+    /// Checks for combinations of parameters which Twitter would reject.
+    fn validate(&self) -> Result<(), Error> {
+        if let Some(media) = &self.media {
+            if media.media_ids.is_empty() {
+                return Err(invalid_params_error("at least one media id must be given"));
+            }
+
+            if media.media_ids.len() > Self::MAX_MEDIA {
+                return Err(invalid_params_error("at most 4 media ids can be attached to a Tweet"));
+            }
+        }
+
+        if let Some(poll) = &self.poll {
+            if self.media.is_some() {
+                return Err(invalid_params_error("a Tweet cannot have both media and a poll"));
+            }
+
+            if !Self::POLL_OPTIONS_RANGE.contains(&poll.options.len()) {
+                return Err(invalid_params_error("a poll must have between 2 and 4 options"));
+            }
+
+            if !Self::POLL_DURATION_RANGE.contains(&poll.duration_minutes) {
+                return Err(invalid_params_error(
+                    "poll duration must be between 5 and 10080 minutes"
+                ));
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -125,22 +325,76 @@ struct PostTweetReply<'a> {
     exclude_reply_user_ids: &'a [UserId],
 }
 
+#[derive(Serialize)]
+struct PostTweetMedia<'a> {
+    media_ids: &'a [MediaUploadId],
+    #[serde(skip_serializing_if = "<[UserId]>::is_empty")]
+    tagged_user_ids: &'a [UserId],
+}
+
+#[derive(Serialize)]
+struct PostTweetPoll<'a> {
+    options: Vec<&'a str>,
+    duration_minutes: u16,
+}
+
+#[derive(Serialize)]
+struct PostTweetGeo<'a> {
+    place_id: &'a str,
+}
+
 #[derive(Debug)]
 pub struct PostTweetResponse {
     pub id: TweetId,
     pub text: Box<str>,
+    /// Objects resolved by the expansions requested with [`PostTweet::expansions`]. Empty if no
+    /// expansions were requested, or if looking them up failed.
+    pub includes: Includes,
+    /// The error returned by the lookup request made to resolve the expansions, if it failed.
+    /// The Tweet was still posted successfully in this case.
+    pub includes_error: Option<Error>,
+    /// Rate limit information for the create Tweet endpoint.
     pub limit_info: LimitInfo,
 }
 
+impl PostTweetResponse {
+    fn new(
+        id: TweetId,
+        text: Box<str>,
+        includes: Result<Includes, Error>,
+        limit_info: LimitInfo
+    ) -> Self
+    {
+        let (includes, includes_error) = match includes {
+            Ok(includes) => (includes, None),
+            Err(err) => (Includes::default(), Some(err)),
+        };
+
+        Self {
+            id,
+            text,
+            includes,
+            includes_error,
+            limit_info,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct LookupTweets {
     ids: String,
     expansions: String,
     tweet_fields: String,
     user_fields: String,
     media_fields: String,
+    poll_fields: String,
+    place_fields: String,
 }
 
 impl LookupTweets {
+    /// The OAuth 2.0 scopes required to look up Tweets.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead];
+
     #[inline]
     #[must_use]
     pub fn new<I>(ids: I) -> Self
@@ -155,6 +409,8 @@ impl LookupTweets {
             tweet_fields: String::new(),
             user_fields: String::new(),
             media_fields: String::new(),
+            poll_fields: String::new(),
+            place_fields: String::new(),
         }
     }
 
@@ -206,6 +462,42 @@ impl LookupTweets {
         }
     }
 
+    #[inline]
+    #[must_use]
+    pub fn poll_fields<I>(self, poll_fields: I) -> Self
+    where
+        I: IntoIterator<Item = PollField>,
+    {
+        Self {
+            poll_fields: scribe_comma_separated(poll_fields),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn place_fields<I>(self, place_fields: I) -> Self
+    where
+        I: IntoIterator<Item = PlaceField>,
+    {
+        Self {
+            place_fields: scribe_comma_separated(place_fields),
+            ..self
+        }
+    }
+
+    /// Returns the field and expansion lists that will be sent with this request.
+    pub fn describe_request(&self) -> RequestFields {
+        RequestFields {
+            expansions: &self.expansions,
+            tweet_fields: &self.tweet_fields,
+            user_fields: &self.user_fields,
+            media_fields: &self.media_fields,
+            poll_fields: &self.poll_fields,
+            place_fields: &self.place_fields,
+        }
+    }
+
     pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<LookupTweetsResponse, Error>
     where
         A: AppAuth,
@@ -245,12 +537,26 @@ impl LookupTweets {
             ));
         }
 
+        if !self.poll_fields.is_empty() {
+            params.push((
+                Cow::Borrowed("poll.fields"),
+                Cow::Borrowed(&self.poll_fields)
+            ));
+        }
+
+        if !self.place_fields.is_empty() {
+            params.push((
+                Cow::Borrowed("place.fields"),
+                Cow::Borrowed(&self.place_fields)
+            ));
+        }
+
         let (response, limit_info)
             = client.apiv2_request::<_, Box<[Tweet]>>(Request::new_with_data(
                 Method::Get,
                 "https://api.twitter.com/2/tweets",
                 FormData::new(&params)
-            )).await?;
+            ).with_scopes(Self::SCOPES)).await?;
 
         let tweets = response
             .data
@@ -262,6 +568,7 @@ impl LookupTweets {
         Ok(LookupTweetsResponse {
             tweets,
             includes: response.includes,
+            errors: response.errors,
             limit_info,
         })
     }
@@ -271,126 +578,51 @@ impl LookupTweets {
 pub struct LookupTweetsResponse {
     pub tweets: Box<[Tweet]>,
     pub includes: Includes,
+    /// Errors for any requested Tweets which could not be returned, for example because they were
+    /// deleted or are protected.
+    pub errors: Box<[ResponseError]>,
     pub limit_info: LimitInfo,
 }
 
-pub struct UserTimeline {
-    id: UserId,
-    start_time: Option<DateTime<Utc>>,
-    end_time: Option<DateTime<Utc>>,
-    exclude_retweets: bool,
-    exclude_replies: bool,
-    max_results: Option<NonZeroU8>,
-    pagination_token: Option<PaginationToken>,
-    since_id: Option<TweetId>,
-    until_id: Option<TweetId>,
+/// A request to look up multiple users by their usernames.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LookupUsersByUsername {
+    usernames: Vec<String>,
     expansions: String,
     tweet_fields: String,
     user_fields: String,
-    media_fields: String,
 }
 
-impl UserTimeline {
+impl LookupUsersByUsername {
+    /// The OAuth 2.0 scopes required to look up users.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead];
+
+    /// The maximum number of usernames which can be looked up in a single request.
+    pub const MAX_USERNAMES: usize = 100;
+
+    /// Creates a request for the users with the given usernames, which may optionally start with
+    /// `@`. The usernames are validated when the request is executed.
     #[inline]
     #[must_use]
-    pub fn new(id: UserId) -> Self {
+    pub fn new<'a, I>(usernames: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
         Self {
-            id,
-            start_time: None,
-            end_time: None,
-            exclude_retweets: false,
-            exclude_replies: false,
-            max_results: None,
-            pagination_token: None,
-            since_id: None,
-            until_id: None,
+            usernames: usernames.into_iter().map(ToOwned::to_owned).collect(),
             expansions: String::new(),
             tweet_fields: String::new(),
             user_fields: String::new(),
-            media_fields: String::new(),
-        }
-    }
-
-    #[inline]
-    #[must_use]
-    pub fn start_time(self, start_time: DateTime<Utc>) -> Self {
-        Self {
-            start_time: Some(start_time),
-            ..self
-        }
-    }
-
-    #[inline]
-    #[must_use]
-    pub fn end_time(self, end_time: DateTime<Utc>) -> Self {
-        Self {
-            end_time: Some(end_time),
-            ..self
-        }
-    }
-
-    #[inline]
-    #[must_use]
-    pub fn exclude_retweets(self) -> Self {
-        Self {
-            exclude_retweets: true,
-            ..self
-        }
-    }
-
-    #[inline]
-    #[must_use]
-    pub fn exclude_replies(self) -> Self {
-        Self {
-            exclude_replies: true,
-            ..self
-        }
-    }
-
-    #[inline]
-    #[must_use]
-    pub fn max_results(self, max_results: u8) -> Self {
-        let max_results = max_results.clamp(5, 100);
-        Self {
-            max_results: Some(NonZeroU8::new(max_results).unwrap()),
-            ..self
         }
     }
 
-    #[inline]
-    #[must_use]
-    pub fn pagination_token(self, pagination_token: PaginationToken) -> Self {
-        Self {
-            pagination_token: Some(pagination_token),
-            ..self
-        }
-    }
-
-    #[inline]
-    #[must_use]
-    pub fn since_id(self, since_id: TweetId) -> Self {
-        Self {
-            since_id: Some(since_id),
-            ..self
-        }
-    }
-
-    #[inline]
-    #[must_use]
-    pub fn until_id(self, until_id: TweetId) -> Self {
-        Self {
-            until_id: Some(until_id),
-            ..self
-        }
-    }
-
-    // FIXME: use a decidated expansion type for each endpoint, since different endpoints allow
-    // different expansions (even when they have the same payload type).
+    /// Expansions to resolve for each user. [`UserPayloadExpansion::PinnedTweetId`] returns the
+    /// users' pinned Tweets in [`Includes::tweets`].
     #[inline]
     #[must_use]
     pub fn expansions<I>(self, expansions: I) -> Self
     where
-        I: IntoIterator<Item = TweetPayloadExpansion>,
+        I: IntoIterator<Item = UserPayloadExpansion>,
     {
         Self {
             expansions: scribe_comma_separated(expansions),
@@ -422,153 +654,3043 @@ impl UserTimeline {
         }
     }
 
-    #[inline]
-    #[must_use]
-    pub fn media_fields<I>(self, media_fields: I) -> Self
-    where
-        I: IntoIterator<Item = MediaField>,
-    {
-        Self {
-            media_fields: scribe_comma_separated(media_fields),
-            ..self
+    /// Returns the field and expansion lists that will be sent with this request.
+    pub fn describe_request(&self) -> RequestFields {
+        RequestFields {
+            expansions: &self.expansions,
+            tweet_fields: &self.tweet_fields,
+            user_fields: &self.user_fields,
+            media_fields: "",
+            poll_fields: "",
+            place_fields: "",
+        }
+    }
+
+    /// Returns the usernames as a comma-separated list, without any leading `@`s, or an error if
+    /// any of them are invalid or there are too many.
+    fn usernames_param(&self) -> Result<String, Error> {
+        if self.usernames.len() > Self::MAX_USERNAMES {
+            return Err(ErrorRepr {
+                kind: ErrorKind::Custom(format!(
+                    "cannot look up {} usernames at once, the maximum is {}",
+                    self.usernames.len(),
+                    Self::MAX_USERNAMES
+                ).into()),
+                limit_info: None,
+            }.boxed());
         }
+
+        let mut usernames = String::new();
+
+        for username in &self.usernames {
+            let username = query::check_username(username)
+                .map_err(|err| ErrorRepr {
+                    kind: ErrorKind::Custom(err.to_string().into()),
+                    limit_info: None,
+                }.boxed())?;
+
+            if !usernames.is_empty() {
+                usernames.push(',');
+            }
+            usernames.push_str(username);
+        }
+
+        Ok(usernames)
     }
 
-    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<UserTimelineResponse, Error>
+    /// Looks up the users. Users which could not be returned are reported in the response's
+    /// [`errors`](LookupUsersResponse::errors), but if none of the users could be returned, the
+    /// lookup fails with an [`ErrorResponse`](ErrorKind::ErrorResponse) error instead, for which
+    /// [`Error::is_not_found`] returns `true` if the users do not exist.
+    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<LookupUsersResponse, Error>
     where
         A: AppAuth,
     {
+        let usernames = self.usernames_param()?;
+
         let mut params = Vec::new();
 
-        if let Some(start_time) = self.start_time {
-            params.push((
-                Cow::Borrowed("start_time"),
-                Cow::Owned(start_time.to_rfc3339())
-            ));
-        }
+        // The form data is percent-encoded, so the commas are sent as `%2C`.
+        params.push((
+            Cow::Borrowed("usernames"),
+            Cow::Borrowed(usernames.as_str())
+        ));
 
-        if let Some(end_time) = self.end_time {
-            params.push((
-                Cow::Borrowed("start_time"),
-                Cow::Owned(end_time.to_rfc3339())
-            ));
-        }
+        push_field_params(&mut params, self.describe_request());
 
-        let excludes = match (self.exclude_retweets, self.exclude_replies) {
-            (true, true) => Some("retweets,replies"),
-            (true, false) => Some("retweets"),
-            (false, true) => Some("replies"),
-            (false, false) => None,
+        let (response, limit_info)
+            = client.apiv2_request::<_, Box<[User]>>(Request::new_with_data(
+                Method::Get,
+                "https://api.twitter.com/2/users/by",
+                FormData::new(&params)
+            ).with_scopes(Self::SCOPES)).await?;
+
+        // A response with no `data` but some `errors` has already been turned into an error by
+        // `apiv2_request`, so `data` can only be missing here if there were no errors either.
+        let users = response.data.unwrap_or_default();
+
+        Ok(LookupUsersResponse {
+            users,
+            includes: response.includes,
+            errors: response.errors,
+            limit_info,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct LookupUsersResponse {
+    pub users: Box<[User]>,
+    pub includes: Includes,
+    /// Errors for any requested users which could not be returned, for example because they do
+    /// not exist or are suspended.
+    pub errors: Box<[ResponseError]>,
+    pub limit_info: LimitInfo,
+}
+
+// The reverse-chronological timeline endpoints take the same parameters, differing only in their
+// URL, auth, scopes, page size and whether they can exclude Retweets and replies, so their request
+// types are generated by this macro.
+macro_rules! timeline_request {
+    ($(
+        $(#[$attr:meta])*
+        $request:ident {
+            url: $url:literal,
+            auth: $auth:ident,
+            $(#[$scopes_attr:meta])*
+            scopes: $scopes:expr,
+            max_results: $max_results:ty = $range:expr,
+            $(exclude: $exclude:ident,)?
+        }
+    )*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Serialize, Deserialize, Clone, Debug)]
+            pub struct $request {
+                id: UserId,
+                start_time: Option<DateTime<Utc>>,
+                end_time: Option<DateTime<Utc>>,
+                $(
+                    #[serde(flatten)]
+                    exclude: $exclude,
+                )?
+                max_results: Option<NonZeroU16>,
+                pagination_token: Option<PaginationToken>,
+                since_id: Option<TweetId>,
+                until_id: Option<TweetId>,
+                stop_at: Option<TweetId>,
+                expansions: String,
+                tweet_fields: String,
+                user_fields: String,
+                media_fields: String,
+                poll_fields: String,
+                place_fields: String,
+            }
+
+            impl $request {
+                $(#[$scopes_attr])*
+                pub const SCOPES: &'static [Scope] = $scopes;
+
+                /// The range of values accepted for `max_results`.
+                pub const MAX_RESULTS_RANGE: RangeInclusive<u16> = $range;
+
+                #[inline]
+                #[must_use]
+                pub fn new(id: UserId) -> Self {
+                    Self {
+                        id,
+                        start_time: None,
+                        end_time: None,
+                        $(exclude: $exclude::default(),)?
+                        max_results: None,
+                        pagination_token: None,
+                        since_id: None,
+                        until_id: None,
+                        stop_at: None,
+                        expansions: String::new(),
+                        tweet_fields: String::new(),
+                        user_fields: String::new(),
+                        media_fields: String::new(),
+                        poll_fields: String::new(),
+                        place_fields: String::new(),
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn start_time(self, start_time: DateTime<Utc>) -> Self {
+                    Self {
+                        start_time: Some(start_time),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn end_time(self, end_time: DateTime<Utc>) -> Self {
+                    Self {
+                        end_time: Some(end_time),
+                        ..self
+                    }
+                }
+
+                $(
+                    #[inline]
+                    #[must_use]
+                    pub fn exclude_retweets(self) -> Self {
+                        Self {
+                            exclude: $exclude { retweets: true, ..self.exclude },
+                            ..self
+                        }
+                    }
+
+                    #[inline]
+                    #[must_use]
+                    pub fn exclude_replies(self) -> Self {
+                        Self {
+                            exclude: $exclude { replies: true, ..self.exclude },
+                            ..self
+                        }
+                    }
+                )?
+
+                #[inline]
+                #[must_use]
+                pub fn max_results(self, max_results: $max_results) -> Self {
+                    Self {
+                        max_results: clamp_max_results(
+                            max_results.into(),
+                            Self::MAX_RESULTS_RANGE
+                        ),
+                        ..self
+                    }
+                }
+
+                /// Sets the maximum number of Tweets to return per page, returning an error if
+                /// it is outside of [`MAX_RESULTS_RANGE`](Self::MAX_RESULTS_RANGE) rather than
+                /// clamping it.
+                #[inline]
+                pub fn try_max_results(self, max_results: u16) -> Result<Self, MaxResultsError> {
+                    let max_results = check_max_results(max_results, Self::MAX_RESULTS_RANGE)?;
+                    Ok(Self {
+                        max_results: Some(max_results),
+                        ..self
+                    })
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn pagination_token(self, pagination_token: PaginationToken) -> Self {
+                    Self {
+                        pagination_token: Some(pagination_token),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn since_id(self, since_id: TweetId) -> Self {
+                    Self {
+                        since_id: Some(since_id),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn until_id(self, until_id: TweetId) -> Self {
+                    Self {
+                        until_id: Some(until_id),
+                        ..self
+                    }
+                }
+
+                /// Stops at the first Tweet with an id less than or equal to the given
+                /// watermark. That Tweet and any after it are removed from the response, and no
+                /// `next_token` is returned so that pagination ends there.
+                ///
+                /// This is useful when polling for new Tweets, as a safeguard in addition to
+                /// [`since_id`](Self::since_id).
+                #[inline]
+                #[must_use]
+                pub fn stop_at(self, watermark: TweetId) -> Self {
+                    Self {
+                        stop_at: Some(watermark),
+                        ..self
+                    }
+                }
+
+                // FIXME: use a decidated expansion type for each endpoint, since different
+                // endpoints allow different expansions (even when they have the same payload
+                // type).
+                #[inline]
+                #[must_use]
+                pub fn expansions<I>(self, expansions: I) -> Self
+                where
+                    I: IntoIterator<Item = TweetPayloadExpansion>,
+                {
+                    Self {
+                        expansions: scribe_comma_separated(expansions),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn tweet_fields<I>(self, tweet_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = TweetField>,
+                {
+                    Self {
+                        tweet_fields: scribe_comma_separated(tweet_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn user_fields<I>(self, user_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = UserField>,
+                {
+                    Self {
+                        user_fields: scribe_comma_separated(user_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn media_fields<I>(self, media_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = MediaField>,
+                {
+                    Self {
+                        media_fields: scribe_comma_separated(media_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn poll_fields<I>(self, poll_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = PollField>,
+                {
+                    Self {
+                        poll_fields: scribe_comma_separated(poll_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn place_fields<I>(self, place_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = PlaceField>,
+                {
+                    Self {
+                        place_fields: scribe_comma_separated(place_fields),
+                        ..self
+                    }
+                }
+
+                /// Returns the field and expansion lists that will be sent with this request.
+                pub fn describe_request(&self) -> RequestFields {
+                    RequestFields {
+                        expansions: &self.expansions,
+                        tweet_fields: &self.tweet_fields,
+                        user_fields: &self.user_fields,
+                        media_fields: &self.media_fields,
+                        poll_fields: &self.poll_fields,
+                        place_fields: &self.place_fields,
+                    }
+                }
+
+                pub async fn execute<A>(
+                    &self,
+                    client: &AsyncClient<A>
+                ) -> Result<UserTimelineResponse, Error>
+                where
+                    A: $auth,
+                {
+                    self.execute_as::<A, Tweet>(client).await
+                }
+
+                /// Like [`execute`](Self::execute), but only deserialises the id of each Tweet,
+                /// which is cheaper for large pages when only the ids are needed, such as when
+                /// polling for the newest Tweet. Any requested expansions are still returned in
+                /// the response's includes.
+                pub async fn execute_ids<A>(
+                    &self,
+                    client: &AsyncClient<A>
+                ) -> Result<UserTimelineResponse<IdOnly<TweetId>>, Error>
+                where
+                    A: $auth,
+                {
+                    self.execute_as::<A, IdOnly<TweetId>>(client).await
+                }
+
+                /// Returns a stream of the Tweets in the timeline, which requests each page in
+                /// turn with a [`Paginator`] by following `next_token` until there are no more
+                /// pages. The includes of each page are discarded.
+                ///
+                /// If a request fails, its error is yielded. Polling the stream again retries the
+                /// failed page if the error is [retryable](Error::is_retryable), and otherwise
+                /// the stream ends.
+                pub fn into_stream<'c, A>(
+                    self,
+                    client: &'c AsyncClient<A>
+                ) -> impl Stream<Item = Result<Tweet, Error>> + 'c
+                where
+                    A: $auth + Send + Sync,
+                {
+                    Paginator::new(client, self)
+                        .into_stream()
+                        .flat_map(|page| match page {
+                            Ok(response) => {
+                                stream::iter(response.tweets.into_vec().into_iter().map(Ok))
+                                    .left_stream()
+                            },
+                            Err(err) => stream::once(future::ready(Err(err))).right_stream(),
+                        })
+                }
+
+                fn params(&self) -> Vec<(Cow<str>, Cow<str>)> {
+                    let mut params = Vec::new();
+
+                    if let Some(start_time) = self.start_time {
+                        params.push((
+                            Cow::Borrowed("start_time"),
+                            Cow::Owned(start_time.to_rfc3339())
+                        ));
+                    }
+
+                    if let Some(end_time) = self.end_time {
+                        params.push((
+                            Cow::Borrowed("end_time"),
+                            Cow::Owned(end_time.to_rfc3339())
+                        ));
+                    }
+
+                    $(
+                        if let Some(excludes) = $exclude::param(&self.exclude) {
+                            params.push((
+                                Cow::Borrowed("exclude"),
+                                Cow::Borrowed(excludes)
+                            ));
+                        }
+                    )?
+
+                    if let Some(max_results) = self.max_results {
+                        params.push((
+                            Cow::Borrowed("max_results"),
+                            Cow::Owned(format!("{}", max_results))
+                        ));
+                    }
+
+                    if let Some(pagination_token) = self.pagination_token.as_ref() {
+                        params.push((
+                            Cow::Borrowed("pagination_token"),
+                            Cow::Borrowed(pagination_token.as_str())
+                        ));
+                    }
+
+                    if let Some(since_id) = self.since_id {
+                        params.push((
+                            Cow::Borrowed("since_id"),
+                            Cow::Owned(format!("{}", since_id))
+                        ));
+                    }
+
+                    if let Some(until_id) = self.until_id {
+                        params.push((
+                            Cow::Borrowed("until_id"),
+                            Cow::Owned(format!("{}", until_id))
+                        ));
+                    }
+
+                    push_field_params(&mut params, self.describe_request());
+
+                    params
+                }
+
+                async fn execute_as<A, T>(
+                    &self,
+                    client: &AsyncClient<A>
+                ) -> Result<UserTimelineResponse<T>, Error>
+                where
+                    A: $auth,
+                    T: TweetModel,
+                {
+                    let params = self.params();
+
+                    let (response, limit_info)
+                        = client.apiv2_request::<_, Box<[T]>>(Request::new_with_data(
+                            Method::Get,
+                            &format!($url, self.id),
+                            FormData::new(&params)
+                        ).with_scopes(Self::SCOPES)).await?;
+
+                    Ok(self.page_from_response(response, limit_info))
+                }
+
+                /// Builds a page of the timeline from a response, applying the
+                /// [`stop_at`](Self::stop_at) watermark.
+                fn page_from_response<T>(
+                    &self,
+                    mut response: ApiV2Response<Box<[T]>>,
+                    limit_info: LimitInfo
+                ) -> UserTimelineResponse<T>
+                where
+                    T: TweetModel,
+                {
+                    // Twitter omits `data` entirely when there are no Tweets in the requested
+                    // range, such as when polling with `since_id` and there are no new Tweets.
+                    let tweets = response.data.unwrap_or_default();
+
+                    let previous_token =
+                        meta_pagination_token(&mut response.meta, "previous_token");
+                    let next_token = meta_pagination_token(&mut response.meta, "next_token");
+
+                    // Timelines are in reverse-chronological order, so every Tweet after the
+                    // first one at or below the watermark is also at or below the watermark.
+                    let watermark_index = self.stop_at.and_then(|watermark| {
+                        tweets.iter().position(|tweet| tweet.tweet_id() <= watermark)
+                    });
+
+                    let (tweets, next_token) = match watermark_index {
+                        Some(watermark_index) => {
+                            let mut tweets = Vec::from(tweets);
+                            tweets.truncate(watermark_index);
+                            (tweets.into_boxed_slice(), None)
+                        },
+                        None => (tweets, next_token),
+                    };
+
+                    UserTimelineResponse {
+                        tweets,
+                        includes: response.includes,
+                        previous_token,
+                        next_token,
+                        limit_info,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+timeline_request! {
+    /// A request for a user's Tweet timeline.
+    ///
+    /// The builder's state, including its pagination token, can be serialised so that a
+    /// paginated crawl can be checkpointed and resumed later.
+    UserTimeline {
+        url: "https://api.twitter.com/2/users/{}/tweets",
+        auth: AppAuth,
+        /// The OAuth 2.0 scopes required to retrieve a user's Tweet timeline.
+        scopes: &[Scope::TweetRead, Scope::UsersRead],
+        max_results: u8 = 5..=100,
+        exclude: TimelineExclude,
+    }
+
+    /// A request for the Tweets which mention a user.
+    ///
+    /// Like [`UserTimeline`], the builder's state can be serialised to checkpoint a paginated
+    /// crawl.
+    UserMentions {
+        url: "https://api.twitter.com/2/users/{}/mentions",
+        auth: AppAuth,
+        /// The OAuth 2.0 scopes required to retrieve the Tweets mentioning a user.
+        scopes: &[Scope::TweetRead, Scope::UsersRead],
+        max_results: u8 = 5..=100,
+    }
+
+    /// A request for the authenticating user's home timeline: the Tweets and Retweets posted by
+    /// the user and the users they follow, in reverse-chronological order. The user id must be
+    /// the authenticating user's.
+    HomeTimeline {
+        url: "https://api.twitter.com/2/users/{}/timelines/reverse_chronological",
+        auth: UserAuth,
+        /// The OAuth 2.0 scopes required to retrieve a user's home timeline.
+        scopes: &[Scope::TweetRead, Scope::UsersRead],
+        max_results: u8 = 1..=100,
+        exclude: TimelineExclude,
+    }
+}
+
+/// The kinds of Tweet to leave out of a timeline or list of Tweets. These are serialised as the
+/// request's own `exclude_retweets` and `exclude_replies` fields.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug)]
+struct TimelineExclude {
+    #[serde(rename = "exclude_retweets")]
+    retweets: bool,
+    #[serde(rename = "exclude_replies")]
+    replies: bool,
+}
+
+impl TimelineExclude {
+    /// Returns the value of the `exclude` parameter, or `None` if nothing is excluded.
+    fn param(&self) -> Option<&'static str> {
+        match (self.retweets, self.replies) {
+            (true, true) => Some("retweets,replies"),
+            (true, false) => Some("retweets"),
+            (false, true) => Some("replies"),
+            (false, false) => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct UserTimelineResponse<T = Tweet> {
+    pub tweets: Box<[T]>,
+    pub includes: Includes,
+    pub previous_token: Option<PaginationToken>,
+    pub next_token: Option<PaginationToken>,
+    pub limit_info: LimitInfo,
+}
+
+pub type UserMentionsResponse = UserTimelineResponse;
+
+pub type HomeTimelineResponse = UserTimelineResponse;
+
+// The recent and full-archive search endpoints take the same parameters, so their request types
+// are generated by this macro. Each type defines its own scopes and `execute`.
+macro_rules! search_request {
+    ($(
+        $(#[$attr:meta])*
+        $request:ident {
+            max_results: $max_results:ty = $range:expr,
+        }
+    )*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Serialize, Deserialize, Clone, Debug)]
+            pub struct $request {
+                query: String,
+                start_time: Option<DateTime<Utc>>,
+                end_time: Option<DateTime<Utc>>,
+                max_results: Option<NonZeroU16>,
+                next_token: Option<PaginationToken>,
+                since_id: Option<TweetId>,
+                until_id: Option<TweetId>,
+                sort_order: Option<SortOrder>,
+                expansions: String,
+                tweet_fields: String,
+                user_fields: String,
+                media_fields: String,
+                poll_fields: String,
+                place_fields: String,
+            }
+
+            impl $request {
+                /// The range of values accepted for `max_results`.
+                pub const MAX_RESULTS_RANGE: RangeInclusive<u16> = $range;
+
+                #[inline]
+                #[must_use]
+                pub fn new<Q>(query: Q) -> Self
+                where
+                    Q: fmt::Display,
+                {
+                    Self {
+                        query: query.to_string(),
+                        start_time: None,
+                        end_time: None,
+                        max_results: None,
+                        next_token: None,
+                        since_id: None,
+                        until_id: None,
+                        sort_order: None,
+                        expansions: String::new(),
+                        tweet_fields: String::new(),
+                        user_fields: String::new(),
+                        media_fields: String::new(),
+                        poll_fields: String::new(),
+                        place_fields: String::new(),
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn start_time(self, start_time: DateTime<Utc>) -> Self {
+                    Self {
+                        start_time: Some(start_time),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn end_time(self, end_time: DateTime<Utc>) -> Self {
+                    Self {
+                        end_time: Some(end_time),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn max_results(self, max_results: $max_results) -> Self {
+                    Self {
+                        max_results: clamp_max_results(
+                            max_results.into(),
+                            Self::MAX_RESULTS_RANGE
+                        ),
+                        ..self
+                    }
+                }
+
+                /// Sets the maximum number of Tweets to return per page, returning an error if
+                /// it is outside of [`MAX_RESULTS_RANGE`](Self::MAX_RESULTS_RANGE) rather than
+                /// clamping it.
+                #[inline]
+                pub fn try_max_results(self, max_results: u16) -> Result<Self, MaxResultsError> {
+                    let max_results = check_max_results(max_results, Self::MAX_RESULTS_RANGE)?;
+                    Ok(Self {
+                        max_results: Some(max_results),
+                        ..self
+                    })
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn next_token(self, next_token: PaginationToken) -> Self {
+                    Self {
+                        next_token: Some(next_token),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn since_id(self, since_id: TweetId) -> Self {
+                    Self {
+                        since_id: Some(since_id),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn until_id(self, until_id: TweetId) -> Self {
+                    Self {
+                        until_id: Some(until_id),
+                        ..self
+                    }
+                }
+
+                /// Sets the order of the results. Twitter uses [`SortOrder::Recency`] if this is
+                /// not set. [`SortOrder::Relevancy`] cannot be combined with `since_id` or
+                /// `until_id`.
+                #[inline]
+                #[must_use]
+                pub fn sort_order(self, sort_order: SortOrder) -> Self {
+                    Self {
+                        sort_order: Some(sort_order),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn expansions<I>(self, expansions: I) -> Self
+                where
+                    I: IntoIterator<Item = TweetPayloadExpansion>,
+                {
+                    Self {
+                        expansions: scribe_comma_separated(expansions),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn tweet_fields<I>(self, tweet_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = TweetField>,
+                {
+                    Self {
+                        tweet_fields: scribe_comma_separated(tweet_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn user_fields<I>(self, user_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = UserField>,
+                {
+                    Self {
+                        user_fields: scribe_comma_separated(user_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn media_fields<I>(self, media_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = MediaField>,
+                {
+                    Self {
+                        media_fields: scribe_comma_separated(media_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn poll_fields<I>(self, poll_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = PollField>,
+                {
+                    Self {
+                        poll_fields: scribe_comma_separated(poll_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn place_fields<I>(self, place_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = PlaceField>,
+                {
+                    Self {
+                        place_fields: scribe_comma_separated(place_fields),
+                        ..self
+                    }
+                }
+
+                /// Returns the field and expansion lists that will be sent with this request.
+                pub fn describe_request(&self) -> RequestFields {
+                    RequestFields {
+                        expansions: &self.expansions,
+                        tweet_fields: &self.tweet_fields,
+                        user_fields: &self.user_fields,
+                        media_fields: &self.media_fields,
+                        poll_fields: &self.poll_fields,
+                        place_fields: &self.place_fields,
+                    }
+                }
+
+                fn search_params(&self) -> SearchParams<'_> {
+                    SearchParams {
+                        query: &self.query,
+                        start_time: self.start_time,
+                        end_time: self.end_time,
+                        max_results: self.max_results.map(NonZeroU16::get),
+                        next_token: self.next_token.as_ref(),
+                        since_id: self.since_id,
+                        until_id: self.until_id,
+                        sort_order: self.sort_order,
+                        fields: self.describe_request(),
+                        max_age: None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+search_request! {
+    /// A request to search Tweets from the last seven days.
+    ///
+    /// The query may be a string or a [`Query`](crate::query::Query). Like
+    /// [`UserTimeline`], the builder's state can be serialised to checkpoint a paginated
+    /// search.
+    SearchRecent {
+        max_results: u8 = 10..=100,
+    }
+
+    /// A request to search the full archive of public Tweets, which is only available to apps
+    /// with Academic Research access. Other apps will receive an [`ErrorKind::ErrorResponse`]
+    /// with a 403 status.
+    ///
+    /// Unlike [`SearchRecent`], `start_time` may be more than seven days in the past.
+    SearchAll {
+        max_results: u16 = 10..=500,
+    }
+}
+
+impl SearchRecent {
+    /// The OAuth 2.0 scopes required to search recent Tweets.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead];
+
+    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<SearchResponse, Error>
+    where
+        A: AppAuth,
+    {
+        // Recent search only covers the last seven days.
+        let params = SearchParams {
+            max_age: Some(Duration::days(7)),
+            ..self.search_params()
         };
 
-        if let Some(excludes) = excludes {
+        execute_search(
+            client,
+            "https://api.twitter.com/2/tweets/search/recent",
+            Self::SCOPES,
+            params
+        ).await
+    }
+}
+
+#[derive(Debug)]
+pub struct SearchResponse {
+    pub tweets: Box<[Tweet]>,
+    pub includes: Includes,
+    /// The number of Tweets in this page of results.
+    pub result_count: Option<u64>,
+    /// The id of the most recent Tweet in this page of results.
+    pub newest_id: Option<TweetId>,
+    /// The id of the oldest Tweet in this page of results.
+    pub oldest_id: Option<TweetId>,
+    pub next_token: Option<PaginationToken>,
+    pub limit_info: LimitInfo,
+}
+
+impl SearchAll {
+    /// The OAuth 2.0 scopes required to search all Tweets.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead];
+
+    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<SearchAllResponse, Error>
+    where
+        A: AppAuth,
+    {
+        execute_search(
+            client,
+            "https://api.twitter.com/2/tweets/search/all",
+            Self::SCOPES,
+            self.search_params()
+        ).await
+    }
+}
+
+/// Full-archive search responses have the same shape as recent search responses.
+pub type SearchAllResponse = SearchResponse;
+
+/// The parameters shared by the recent and full-archive search endpoints.
+struct SearchParams<'a> {
+    query: &'a str,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    max_results: Option<u16>,
+    next_token: Option<&'a PaginationToken>,
+    since_id: Option<TweetId>,
+    until_id: Option<TweetId>,
+    sort_order: Option<SortOrder>,
+    fields: RequestFields<'a>,
+    /// How far in the past `start_time` may be, or `None` if it is unlimited.
+    max_age: Option<Duration>,
+}
+
+impl<'a> SearchParams<'a> {
+    /// How much further in the past than `max_age` `start_time` may be before it is rejected
+    /// locally, in case the local clock is behind Twitter's. Twitter still rejects a
+    /// `start_time` which is actually too old.
+    const CLOCK_SKEW_ALLOWANCE_MINUTES: i64 = 5;
+
+    /// Checks for combinations of parameters which Twitter would reject, given the current time.
+    fn validate(&self, now: DateTime<Utc>) -> Result<(), Error> {
+        if let (Some(start_time), Some(max_age)) = (self.start_time, self.max_age) {
+            let allowance = Duration::minutes(Self::CLOCK_SKEW_ALLOWANCE_MINUTES);
+            if start_time < now - max_age - allowance {
+                return Err(invalid_params_error(
+                    "start_time must be within the last seven days for recent search; use \
+                     SearchAll to search older Tweets"
+                ));
+            }
+        }
+
+        if let (Some(start_time), Some(end_time)) = (self.start_time, self.end_time) {
+            if start_time >= end_time {
+                return Err(invalid_params_error("start_time must be before end_time"));
+            }
+        }
+
+        if let (Some(since_id), Some(until_id)) = (self.since_id, self.until_id) {
+            if since_id >= until_id {
+                return Err(invalid_params_error("since_id must be less than until_id"));
+            }
+        }
+
+        let has_id_bounds = self.since_id.is_some() || self.until_id.is_some();
+
+        if self.sort_order == Some(SortOrder::Relevancy) && has_id_bounds {
+            return Err(invalid_params_error(
+                "since_id and until_id cannot be used with the relevancy sort order"
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid_params_error(message: &'static str) -> Error {
+    ErrorRepr {
+        kind: ErrorKind::Custom(Cow::Borrowed(message)),
+        limit_info: None,
+    }.boxed()
+}
+
+async fn execute_search<A>(
+    client: &AsyncClient<A>,
+    url: &str,
+    scopes: &'static [Scope],
+    search: SearchParams<'_>,
+) -> Result<SearchResponse, Error>
+where
+    A: AppAuth,
+{
+    search.validate(Utc::now())?;
+
+    let mut params = Vec::new();
+
+    params.push((
+        Cow::Borrowed("query"),
+        Cow::Borrowed(search.query)
+    ));
+
+    if let Some(start_time) = search.start_time {
+        params.push((
+            Cow::Borrowed("start_time"),
+            Cow::Owned(start_time.to_rfc3339())
+        ));
+    }
+
+    if let Some(end_time) = search.end_time {
+        params.push((
+            Cow::Borrowed("end_time"),
+            Cow::Owned(end_time.to_rfc3339())
+        ));
+    }
+
+    if let Some(max_results) = search.max_results {
+        params.push((
+            Cow::Borrowed("max_results"),
+            Cow::Owned(format!("{}", max_results))
+        ));
+    }
+
+    if let Some(next_token) = search.next_token {
+        params.push((
+            Cow::Borrowed("next_token"),
+            Cow::Borrowed(next_token.as_str())
+        ));
+    }
+
+    if let Some(since_id) = search.since_id {
+        params.push((
+            Cow::Borrowed("since_id"),
+            Cow::Owned(format!("{}", since_id))
+        ));
+    }
+
+    if let Some(until_id) = search.until_id {
+        params.push((
+            Cow::Borrowed("until_id"),
+            Cow::Owned(format!("{}", until_id))
+        ));
+    }
+
+    if let Some(sort_order) = search.sort_order {
+        params.push((
+            Cow::Borrowed("sort_order"),
+            Cow::Borrowed(sort_order.scribe())
+        ));
+    }
+
+    push_field_params(&mut params, search.fields);
+
+    let (mut response, limit_info)
+        = client.apiv2_request::<_, Box<[Tweet]>>(Request::new_with_data(
+            Method::Get,
+            url,
+            FormData::new(&params)
+        ).with_scopes(scopes)).await?;
+
+    // Twitter omits `data` entirely when a search has no results.
+    let tweets = response.data.unwrap_or_default();
+
+    Ok(SearchResponse {
+        tweets,
+        includes: response.includes,
+        result_count: meta_u64(&response.meta, "result_count"),
+        newest_id: meta_tweet_id(&response.meta, "newest_id"),
+        oldest_id: meta_tweet_id(&response.meta, "oldest_id"),
+        next_token: meta_pagination_token(&mut response.meta, "next_token"),
+        limit_info,
+    })
+}
+
+/// A request for the number of Tweets from the last seven days which match a search query,
+/// grouped into time buckets.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CountsRecent {
+    query: String,
+    granularity: Option<Granularity>,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    since_id: Option<TweetId>,
+    until_id: Option<TweetId>,
+}
+
+impl CountsRecent {
+    /// The OAuth 2.0 scopes required to count recent Tweets.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead];
+
+    #[inline]
+    #[must_use]
+    pub fn new<Q>(query: Q) -> Self
+    where
+        Q: fmt::Display,
+    {
+        Self {
+            query: query.to_string(),
+            granularity: None,
+            start_time: None,
+            end_time: None,
+            since_id: None,
+            until_id: None,
+        }
+    }
+
+    /// Sets the size of the time buckets. Twitter uses [`Granularity::Hour`] if this is not set.
+    #[inline]
+    #[must_use]
+    pub fn granularity(self, granularity: Granularity) -> Self {
+        Self {
+            granularity: Some(granularity),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn start_time(self, start_time: DateTime<Utc>) -> Self {
+        Self {
+            start_time: Some(start_time),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn end_time(self, end_time: DateTime<Utc>) -> Self {
+        Self {
+            end_time: Some(end_time),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn since_id(self, since_id: TweetId) -> Self {
+        Self {
+            since_id: Some(since_id),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn until_id(self, until_id: TweetId) -> Self {
+        Self {
+            until_id: Some(until_id),
+            ..self
+        }
+    }
+
+    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<CountsResponse, Error>
+    where
+        A: AppAuth,
+    {
+        let mut params = Vec::new();
+
+        params.push((
+            Cow::Borrowed("query"),
+            Cow::Borrowed(self.query.as_str())
+        ));
+
+        if let Some(granularity) = self.granularity {
+            params.push((
+                Cow::Borrowed("granularity"),
+                Cow::Borrowed(granularity.scribe())
+            ));
+        }
+
+        if let Some(start_time) = self.start_time {
+            params.push((
+                Cow::Borrowed("start_time"),
+                Cow::Owned(start_time.to_rfc3339())
+            ));
+        }
+
+        if let Some(end_time) = self.end_time {
+            params.push((
+                Cow::Borrowed("end_time"),
+                Cow::Owned(end_time.to_rfc3339())
+            ));
+        }
+
+        if let Some(since_id) = self.since_id {
+            params.push((
+                Cow::Borrowed("since_id"),
+                Cow::Owned(format!("{}", since_id))
+            ));
+        }
+
+        if let Some(until_id) = self.until_id {
+            params.push((
+                Cow::Borrowed("until_id"),
+                Cow::Owned(format!("{}", until_id))
+            ));
+        }
+
+        let (response, limit_info)
+            = client.apiv2_request::<_, Box<[TweetCountBucket]>>(Request::new_with_data(
+                Method::Get,
+                "https://api.twitter.com/2/tweets/counts/recent",
+                FormData::new(&params)
+            ).with_scopes(Self::SCOPES)).await?;
+
+        let buckets = response
+            .data
+            .ok_or_else(|| ErrorRepr {
+                kind: ErrorKind::NoData,
+                limit_info: Some(limit_info.clone()),
+            }.boxed())?;
+
+        Ok(CountsResponse {
+            buckets,
+            total_tweet_count: meta_u64(&response.meta, "total_tweet_count"),
+            limit_info,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct CountsResponse {
+    pub buckets: Box<[TweetCountBucket]>,
+    /// The sum of the Tweet counts of all of the buckets.
+    pub total_tweet_count: Option<u64>,
+    pub limit_info: LimitInfo,
+}
+
+/// The number of Tweets matching a query within a time bucket.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TweetCountBucket {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub tweet_count: u64,
+}
+
+// The endpoints which return a paginated list of Tweets for a Tweet or a List take the same
+// parameters, differing only in their URL, scopes, page size and whether they can exclude Retweets
+// and replies, so their request and response types are generated by this macro.
+macro_rules! tweet_list_request {
+    ($(
+        $(#[$attr:meta])*
+        $request:ident => $response:ident {
+            id: $id:ty,
+            url: $url:literal,
+            auth: $auth:ident,
+            $(#[$scopes_attr:meta])*
+            scopes: $scopes:expr,
+            max_results: $max_results:ty = $range:expr,
+            $(exclude: $exclude:ident,)?
+        }
+    )*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Serialize, Deserialize, Clone, Debug)]
+            pub struct $request {
+                id: $id,
+                $(
+                    #[serde(flatten)]
+                    exclude: $exclude,
+                )?
+                max_results: Option<NonZeroU16>,
+                pagination_token: Option<PaginationToken>,
+                expansions: String,
+                tweet_fields: String,
+                user_fields: String,
+                media_fields: String,
+                poll_fields: String,
+                place_fields: String,
+            }
+
+            impl $request {
+                $(#[$scopes_attr])*
+                pub const SCOPES: &'static [Scope] = $scopes;
+
+                /// The range of values accepted for `max_results`.
+                pub const MAX_RESULTS_RANGE: RangeInclusive<u16> = $range;
+
+                #[inline]
+                #[must_use]
+                pub fn new(id: $id) -> Self {
+                    Self {
+                        id,
+                        $(exclude: $exclude::default(),)?
+                        max_results: None,
+                        pagination_token: None,
+                        expansions: String::new(),
+                        tweet_fields: String::new(),
+                        user_fields: String::new(),
+                        media_fields: String::new(),
+                        poll_fields: String::new(),
+                        place_fields: String::new(),
+                    }
+                }
+
+                $(
+                    #[inline]
+                    #[must_use]
+                    pub fn exclude_retweets(self) -> Self {
+                        Self {
+                            exclude: $exclude { retweets: true, ..self.exclude },
+                            ..self
+                        }
+                    }
+
+                    #[inline]
+                    #[must_use]
+                    pub fn exclude_replies(self) -> Self {
+                        Self {
+                            exclude: $exclude { replies: true, ..self.exclude },
+                            ..self
+                        }
+                    }
+                )?
+
+                #[inline]
+                #[must_use]
+                pub fn max_results(self, max_results: $max_results) -> Self {
+                    Self {
+                        max_results: clamp_max_results(
+                            max_results.into(),
+                            Self::MAX_RESULTS_RANGE
+                        ),
+                        ..self
+                    }
+                }
+
+                /// Sets the maximum number of Tweets to return per page, returning an error if
+                /// it is outside of [`MAX_RESULTS_RANGE`](Self::MAX_RESULTS_RANGE) rather than
+                /// clamping it.
+                #[inline]
+                pub fn try_max_results(self, max_results: u16) -> Result<Self, MaxResultsError> {
+                    let max_results = check_max_results(max_results, Self::MAX_RESULTS_RANGE)?;
+                    Ok(Self {
+                        max_results: Some(max_results),
+                        ..self
+                    })
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn pagination_token(self, pagination_token: PaginationToken) -> Self {
+                    Self {
+                        pagination_token: Some(pagination_token),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn expansions<I>(self, expansions: I) -> Self
+                where
+                    I: IntoIterator<Item = TweetPayloadExpansion>,
+                {
+                    Self {
+                        expansions: scribe_comma_separated(expansions),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn tweet_fields<I>(self, tweet_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = TweetField>,
+                {
+                    Self {
+                        tweet_fields: scribe_comma_separated(tweet_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn user_fields<I>(self, user_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = UserField>,
+                {
+                    Self {
+                        user_fields: scribe_comma_separated(user_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn media_fields<I>(self, media_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = MediaField>,
+                {
+                    Self {
+                        media_fields: scribe_comma_separated(media_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn poll_fields<I>(self, poll_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = PollField>,
+                {
+                    Self {
+                        poll_fields: scribe_comma_separated(poll_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn place_fields<I>(self, place_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = PlaceField>,
+                {
+                    Self {
+                        place_fields: scribe_comma_separated(place_fields),
+                        ..self
+                    }
+                }
+
+                /// Returns the field and expansion lists that will be sent with this request.
+                pub fn describe_request(&self) -> RequestFields {
+                    RequestFields {
+                        expansions: &self.expansions,
+                        tweet_fields: &self.tweet_fields,
+                        user_fields: &self.user_fields,
+                        media_fields: &self.media_fields,
+                        poll_fields: &self.poll_fields,
+                        place_fields: &self.place_fields,
+                    }
+                }
+
+                pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<$response, Error>
+                where
+                    A: $auth,
+                {
+                    let mut params = Vec::new();
+
+                    $(
+                        if let Some(excludes) = $exclude::param(&self.exclude) {
+                            params.push((
+                                Cow::Borrowed("exclude"),
+                                Cow::Borrowed(excludes)
+                            ));
+                        }
+                    )?
+
+                    if let Some(max_results) = self.max_results {
+                        params.push((
+                            Cow::Borrowed("max_results"),
+                            Cow::Owned(format!("{}", max_results))
+                        ));
+                    }
+
+                    if let Some(pagination_token) = self.pagination_token.as_ref() {
+                        params.push((
+                            Cow::Borrowed("pagination_token"),
+                            Cow::Borrowed(pagination_token.as_str())
+                        ));
+                    }
+
+                    push_field_params(&mut params, self.describe_request());
+
+                    let (mut response, limit_info)
+                        = client.apiv2_request::<_, Box<[Tweet]>>(Request::new_with_data(
+                            Method::Get,
+                            &format!($url, self.id),
+                            FormData::new(&params)
+                        ).with_scopes(Self::SCOPES)).await?;
+
+                    // Twitter omits `data` entirely when there are no Tweets to return.
+                    let tweets = response.data.unwrap_or_default();
+
+                    Ok($response {
+                        tweets,
+                        includes: response.includes,
+                        next_token: meta_pagination_token(&mut response.meta, "next_token"),
+                        limit_info,
+                    })
+                }
+            }
+
+            #[derive(Debug)]
+            pub struct $response {
+                pub tweets: Box<[Tweet]>,
+                pub includes: Includes,
+                pub next_token: Option<PaginationToken>,
+                pub limit_info: LimitInfo,
+            }
+        )*
+    };
+}
+
+tweet_list_request! {
+    /// A request for the Tweets which quote a Tweet.
+    QuoteTweets => QuoteTweetsResponse {
+        id: TweetId,
+        url: "https://api.twitter.com/2/tweets/{}/quote_tweets",
+        auth: AppAuth,
+        /// The OAuth 2.0 scopes required to look up the quotes of a Tweet.
+        scopes: &[Scope::TweetRead, Scope::UsersRead],
+        max_results: u8 = 10..=100,
+        exclude: TimelineExclude,
+    }
+
+    /// A request for the Tweets posted by the members of a List, in reverse-chronological order.
+    ListTweets => ListTweetsResponse {
+        id: ListId,
+        url: "https://api.twitter.com/2/lists/{}/tweets",
+        auth: AppAuth,
+        /// The OAuth 2.0 scopes required to look up a List's Tweets.
+        scopes: &[Scope::TweetRead, Scope::UsersRead, Scope::ListRead],
+        max_results: u8 = 1..=100,
+    }
+}
+
+// The endpoints which return a paginated list of users all take the same parameters, differing only
+// in their URL, scopes and page size, so their request types are generated by this macro.
+macro_rules! user_list_request {
+    ($(
+        $(#[$attr:meta])*
+        $request:ident {
+            id: $id:ty,
+            url: $url:literal,
+            auth: $auth:ident,
+            $(#[$scopes_attr:meta])*
+            scopes: $scopes:expr,
+            max_results: $max_results:ty = $range:expr,
+        }
+    )*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Serialize, Deserialize, Clone, Debug)]
+            pub struct $request {
+                id: $id,
+                max_results: Option<NonZeroU16>,
+                pagination_token: Option<PaginationToken>,
+                expansions: String,
+                tweet_fields: String,
+                user_fields: String,
+            }
+
+            impl $request {
+                $(#[$scopes_attr])*
+                pub const SCOPES: &'static [Scope] = $scopes;
+
+                /// The range of values accepted for `max_results`.
+                pub const MAX_RESULTS_RANGE: RangeInclusive<u16> = $range;
+
+                #[inline]
+                #[must_use]
+                pub fn new(id: $id) -> Self {
+                    Self {
+                        id,
+                        max_results: None,
+                        pagination_token: None,
+                        expansions: String::new(),
+                        tweet_fields: String::new(),
+                        user_fields: String::new(),
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn max_results(self, max_results: $max_results) -> Self {
+                    Self {
+                        max_results: clamp_max_results(
+                            max_results.into(),
+                            Self::MAX_RESULTS_RANGE
+                        ),
+                        ..self
+                    }
+                }
+
+                /// Sets the maximum number of users to return per page, returning an error if it
+                /// is outside of [`MAX_RESULTS_RANGE`](Self::MAX_RESULTS_RANGE) rather than
+                /// clamping it.
+                #[inline]
+                pub fn try_max_results(self, max_results: u16) -> Result<Self, MaxResultsError> {
+                    let max_results = check_max_results(max_results, Self::MAX_RESULTS_RANGE)?;
+                    Ok(Self {
+                        max_results: Some(max_results),
+                        ..self
+                    })
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn pagination_token(self, pagination_token: PaginationToken) -> Self {
+                    Self {
+                        pagination_token: Some(pagination_token),
+                        ..self
+                    }
+                }
+
+                /// Expansions to resolve for each user. [`UserPayloadExpansion::PinnedTweetId`]
+                /// returns the users' pinned Tweets in [`Includes::tweets`].
+                #[inline]
+                #[must_use]
+                pub fn expansions<I>(self, expansions: I) -> Self
+                where
+                    I: IntoIterator<Item = UserPayloadExpansion>,
+                {
+                    Self {
+                        expansions: scribe_comma_separated(expansions),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn tweet_fields<I>(self, tweet_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = TweetField>,
+                {
+                    Self {
+                        tweet_fields: scribe_comma_separated(tweet_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn user_fields<I>(self, user_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = UserField>,
+                {
+                    Self {
+                        user_fields: scribe_comma_separated(user_fields),
+                        ..self
+                    }
+                }
+
+                /// Returns the field and expansion lists that will be sent with this request.
+                pub fn describe_request(&self) -> RequestFields {
+                    RequestFields {
+                        expansions: &self.expansions,
+                        tweet_fields: &self.tweet_fields,
+                        user_fields: &self.user_fields,
+                        media_fields: "",
+                        poll_fields: "",
+                        place_fields: "",
+                    }
+                }
+
+                pub async fn execute<A>(
+                    &self,
+                    client: &AsyncClient<A>
+                ) -> Result<UsersResponse, Error>
+                where
+                    A: $auth,
+                {
+                    let mut params = Vec::new();
+
+                    if let Some(max_results) = self.max_results {
+                        params.push((
+                            Cow::Borrowed("max_results"),
+                            Cow::Owned(format!("{}", max_results))
+                        ));
+                    }
+
+                    if let Some(pagination_token) = self.pagination_token.as_ref() {
+                        params.push((
+                            Cow::Borrowed("pagination_token"),
+                            Cow::Borrowed(pagination_token.as_str())
+                        ));
+                    }
+
+                    push_field_params(&mut params, self.describe_request());
+
+                    execute_users_page(
+                        client,
+                        &format!($url, self.id),
+                        Self::SCOPES,
+                        &params
+                    ).await
+                }
+            }
+        )*
+    };
+}
+
+user_list_request! {
+    /// A request for the users who have Retweeted a Tweet.
+    TweetRetweetedBy {
+        id: TweetId,
+        url: "https://api.twitter.com/2/tweets/{}/retweeted_by",
+        auth: AppAuth,
+        /// The OAuth 2.0 scopes required to look up the users who Retweeted a Tweet.
+        scopes: &[Scope::TweetRead, Scope::UsersRead],
+        max_results: u8 = 1..=100,
+    }
+
+    /// A request for the users who have liked a Tweet.
+    TweetLikingUsers {
+        id: TweetId,
+        url: "https://api.twitter.com/2/tweets/{}/liking_users",
+        auth: AppAuth,
+        /// The OAuth 2.0 scopes required to look up the users who liked a Tweet.
+        scopes: &[Scope::TweetRead, Scope::UsersRead, Scope::LikeRead],
+        max_results: u8 = 1..=100,
+    }
+
+    /// A request for the users who follow a user.
+    UserFollowers {
+        id: UserId,
+        url: "https://api.twitter.com/2/users/{}/followers",
+        auth: AppAuth,
+        /// The OAuth 2.0 scopes required to look up a user's followers.
+        scopes: &[Scope::TweetRead, Scope::UsersRead, Scope::FollowsRead],
+        max_results: u16 = 1..=1000,
+    }
+
+    /// A request for the members of a List.
+    ListMembers {
+        id: ListId,
+        url: "https://api.twitter.com/2/lists/{}/members",
+        auth: AppAuth,
+        /// The OAuth 2.0 scopes required to look up the members of a List.
+        scopes: &[Scope::TweetRead, Scope::UsersRead, Scope::ListRead],
+        max_results: u8 = 1..=100,
+    }
+
+    /// A request for the users whom a user follows.
+    UserFollowing {
+        id: UserId,
+        url: "https://api.twitter.com/2/users/{}/following",
+        auth: AppAuth,
+        /// The OAuth 2.0 scopes required to look up the users a user follows.
+        scopes: &[Scope::TweetRead, Scope::UsersRead, Scope::FollowsRead],
+        max_results: u16 = 1..=1000,
+    }
+
+    /// A request for the users blocked by the authenticating user. The user id must be the
+    /// authenticating user's.
+    BlockedUsers {
+        id: UserId,
+        url: "https://api.twitter.com/2/users/{}/blocking",
+        auth: UserAuth,
+        /// The OAuth 2.0 scopes required to look up blocked users.
+        scopes: &[Scope::TweetRead, Scope::UsersRead, Scope::BlockRead],
+        max_results: u16 = 1..=1000,
+    }
+}
+
+/// A page of users returned by an endpoint which lists users, such as
+/// [`TweetRetweetedBy`], [`TweetLikingUsers`] or [`UserFollowers`].
+#[derive(Debug)]
+pub struct UsersResponse {
+    pub users: Box<[User]>,
+    pub includes: Includes,
+    pub previous_token: Option<PaginationToken>,
+    pub next_token: Option<PaginationToken>,
+    pub limit_info: LimitInfo,
+}
+
+async fn execute_users_page<A>(
+    client: &AsyncClient<A>,
+    url: &str,
+    scopes: &'static [Scope],
+    params: &[(Cow<'_, str>, Cow<'_, str>)],
+) -> Result<UsersResponse, Error>
+where
+    A: AppAuth,
+{
+    let (mut response, limit_info)
+        = client.apiv2_request::<_, Box<[User]>>(Request::new_with_data(
+            Method::Get,
+            url,
+            FormData::new(params)
+        ).with_scopes(scopes)).await?;
+
+    // Twitter omits `data` entirely when there are no users to return.
+    let users = response.data.unwrap_or_default();
+
+    Ok(UsersResponse {
+        users,
+        includes: response.includes,
+        previous_token: meta_pagination_token(&mut response.meta, "previous_token"),
+        next_token: meta_pagination_token(&mut response.meta, "next_token"),
+        limit_info,
+    })
+}
+
+/// A request to look up a single user by their username.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GetUserByUsername {
+    username: String,
+    expansions: String,
+    tweet_fields: String,
+    user_fields: String,
+}
+
+impl GetUserByUsername {
+    /// The OAuth 2.0 scopes required to look up a user.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead];
+
+    /// Creates a request for the user with the given username, which may optionally start with
+    /// `@`. The username is validated when the request is executed.
+    #[inline]
+    #[must_use]
+    pub fn new(username: &str) -> Self {
+        Self {
+            username: username.to_owned(),
+            expansions: String::new(),
+            tweet_fields: String::new(),
+            user_fields: String::new(),
+        }
+    }
+
+    /// Expansions to resolve for the user. [`UserPayloadExpansion::PinnedTweetId`] returns the
+    /// user's pinned Tweet in [`Includes::tweets`].
+    #[inline]
+    #[must_use]
+    pub fn expansions<I>(self, expansions: I) -> Self
+    where
+        I: IntoIterator<Item = UserPayloadExpansion>,
+    {
+        Self {
+            expansions: scribe_comma_separated(expansions),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn tweet_fields<I>(self, tweet_fields: I) -> Self
+    where
+        I: IntoIterator<Item = TweetField>,
+    {
+        Self {
+            tweet_fields: scribe_comma_separated(tweet_fields),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn user_fields<I>(self, user_fields: I) -> Self
+    where
+        I: IntoIterator<Item = UserField>,
+    {
+        Self {
+            user_fields: scribe_comma_separated(user_fields),
+            ..self
+        }
+    }
+
+    /// Returns the field and expansion lists that will be sent with this request.
+    pub fn describe_request(&self) -> RequestFields {
+        RequestFields {
+            expansions: &self.expansions,
+            tweet_fields: &self.tweet_fields,
+            user_fields: &self.user_fields,
+            media_fields: "",
+            poll_fields: "",
+            place_fields: "",
+        }
+    }
+
+    /// Looks up the user. If there is no user with the username, Twitter responds with a
+    /// "Not Found Error", which is returned as an [`ErrorKind::ErrorResponse`].
+    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<UserResponse, Error>
+    where
+        A: AppAuth,
+    {
+        let username = query::check_username(&self.username)
+            .map_err(|err| ErrorRepr {
+                kind: ErrorKind::Custom(err.to_string().into()),
+                limit_info: None,
+            }.boxed())?;
+
+        let mut params = Vec::new();
+
+        push_field_params(&mut params, self.describe_request());
+
+        let (response, limit_info)
+            = client.apiv2_request::<_, User>(Request::new_with_data(
+                Method::Get,
+                &format!("https://api.twitter.com/2/users/by/username/{}", username),
+                FormData::new(&params)
+            ).with_scopes(Self::SCOPES)).await?;
+
+        let user = response
+            .data
+            .ok_or_else(|| ErrorRepr {
+                kind: ErrorKind::NoData,
+                limit_info: Some(limit_info.clone()),
+            }.boxed())?;
+
+        Ok(UserResponse {
+            user,
+            includes: response.includes,
+            limit_info,
+        })
+    }
+
+    /// Like [`execute`](Self::execute), but returns `None` rather than an error if the user does
+    /// not exist or is suspended, both of which Twitter reports as a "resource not found"
+    /// problem.
+    pub async fn execute_optional<A>(
+        &self,
+        client: &AsyncClient<A>
+    ) -> Result<Option<UserResponse>, Error>
+    where
+        A: AppAuth,
+    {
+        match self.execute(client).await {
+            Ok(response) => Ok(Some(response)),
+            Err(err) if err.is_not_found() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// A request for the authenticating user, which can be used to find the id of the user after
+/// authorising with OAuth.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GetMe {
+    expansions: String,
+    tweet_fields: String,
+    user_fields: String,
+}
+
+impl GetMe {
+    /// The OAuth 2.0 scopes required to look up the authenticating user.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead];
+
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            expansions: String::new(),
+            tweet_fields: String::new(),
+            user_fields: String::new(),
+        }
+    }
+
+    /// Expansions to resolve for the user. [`UserPayloadExpansion::PinnedTweetId`] returns the
+    /// user's pinned Tweet in [`Includes::tweets`].
+    #[inline]
+    #[must_use]
+    pub fn expansions<I>(self, expansions: I) -> Self
+    where
+        I: IntoIterator<Item = UserPayloadExpansion>,
+    {
+        Self {
+            expansions: scribe_comma_separated(expansions),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn tweet_fields<I>(self, tweet_fields: I) -> Self
+    where
+        I: IntoIterator<Item = TweetField>,
+    {
+        Self {
+            tweet_fields: scribe_comma_separated(tweet_fields),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn user_fields<I>(self, user_fields: I) -> Self
+    where
+        I: IntoIterator<Item = UserField>,
+    {
+        Self {
+            user_fields: scribe_comma_separated(user_fields),
+            ..self
+        }
+    }
+
+    /// Returns the field and expansion lists that will be sent with this request.
+    pub fn describe_request(&self) -> RequestFields {
+        RequestFields {
+            expansions: &self.expansions,
+            tweet_fields: &self.tweet_fields,
+            user_fields: &self.user_fields,
+            media_fields: "",
+            poll_fields: "",
+            place_fields: "",
+        }
+    }
+
+    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<UserResponse, Error>
+    where
+        A: UserAuth,
+    {
+        let mut params = Vec::new();
+
+        push_field_params(&mut params, self.describe_request());
+
+        let (response, limit_info)
+            = client.apiv2_request::<_, User>(Request::new_with_data(
+                Method::Get,
+                "https://api.twitter.com/2/users/me",
+                FormData::new(&params)
+            ).with_scopes(Self::SCOPES)).await?;
+
+        let user = response
+            .data
+            .ok_or_else(|| ErrorRepr {
+                kind: ErrorKind::NoData,
+                limit_info: Some(limit_info.clone()),
+            }.boxed())?;
+
+        Ok(UserResponse {
+            user,
+            includes: response.includes,
+            limit_info,
+        })
+    }
+}
+
+impl Default for GetMe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+pub struct UserResponse {
+    pub user: User,
+    pub includes: Includes,
+    pub limit_info: LimitInfo,
+}
+
+/// A request to look up a List by its id.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GetList {
+    id: ListId,
+    list_fields: String,
+}
+
+impl GetList {
+    /// The OAuth 2.0 scopes required to look up a List.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::ListRead];
+
+    #[inline]
+    #[must_use]
+    pub fn new(id: ListId) -> Self {
+        Self {
+            id,
+            list_fields: String::new(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn list_fields<I>(self, list_fields: I) -> Self
+    where
+        I: IntoIterator<Item = ListField>,
+    {
+        Self {
+            list_fields: scribe_comma_separated(list_fields),
+            ..self
+        }
+    }
+
+    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<ListResponse, Error>
+    where
+        A: AppAuth,
+    {
+        let mut params = Vec::new();
+
+        push_list_fields_param(&mut params, &self.list_fields);
+
+        let (response, limit_info)
+            = client.apiv2_request::<_, List>(Request::new_with_data(
+                Method::Get,
+                &format!("https://api.twitter.com/2/lists/{}", self.id),
+                FormData::new(&params)
+            ).with_scopes(Self::SCOPES)).await?;
+
+        let list = response
+            .data
+            .ok_or_else(|| ErrorRepr {
+                kind: ErrorKind::NoData,
+                limit_info: Some(limit_info.clone()),
+            }.boxed())?;
+
+        Ok(ListResponse {
+            list,
+            includes: response.includes,
+            limit_info,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ListResponse {
+    pub list: List,
+    pub includes: Includes,
+    pub limit_info: LimitInfo,
+}
+
+/// A request for the Lists owned by a user, or the Lists a user follows.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UserLists {
+    endpoint: UserListsEndpoint,
+    id: UserId,
+    max_results: Option<NonZeroU16>,
+    pagination_token: Option<PaginationToken>,
+    list_fields: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+enum UserListsEndpoint {
+    Owned,
+    Followed,
+}
+
+impl UserListsEndpoint {
+    fn path(self) -> &'static str {
+        match self {
+            Self::Owned => "owned_lists",
+            Self::Followed => "followed_lists",
+        }
+    }
+}
+
+impl UserLists {
+    /// The OAuth 2.0 scopes required to look up the Lists owned or followed by a user.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::ListRead];
+
+    /// The range of values accepted for `max_results`.
+    pub const MAX_RESULTS_RANGE: RangeInclusive<u16> = 1..=100;
+
+    /// Returns a request for the Lists owned by the user with the given id.
+    #[inline]
+    #[must_use]
+    pub fn owned(id: UserId) -> Self {
+        Self::new(UserListsEndpoint::Owned, id)
+    }
+
+    /// Returns a request for the Lists followed by the user with the given id.
+    #[inline]
+    #[must_use]
+    pub fn followed(id: UserId) -> Self {
+        Self::new(UserListsEndpoint::Followed, id)
+    }
+
+    fn new(endpoint: UserListsEndpoint, id: UserId) -> Self {
+        Self {
+            endpoint,
+            id,
+            max_results: None,
+            pagination_token: None,
+            list_fields: String::new(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn max_results(self, max_results: u8) -> Self {
+        Self {
+            max_results: clamp_max_results(max_results.into(), Self::MAX_RESULTS_RANGE),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of Lists to return per page, returning an error if it is outside
+    /// of [`MAX_RESULTS_RANGE`](Self::MAX_RESULTS_RANGE) rather than clamping it.
+    #[inline]
+    pub fn try_max_results(self, max_results: u16) -> Result<Self, MaxResultsError> {
+        let max_results = check_max_results(max_results, Self::MAX_RESULTS_RANGE)?;
+        Ok(Self {
+            max_results: Some(max_results),
+            ..self
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn pagination_token(self, pagination_token: PaginationToken) -> Self {
+        Self {
+            pagination_token: Some(pagination_token),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn list_fields<I>(self, list_fields: I) -> Self
+    where
+        I: IntoIterator<Item = ListField>,
+    {
+        Self {
+            list_fields: scribe_comma_separated(list_fields),
+            ..self
+        }
+    }
+
+    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<ListsResponse, Error>
+    where
+        A: AppAuth,
+    {
+        let mut params = Vec::new();
+
+        if let Some(max_results) = self.max_results {
+            params.push((
+                Cow::Borrowed("max_results"),
+                Cow::Owned(format!("{}", max_results))
+            ));
+        }
+
+        if let Some(pagination_token) = self.pagination_token.as_ref() {
+            params.push((
+                Cow::Borrowed("pagination_token"),
+                Cow::Borrowed(pagination_token.as_str())
+            ));
+        }
+
+        push_list_fields_param(&mut params, &self.list_fields);
+
+        execute_lists_page(
+            client,
+            &format!("https://api.twitter.com/2/users/{}/{}", self.id, self.endpoint.path()),
+            Self::SCOPES,
+            &params
+        ).await
+    }
+}
+
+/// A request for the Lists pinned by the authenticating user. The user id must be the
+/// authenticating user's.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PinnedLists {
+    id: UserId,
+    list_fields: String,
+}
+
+impl PinnedLists {
+    /// The OAuth 2.0 scopes required to look up the Lists a user has pinned.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::ListRead];
+
+    #[inline]
+    #[must_use]
+    pub fn new(id: UserId) -> Self {
+        Self {
+            id,
+            list_fields: String::new(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn list_fields<I>(self, list_fields: I) -> Self
+    where
+        I: IntoIterator<Item = ListField>,
+    {
+        Self {
+            list_fields: scribe_comma_separated(list_fields),
+            ..self
+        }
+    }
+
+    /// Looks up the pinned Lists. This endpoint is not paginated, so the response never has a
+    /// `next_token`.
+    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<ListsResponse, Error>
+    where
+        A: UserAuth,
+    {
+        let mut params = Vec::new();
+
+        push_list_fields_param(&mut params, &self.list_fields);
+
+        execute_lists_page(
+            client,
+            &format!("https://api.twitter.com/2/users/{}/pinned_lists", self.id),
+            Self::SCOPES,
+            &params
+        ).await
+    }
+}
+
+/// A page of Lists, returned by [`UserLists`] and [`PinnedLists`].
+#[derive(Debug)]
+pub struct ListsResponse {
+    pub lists: Box<[List]>,
+    pub includes: Includes,
+    pub next_token: Option<PaginationToken>,
+    pub limit_info: LimitInfo,
+}
+
+async fn execute_lists_page<A>(
+    client: &AsyncClient<A>,
+    url: &str,
+    scopes: &'static [Scope],
+    params: &[(Cow<'_, str>, Cow<'_, str>)],
+) -> Result<ListsResponse, Error>
+where
+    A: AppAuth,
+{
+    let (mut response, limit_info)
+        = client.apiv2_request::<_, Box<[List]>>(Request::new_with_data(
+            Method::Get,
+            url,
+            FormData::new(params)
+        ).with_scopes(scopes)).await?;
+
+    // Twitter omits `data` entirely when there are no Lists to return.
+    let lists = response.data.unwrap_or_default();
+
+    Ok(ListsResponse {
+        lists,
+        includes: response.includes,
+        next_token: meta_pagination_token(&mut response.meta, "next_token"),
+        limit_info,
+    })
+}
+
+/// A request to look up Spaces by their ids.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LookupSpaces {
+    ids: String,
+    space_fields: String,
+}
+
+impl LookupSpaces {
+    /// The OAuth 2.0 scopes required to look up Spaces.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::SpaceRead];
+
+    #[inline]
+    #[must_use]
+    pub fn new<'a, I>(ids: I) -> Self
+    where
+        I: IntoIterator<Item = &'a SpaceId>,
+    {
+        let ids = fmt_comma_separated(ids);
+
+        Self {
+            ids,
+            space_fields: String::new(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn space_fields<I>(self, space_fields: I) -> Self
+    where
+        I: IntoIterator<Item = SpaceField>,
+    {
+        Self {
+            space_fields: scribe_comma_separated(space_fields),
+            ..self
+        }
+    }
+
+    /// Looks up the Spaces. Spaces which could not be returned are reported in the response's
+    /// [`errors`](LookupSpacesResponse::errors), but if none of the Spaces could be returned, the
+    /// lookup fails with an [`ErrorResponse`](ErrorKind::ErrorResponse) error instead, for which
+    /// [`Error::is_not_found`] returns `true` if the Spaces do not exist.
+    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<LookupSpacesResponse, Error>
+    where
+        A: AppAuth,
+    {
+        let mut params = Vec::new();
+
+        params.push((
+            Cow::Borrowed("ids"),
+            Cow::Borrowed(self.ids.as_str())
+        ));
+
+        push_space_fields_param(&mut params, &self.space_fields);
+
+        let (response, limit_info)
+            = client.apiv2_request::<_, Box<[Space]>>(Request::new_with_data(
+                Method::Get,
+                "https://api.twitter.com/2/spaces",
+                FormData::new(&params)
+            ).with_scopes(Self::SCOPES)).await?;
+
+        // A response with no `data` but some `errors` has already been turned into an error by
+        // `apiv2_request`, so `data` can only be missing here if there were no errors either.
+        let spaces = response.data.unwrap_or_default();
+
+        Ok(LookupSpacesResponse {
+            spaces,
+            errors: response.errors,
+            limit_info,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct LookupSpacesResponse {
+    pub spaces: Box<[Space]>,
+    /// Errors for any requested Spaces which could not be returned, for example because they do
+    /// not exist.
+    pub errors: Box<[ResponseError]>,
+    pub limit_info: LimitInfo,
+}
+
+/// A request to search for live or scheduled Spaces by their titles.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SearchSpaces {
+    query: String,
+    state: Option<SpaceStateFilter>,
+    max_results: Option<NonZeroU8>,
+    space_fields: String,
+}
+
+impl SearchSpaces {
+    /// The OAuth 2.0 scopes required to search Spaces.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::SpaceRead];
+
+    /// The range of values accepted for `max_results`.
+    pub const MAX_RESULTS_RANGE: RangeInclusive<u16> = 1..=100;
+
+    #[inline]
+    #[must_use]
+    pub fn new(query: &str) -> Self {
+        Self {
+            query: query.to_owned(),
+            state: None,
+            max_results: None,
+            space_fields: String::new(),
+        }
+    }
+
+    /// Only returns Spaces in the given state. Twitter returns both live and scheduled Spaces if
+    /// this is not set.
+    #[inline]
+    #[must_use]
+    pub fn state(self, state: SpaceStateFilter) -> Self {
+        Self {
+            state: Some(state),
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn max_results(self, max_results: u8) -> Self {
+        let max_results = max_results.clamp(1, 100);
+        Self {
+            max_results: Some(NonZeroU8::new(max_results).unwrap()),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of Spaces to return, returning an error if it is outside of
+    /// [`MAX_RESULTS_RANGE`](Self::MAX_RESULTS_RANGE) rather than clamping it.
+    #[inline]
+    pub fn try_max_results(self, max_results: u16) -> Result<Self, MaxResultsError> {
+        let max_results = check_max_results(max_results, Self::MAX_RESULTS_RANGE)?;
+        Ok(Self {
+            // `MAX_RESULTS_RANGE` is within the range of a `u8`, so the cast is lossless.
+            max_results: NonZeroU8::new(max_results.get() as u8),
+            ..self
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn space_fields<I>(self, space_fields: I) -> Self
+    where
+        I: IntoIterator<Item = SpaceField>,
+    {
+        Self {
+            space_fields: scribe_comma_separated(space_fields),
+            ..self
+        }
+    }
+
+    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<SearchSpacesResponse, Error>
+    where
+        A: AppAuth,
+    {
+        let mut params = Vec::new();
+
+        params.push((
+            Cow::Borrowed("query"),
+            Cow::Borrowed(self.query.as_str())
+        ));
+
+        if let Some(state) = self.state {
+            params.push((
+                Cow::Borrowed("state"),
+                Cow::Borrowed(state.scribe())
+            ));
+        }
+
+        if let Some(max_results) = self.max_results {
+            params.push((
+                Cow::Borrowed("max_results"),
+                Cow::Owned(format!("{}", max_results))
+            ));
+        }
+
+        push_space_fields_param(&mut params, &self.space_fields);
+
+        let (response, limit_info)
+            = client.apiv2_request::<_, Box<[Space]>>(Request::new_with_data(
+                Method::Get,
+                "https://api.twitter.com/2/spaces/search",
+                FormData::new(&params)
+            ).with_scopes(Self::SCOPES)).await?;
+
+        // Twitter omits `data` entirely when no Spaces match the query.
+        let spaces = response.data.unwrap_or_default();
+
+        Ok(SearchSpacesResponse {
+            spaces,
+            result_count: meta_u64(&response.meta, "result_count"),
+            limit_info,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct SearchSpacesResponse {
+    pub spaces: Box<[Space]>,
+    pub result_count: Option<u64>,
+    pub limit_info: LimitInfo,
+}
+
+/// A request for the Spaces created by any of a set of users, which can be used to check whether
+/// any of the users are hosting a Space.
+///
+/// Twitter accepts at most [`MAX_IDS_PER_REQUEST`](Self::MAX_IDS_PER_REQUEST) user ids per
+/// request, so larger sets of users are split across several requests.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SpacesByCreators {
+    user_ids: Vec<UserId>,
+    space_fields: String,
+}
+
+impl SpacesByCreators {
+    /// The OAuth 2.0 scopes required to look up Spaces by their creators.
+    pub const SCOPES: &'static [Scope] = &[Scope::TweetRead, Scope::UsersRead, Scope::SpaceRead];
+
+    /// The maximum number of user ids Twitter accepts in a single request.
+    pub const MAX_IDS_PER_REQUEST: usize = 100;
+
+    #[inline]
+    #[must_use]
+    pub fn new<I>(user_ids: I) -> Self
+    where
+        I: IntoIterator<Item = UserId>,
+    {
+        Self {
+            user_ids: user_ids.into_iter().collect(),
+            space_fields: String::new(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn space_fields<I>(self, space_fields: I) -> Self
+    where
+        I: IntoIterator<Item = SpaceField>,
+    {
+        Self {
+            space_fields: scribe_comma_separated(space_fields),
+            ..self
+        }
+    }
+
+    /// Looks up the Spaces, making one request for each
+    /// [`MAX_IDS_PER_REQUEST`](Self::MAX_IDS_PER_REQUEST) user ids. The returned rate limit
+    /// information is from the last request made. If any request fails, its error is returned
+    /// and the Spaces from earlier requests are discarded.
+    pub async fn execute<A>(&self, client: &AsyncClient<A>) -> Result<SpacesResponse, Error>
+    where
+        A: AppAuth,
+    {
+        let mut spaces = Vec::new();
+        let mut limit_info = LimitInfo::empty();
+
+        for user_ids in self.user_ids.chunks(Self::MAX_IDS_PER_REQUEST) {
+            let user_ids = fmt_comma_separated(user_ids);
+
+            let mut params = Vec::new();
+
             params.push((
-                Cow::Borrowed("exclude"),
-                Cow::Borrowed(excludes)
+                Cow::Borrowed("user_ids"),
+                Cow::Borrowed(user_ids.as_str())
             ));
+
+            push_space_fields_param(&mut params, &self.space_fields);
+
+            let (response, chunk_limit_info)
+                = client.apiv2_request::<_, Box<[Space]>>(Request::new_with_data(
+                    Method::Get,
+                    "https://api.twitter.com/2/spaces/by/creator_ids",
+                    FormData::new(&params)
+                ).with_scopes(Self::SCOPES)).await?;
+
+            // Twitter omits `data` entirely when none of the users have created a Space.
+            spaces.extend(Vec::from(response.data.unwrap_or_default()));
+            limit_info = chunk_limit_info;
         }
 
-        if let Some(max_results) = self.max_results {
-            params.push((
-                Cow::Borrowed("max_results"),
-                Cow::Owned(format!("{}", max_results))
-            ));
+        Ok(SpacesResponse {
+            spaces: spaces.into_boxed_slice(),
+            limit_info,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct SpacesResponse {
+    pub spaces: Box<[Space]>,
+    pub limit_info: LimitInfo,
+}
+
+/// A request to connect to the filtered stream, which delivers Tweets matching the app's stream
+/// rules in real time.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FilteredStream {
+    expansions: String,
+    tweet_fields: String,
+    user_fields: String,
+    media_fields: String,
+    poll_fields: String,
+    place_fields: String,
+}
+
+impl FilteredStream {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            expansions: String::new(),
+            tweet_fields: String::new(),
+            user_fields: String::new(),
+            media_fields: String::new(),
+            poll_fields: String::new(),
+            place_fields: String::new(),
         }
+    }
 
-        if let Some(pagination_token) = self.pagination_token.as_ref() {
-            params.push((
-                Cow::Borrowed("pagination_token"),
-                Cow::Borrowed(&pagination_token.0)
-            ))
-        }
+    /// Connects to the stream. Returns an error if the connection could not be opened; once it
+    /// is open, Tweets are received from the returned [`TweetStream`].
+    pub async fn connect<A>(&self, client: &AsyncClient<A>) -> Result<TweetStream, Error>
+    where
+        A: AppAuth,
+    {
+        let mut params = Vec::new();
 
-        if let Some(since_id) = self.since_id {
-            params.push((
-                Cow::Borrowed("since_id"),
-                Cow::Owned(format!("{}", since_id))
-            ));
-        }
+        push_field_params(&mut params, self.describe_request());
 
-        if let Some(until_id) = self.until_id {
-            params.push((
-                Cow::Borrowed("until_id"),
-                Cow::Owned(format!("{}", until_id))
-            ));
-        }
+        let (response, limit_info) = client.stream_request(Request::new_with_data(
+            Method::Get,
+            "https://api.twitter.com/2/tweets/search/stream",
+            FormData::new(&params)
+        )).await?;
 
-        if !self.expansions.is_empty() {
-            params.push((
-                Cow::Borrowed("expansions"),
-                Cow::Borrowed(&self.expansions)
-            ));
-        }
+        Ok(TweetStream::new(response, limit_info))
+    }
+}
 
-        if !self.tweet_fields.is_empty() {
-            params.push((
-                Cow::Borrowed("tweet.fields"),
-                Cow::Borrowed(&self.tweet_fields)
-            ));
+impl Default for FilteredStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A request to connect to the volume stream, which delivers a random sample of roughly 1% of
+/// all public Tweets in real time.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SampleStream {
+    backfill_minutes: Option<NonZeroU8>,
+    expansions: String,
+    tweet_fields: String,
+    user_fields: String,
+    media_fields: String,
+    poll_fields: String,
+    place_fields: String,
+}
+
+impl SampleStream {
+    /// The maximum number of minutes of backfill which can be requested.
+    pub const MAX_BACKFILL_MINUTES: u8 = 5;
+
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            backfill_minutes: None,
+            expansions: String::new(),
+            tweet_fields: String::new(),
+            user_fields: String::new(),
+            media_fields: String::new(),
+            poll_fields: String::new(),
+            place_fields: String::new(),
         }
+    }
 
-        if !self.user_fields.is_empty() {
-            params.push((
-                Cow::Borrowed("user.fields"),
-                Cow::Borrowed(&self.user_fields)
-            ));
+    /// Requests Tweets from up to the given number of minutes before connecting, to recover
+    /// Tweets missed during a disconnection. The value is capped at
+    /// [`MAX_BACKFILL_MINUTES`](Self::MAX_BACKFILL_MINUTES), and 0 disables backfill. Backfill is
+    /// only available with Academic Research access.
+    #[inline]
+    #[must_use]
+    pub fn backfill_minutes(self, backfill_minutes: u8) -> Self {
+        let backfill_minutes = backfill_minutes.min(Self::MAX_BACKFILL_MINUTES);
+        Self {
+            backfill_minutes: NonZeroU8::new(backfill_minutes),
+            ..self
         }
+    }
 
-        if !self.media_fields.is_empty() {
+    /// Connects to the stream. Returns an error if the connection could not be opened; once it
+    /// is open, Tweets are received from the returned [`TweetStream`].
+    pub async fn connect<A>(&self, client: &AsyncClient<A>) -> Result<TweetStream, Error>
+    where
+        A: AppAuth,
+    {
+        let mut params = Vec::new();
+
+        if let Some(backfill_minutes) = self.backfill_minutes {
             params.push((
-                Cow::Borrowed("media.fields"),
-                Cow::Borrowed(&self.media_fields)
+                Cow::Borrowed("backfill_minutes"),
+                Cow::Owned(format!("{}", backfill_minutes))
             ));
         }
 
-        let (mut response, limit_info)
-            = client.apiv2_request::<_, Box<[Tweet]>>(Request::new_with_data(
-                Method::Get,
-                &format!("https://api.twitter.com/2/users/{}/tweets", self.id),
-                FormData::new(&params)
-            )).await?;
+        push_field_params(&mut params, self.describe_request());
 
-        let tweets = response
-            .data
-            .ok_or_else(|| ErrorRepr {
-                kind: ErrorKind::NoData,
-                limit_info: Some(limit_info.clone()),
-            }.boxed())?;
+        let (response, limit_info) = client.stream_request(Request::new_with_data(
+            Method::Get,
+            "https://api.twitter.com/2/tweets/sample/stream",
+            FormData::new(&params)
+        )).await?;
 
-        let previous_token = match response.meta.remove("previous_token") {
-            Some(Value::String(previous_token)) => {
-                Some(PaginationToken(previous_token.into_boxed_str()))
-            },
-            _ => None,
-        }; 
+        Ok(TweetStream::new(response, limit_info))
+    }
+}
 
-        let next_token = match response.meta.remove("next_token") {
-            Some(Value::String(next_token)) => {
-                Some(PaginationToken(next_token.into_boxed_str()))
-            },
-            _ => None,
-        };
+impl Default for SampleStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        Ok(UserTimelineResponse {
-            tweets,
-            includes: response.includes,
-            previous_token,
-            next_token,
-            limit_info,
+// The streaming endpoints accept the same fields and expansions, so the setters for them are
+// generated by this macro for each stream request type.
+macro_rules! impl_stream_field_setters {
+    ($($request:ty),* $(,)?) => {
+        $(
+            impl $request {
+                #[inline]
+                #[must_use]
+                pub fn expansions<I>(self, expansions: I) -> Self
+                where
+                    I: IntoIterator<Item = TweetPayloadExpansion>,
+                {
+                    Self {
+                        expansions: scribe_comma_separated(expansions),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn tweet_fields<I>(self, tweet_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = TweetField>,
+                {
+                    Self {
+                        tweet_fields: scribe_comma_separated(tweet_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn user_fields<I>(self, user_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = UserField>,
+                {
+                    Self {
+                        user_fields: scribe_comma_separated(user_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn media_fields<I>(self, media_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = MediaField>,
+                {
+                    Self {
+                        media_fields: scribe_comma_separated(media_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn poll_fields<I>(self, poll_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = PollField>,
+                {
+                    Self {
+                        poll_fields: scribe_comma_separated(poll_fields),
+                        ..self
+                    }
+                }
+
+                #[inline]
+                #[must_use]
+                pub fn place_fields<I>(self, place_fields: I) -> Self
+                where
+                    I: IntoIterator<Item = PlaceField>,
+                {
+                    Self {
+                        place_fields: scribe_comma_separated(place_fields),
+                        ..self
+                    }
+                }
+
+                /// Returns the field and expansion lists that will be sent with this request.
+                pub fn describe_request(&self) -> RequestFields<'_> {
+                    RequestFields {
+                        expansions: &self.expansions,
+                        tweet_fields: &self.tweet_fields,
+                        user_fields: &self.user_fields,
+                        media_fields: &self.media_fields,
+                        poll_fields: &self.poll_fields,
+                        place_fields: &self.place_fields,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_stream_field_setters!(FilteredStream, SampleStream);
+
+/// An error indicating that a `max_results` value is outside of the range accepted by an endpoint.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MaxResultsError {
+    value: u16,
+    range: (u16, u16),
+}
+
+impl MaxResultsError {
+    /// The `max_results` value that was rejected.
+    pub fn value(&self) -> u16 {
+        self.value
+    }
+
+    /// The range of `max_results` values the endpoint accepts.
+    pub fn range(&self) -> RangeInclusive<u16> {
+        self.range.0..=self.range.1
+    }
+}
+
+impl fmt::Display for MaxResultsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "max_results must be between {} and {}, but got {}",
+            self.range.0, self.range.1, self.value
+        )
+    }
+}
+
+impl error::Error for MaxResultsError {}
+
+/// A type which Tweets can be deserialised into, either fully or partially.
+trait TweetModel: for<'de> Deserialize<'de> {
+    fn tweet_id(&self) -> TweetId;
+}
+
+impl TweetModel for Tweet {
+    fn tweet_id(&self) -> TweetId {
+        self.id
+    }
+}
+
+impl TweetModel for IdOnly<TweetId> {
+    fn tweet_id(&self) -> TweetId {
+        self.id
+    }
+}
+
+macro_rules! impl_paginated_request {
+    ($($request:ty => $response:ty, $auth:ident, $token_fn:ident);* $(;)?) => {
+        $(
+            impl<A> PaginatedRequest<A> for $request
+            where
+                A: $auth + Send + Sync,
+            {
+                type Response = $response;
+
+                fn with_pagination_token(self, token: PaginationToken) -> Self {
+                    self.$token_fn(token)
+                }
+
+                fn execute_page<'a>(
+                    &'a self,
+                    client: &'a AsyncClient<A>
+                ) -> BoxFuture<'a, Result<Self::Response, Error>>
+                {
+                    Box::pin(self.execute(client))
+                }
+            }
+        )*
+    };
+}
+
+impl_paginated_request! {
+    UserTimeline => UserTimelineResponse, AppAuth, pagination_token;
+    UserMentions => UserMentionsResponse, AppAuth, pagination_token;
+    HomeTimeline => HomeTimelineResponse, UserAuth, pagination_token;
+    SearchRecent => SearchResponse, AppAuth, next_token;
+    SearchAll => SearchAllResponse, AppAuth, next_token;
+    QuoteTweets => QuoteTweetsResponse, AppAuth, pagination_token;
+    ListTweets => ListTweetsResponse, AppAuth, pagination_token;
+    TweetRetweetedBy => UsersResponse, AppAuth, pagination_token;
+    TweetLikingUsers => UsersResponse, AppAuth, pagination_token;
+    UserFollowers => UsersResponse, AppAuth, pagination_token;
+    UserFollowing => UsersResponse, AppAuth, pagination_token;
+    ListMembers => UsersResponse, AppAuth, pagination_token;
+    BlockedUsers => UsersResponse, UserAuth, pagination_token;
+    UserLists => ListsResponse, AppAuth, pagination_token;
+}
+
+macro_rules! impl_paginated_response {
+    ($($response:ty),* $(,)?) => {
+        $(
+            impl PaginatedResponse for $response {
+                fn next_token(&self) -> Option<&PaginationToken> {
+                    self.next_token.as_ref()
+                }
+            }
+        )*
+    };
+}
+
+impl_paginated_response! {
+    SearchResponse,
+    QuoteTweetsResponse,
+    ListTweetsResponse,
+    UsersResponse,
+    ListsResponse,
+}
+
+impl<T> PaginatedResponse for UserTimelineResponse<T> {
+    fn next_token(&self) -> Option<&PaginationToken> {
+        self.next_token.as_ref()
+    }
+}
+
+/// Clamps `max_results` to the range a request accepts, for the request's `max_results` setter.
+fn clamp_max_results(max_results: u16, range: RangeInclusive<u16>) -> Option<NonZeroU16> {
+    NonZeroU16::new(max_results.clamp(*range.start(), *range.end()))
+}
+
+fn check_max_results(
+    max_results: u16,
+    range: RangeInclusive<u16>
+) -> Result<NonZeroU16, MaxResultsError>
+{
+    NonZeroU16::new(max_results)
+        .filter(|_| range.contains(&max_results))
+        .ok_or(MaxResultsError {
+            value: max_results,
+            range: (*range.start(), *range.end()),
         })
+}
+
+/// The comma-separated field and expansion lists a request will send, exactly as they will appear
+/// in the request's query parameters. An empty string means the parameter will be omitted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RequestFields<'a> {
+    pub expansions: &'a str,
+    pub tweet_fields: &'a str,
+    pub user_fields: &'a str,
+    pub media_fields: &'a str,
+    pub poll_fields: &'a str,
+    pub place_fields: &'a str,
+}
+
+/// Adds the non-empty field and expansion lists to the request parameters.
+fn push_field_params<'a>(
+    params: &mut Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    fields: RequestFields<'a>
+) {
+    let fields = [
+        ("expansions", fields.expansions),
+        ("tweet.fields", fields.tweet_fields),
+        ("user.fields", fields.user_fields),
+        ("media.fields", fields.media_fields),
+        ("poll.fields", fields.poll_fields),
+        ("place.fields", fields.place_fields),
+    ];
+
+    for (key, value) in fields {
+        if !value.is_empty() {
+            params.push((Cow::Borrowed(key), Cow::Borrowed(value)));
+        }
     }
 }
 
-#[derive(Debug)]
-pub struct UserTimelineResponse {
-    pub tweets: Box<[Tweet]>,
-    pub includes: Includes,
-    pub previous_token: Option<PaginationToken>,
-    pub next_token: Option<PaginationToken>,
-    pub limit_info: LimitInfo,
+fn push_list_fields_param<'a>(
+    params: &mut Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    list_fields: &'a str
+) {
+    if !list_fields.is_empty() {
+        params.push((Cow::Borrowed("list.fields"), Cow::Borrowed(list_fields)));
+    }
+}
+
+fn push_space_fields_param<'a>(
+    params: &mut Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    space_fields: &'a str
+) {
+    if !space_fields.is_empty() {
+        params.push((Cow::Borrowed("space.fields"), Cow::Borrowed(space_fields)));
+    }
+}
+
+fn meta_pagination_token(meta: &mut Map<String, Value>, key: &str) -> Option<PaginationToken> {
+    match meta.remove(key) {
+        Some(Value::String(token)) => Some(PaginationToken(token.into_boxed_str())),
+        _ => None,
+    }
+}
+
+fn meta_u64(meta: &Map<String, Value>, key: &str) -> Option<u64> {
+    meta.get(key).and_then(Value::as_u64)
+}
+
+fn meta_tweet_id(meta: &Map<String, Value>, key: &str) -> Option<TweetId> {
+    meta.get(key)
+        .and_then(Value::as_str)
+        .and_then(|id| id.parse().ok())
 }
 
 fn scribe_comma_separated<T, I>(iter: I) -> String
@@ -576,7 +3698,15 @@ where
     T: ScribeStaticStr,
     I: IntoIterator<Item = T>,
 {
-    let iter = iter.into_iter().map(|t| t.scribe());
+    // Skip any duplicates, since Twitter rejects some requests with repeated values.
+    let mut seen = Vec::<&'static str>::new();
+    let iter = iter.into_iter().map(|t| t.scribe()).filter(|s| {
+        let duplicate = seen.contains(s);
+        if !duplicate {
+            seen.push(*s);
+        }
+        !duplicate
+    });
     let mut sink = SinkString::empty();
     result_elim(sink_comma_separated(&mut sink, iter));
     sink.0
@@ -624,3 +3754,391 @@ where
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use reqwest::StatusCode;
+    use serde_json::json;
+
+    use crate::{
+        client::{ErrorKind, ErrorRepr},
+        limit::LimitInfo,
+        query::Query,
+        response::{ApiV2Response, Includes},
+        timeline::PaginationToken,
+        tweet::{ReplySettings, Tweet, TweetId},
+        upload::MediaUploadId,
+        user::UserId,
+        request_options::{SortOrder, TweetField, TweetPayloadExpansion},
+    };
+
+    use super::{
+        meta_pagination_token,
+        meta_tweet_id,
+        meta_u64,
+        LookupTweets,
+        LookupUsersByUsername,
+        PostTweet,
+        PostTweetResponse,
+        SearchParams,
+        SearchRecent,
+        TweetCountBucket,
+        UserFollowers,
+        UserLists,
+        UserTimeline,
+    };
+
+    #[test]
+    fn test_dedup_expansions() {
+        let lookup = LookupTweets::new([TweetId(1)])
+            .expansions([
+                TweetPayloadExpansion::AuthorId,
+                TweetPayloadExpansion::ReferencedTweetsId,
+                TweetPayloadExpansion::AuthorId,
+            ])
+            .tweet_fields([TweetField::CreatedAt, TweetField::CreatedAt]);
+
+        let fields = lookup.describe_request();
+        assert_eq!(fields.expansions, "author_id,referenced_tweets.id");
+        assert_eq!(fields.tweet_fields, "created_at");
+    }
+
+    #[test]
+    fn test_post_tweet_reply_settings() {
+        let post = PostTweet::new_with_text("hello");
+        assert_eq!(serde_json::to_value(&post).unwrap(), json!({ "text": "hello" }));
+
+        let post = PostTweet::new_with_text("hello")
+            .reply_settings(ReplySettings::Followers);
+        assert_eq!(
+            serde_json::to_value(&post).unwrap(),
+            json!({ "text": "hello", "reply_settings": "followers" })
+        );
+
+        let post = PostTweet::new_with_text("hello")
+            .reply_settings(ReplySettings::Other("subscribers".into()));
+        assert_eq!(
+            serde_json::to_value(&post).unwrap(),
+            json!({ "text": "hello", "reply_settings": "subscribers" })
+        );
+    }
+
+    #[test]
+    fn test_post_tweet_media() {
+        let media_ids = [MediaUploadId(1455952740635586573)];
+        let tagged = [UserId(2244994945)];
+
+        let post = PostTweet::new_with_text("hello").media(&media_ids, None);
+        assert_eq!(
+            serde_json::to_value(&post).unwrap(),
+            json!({ "text": "hello", "media": { "media_ids": ["1455952740635586573"] } })
+        );
+        assert!(post.validate().is_ok());
+
+        let post = PostTweet::new_with_text("hello").media(&media_ids, Some(&tagged));
+        assert_eq!(
+            serde_json::to_value(&post).unwrap(),
+            json!({
+                "text": "hello",
+                "media": {
+                    "media_ids": ["1455952740635586573"],
+                    "tagged_user_ids": ["2244994945"],
+                },
+            })
+        );
+
+        assert!(PostTweet::new_with_text("hello").media(&[], None).validate().is_err());
+
+        let media_ids = [1, 2, 3, 4, 5].map(MediaUploadId);
+        assert!(PostTweet::new_with_text("hello").media(&media_ids[..4], None).validate().is_ok());
+        assert!(PostTweet::new_with_text("hello").media(&media_ids, None).validate().is_err());
+    }
+
+    #[test]
+    fn test_post_tweet_poll() {
+        let post = PostTweet::new_with_text("hello").poll(["a", "b"], 1440);
+        assert_eq!(
+            serde_json::to_value(&post).unwrap(),
+            json!({
+                "text": "hello",
+                "poll": { "options": ["a", "b"], "duration_minutes": 1440 },
+            })
+        );
+        assert!(post.validate().is_ok());
+
+        assert!(PostTweet::new_with_text("hello").poll(["a"], 1440).validate().is_err());
+        assert!(PostTweet::new_with_text("hello")
+            .poll(["a", "b", "c", "d", "e"], 1440)
+            .validate()
+            .is_err());
+        assert!(PostTweet::new_with_text("hello").poll(["a", "b"], 4).validate().is_err());
+        assert!(PostTweet::new_with_text("hello").poll(["a", "b"], 10081).validate().is_err());
+
+        let media_ids = [MediaUploadId(1)];
+        assert!(PostTweet::new_with_text("hello")
+            .media(&media_ids, None)
+            .poll(["a", "b"], 1440)
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_post_tweet_geo() {
+        let post = PostTweet::new_with_text("hello").geo("df51dec6f4ee2b2c");
+        assert_eq!(
+            serde_json::to_value(&post).unwrap(),
+            json!({ "text": "hello", "geo": { "place_id": "df51dec6f4ee2b2c" } })
+        );
+    }
+
+    #[test]
+    fn test_post_tweet_direct_message_deep_link() {
+        let link = "https://twitter.com/messages/compose?recipient_id=2244994945";
+        let post = PostTweet::new_with_text("hello").direct_message_deep_link(link);
+        assert_eq!(
+            serde_json::to_value(&post).unwrap(),
+            json!({ "text": "hello", "direct_message_deep_link": link })
+        );
+    }
+
+    #[test]
+    fn test_post_tweet_includes_error() {
+        let lookup_error = ErrorRepr {
+            kind: ErrorKind::ErrorResponse {
+                status: StatusCode::TOO_MANY_REQUESTS,
+                errors: Box::new([]),
+            },
+            limit_info: None,
+        }.boxed();
+
+        let response = PostTweetResponse::new(
+            TweetId(1),
+            "hello".into(),
+            Err(lookup_error),
+            LimitInfo::empty()
+        );
+        assert_eq!(response.id, TweetId(1));
+        assert_eq!(&*response.text, "hello");
+        assert!(response.includes.users.is_empty());
+        assert!(response.includes_error.unwrap().is_rate_limited());
+
+        let response = PostTweetResponse::new(
+            TweetId(1),
+            "hello".into(),
+            Ok(Includes::default()),
+            LimitInfo::empty()
+        );
+        assert!(response.includes_error.is_none());
+    }
+
+    #[test]
+    fn test_user_timeline_serde_round_trip() {
+        let timeline = UserTimeline::new(UserId(1030814512851681280))
+            .start_time(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap())
+            .exclude_retweets()
+            .max_results(50)
+            .pagination_token(PaginationToken("7140dibdnow9c7btw423x78ysnnmq0l8".into()))
+            .since_id(TweetId(1612530463424790530))
+            .tweet_fields([TweetField::AuthorId, TweetField::CreatedAt]);
+
+        let json = serde_json::to_string(&timeline).unwrap();
+        let restored = serde_json::from_str::<UserTimeline>(&json).unwrap();
+
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+        assert_eq!(restored.pagination_token, timeline.pagination_token);
+        assert_eq!(restored.since_id, Some(TweetId(1612530463424790530)));
+        assert_eq!(&*restored.tweet_fields, "author_id,created_at");
+    }
+
+    #[test]
+    fn test_user_timeline_time_params() {
+        let start_time = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+        let end_time = Utc.with_ymd_and_hms(2023, 2, 1, 0, 0, 0).unwrap();
+
+        let timeline = UserTimeline::new(UserId(1030814512851681280))
+            .start_time(start_time)
+            .end_time(end_time);
+
+        let params = timeline.params();
+        let param = |key: &str| {
+            params.iter().find(|(k, _)| k == key).map(|(_, v)| &**v)
+        };
+
+        assert_eq!(param("start_time"), Some(&*start_time.to_rfc3339()));
+        assert_eq!(param("end_time"), Some(&*end_time.to_rfc3339()));
+        assert_eq!(params.iter().filter(|(k, _)| k == "start_time").count(), 1);
+    }
+
+    #[test]
+    fn test_user_timeline_empty_page() {
+        // The final page of a timeline, or a `since_id` poll with no new Tweets, has no `data`.
+        let json = r#"{"meta": {"result_count": 0}}"#;
+        let response = serde_json::from_str::<ApiV2Response<Box<[Tweet]>>>(json).unwrap();
+
+        let page = UserTimeline::new(UserId(1030814512851681280))
+            .page_from_response(response, LimitInfo::empty());
+
+        assert!(page.tweets.is_empty());
+        assert!(page.next_token.is_none());
+    }
+
+    #[test]
+    fn test_search_recent_query() {
+        let search = SearchRecent::new("from:rustlang");
+        assert_eq!(search.query, "from:rustlang");
+
+        let query = Query::from_user("rustlang").unwrap().and(Query::has_media());
+        let search = SearchRecent::new(query);
+        assert_eq!(search.query, "from:rustlang has:media");
+    }
+
+    #[test]
+    fn test_search_params_validation() {
+        let search = SearchRecent::new("rust");
+        let params = SearchParams {
+            query: &search.query,
+            start_time: Some(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()),
+            end_time: Some(Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap()),
+            max_results: None,
+            next_token: None,
+            since_id: Some(TweetId(1)),
+            until_id: Some(TweetId(2)),
+            sort_order: Some(SortOrder::Recency),
+            fields: search.describe_request(),
+            max_age: None,
+        };
+        let now = Utc.with_ymd_and_hms(2023, 1, 3, 0, 0, 0).unwrap();
+        assert!(params.validate(now).is_ok());
+
+        let reversed_times = SearchParams {
+            start_time: params.end_time,
+            end_time: params.start_time,
+            fields: search.describe_request(),
+            ..params
+        };
+        assert!(reversed_times.validate(now).is_err());
+
+        let reversed_ids = SearchParams {
+            since_id: Some(TweetId(2)),
+            until_id: Some(TweetId(1)),
+            fields: search.describe_request(),
+            ..params
+        };
+        assert!(reversed_ids.validate(now).is_err());
+
+        let relevancy = SearchParams {
+            sort_order: Some(SortOrder::Relevancy),
+            fields: search.describe_request(),
+            ..params
+        };
+        assert!(relevancy.validate(now).is_err());
+
+        let relevancy = SearchParams {
+            sort_order: Some(SortOrder::Relevancy),
+            since_id: None,
+            until_id: None,
+            fields: search.describe_request(),
+            ..params
+        };
+        assert!(relevancy.validate(now).is_ok());
+    }
+
+    #[test]
+    fn test_search_params_max_age() {
+        let search = SearchRecent::new("rust");
+        let now = Utc.with_ymd_and_hms(2023, 1, 8, 12, 0, 0).unwrap();
+        let params = |start_time| SearchParams {
+            start_time: Some(start_time),
+            max_age: Some(Duration::days(7)),
+            ..search.search_params()
+        };
+
+        // Exactly seven days ago, and slightly older to allow for clock skew.
+        let start_time = Utc.with_ymd_and_hms(2023, 1, 1, 12, 0, 0).unwrap();
+        assert!(params(start_time).validate(now).is_ok());
+        let start_time = Utc.with_ymd_and_hms(2023, 1, 1, 11, 56, 0).unwrap();
+        assert!(params(start_time).validate(now).is_ok());
+
+        // Older than the clock skew allowance.
+        let start_time = Utc.with_ymd_and_hms(2023, 1, 1, 11, 54, 0).unwrap();
+        assert!(params(start_time).validate(now).is_err());
+
+        // Full-archive search has no limit.
+        let params = SearchParams {
+            start_time: Some(Utc.with_ymd_and_hms(2010, 1, 1, 0, 0, 0).unwrap()),
+            ..search.search_params()
+        };
+        assert!(params.validate(now).is_ok());
+    }
+
+    #[test]
+    fn test_user_followers_max_results() {
+        let followers = UserFollowers::new(UserId(1)).max_results(5000);
+        assert_eq!(followers.max_results.unwrap().get(), 1000);
+
+        let followers = UserFollowers::new(UserId(1)).try_max_results(1000).unwrap();
+        assert_eq!(followers.max_results.unwrap().get(), 1000);
+        assert!(UserFollowers::new(UserId(1)).try_max_results(0).is_err());
+        assert!(UserFollowers::new(UserId(1)).try_max_results(1001).is_err());
+    }
+
+    #[test]
+    fn test_user_lists_max_results() {
+        let lists = UserLists::owned(UserId(1)).max_results(0);
+        assert_eq!(lists.max_results.unwrap().get(), 1);
+
+        let lists = UserLists::owned(UserId(1)).max_results(255);
+        assert_eq!(lists.max_results.unwrap().get(), 100);
+
+        let lists = UserLists::followed(UserId(1)).try_max_results(100).unwrap();
+        assert_eq!(lists.max_results.unwrap().get(), 100);
+        assert!(UserLists::followed(UserId(1)).try_max_results(101).is_err());
+    }
+
+    #[test]
+    fn test_lookup_usernames_param() {
+        let lookup = LookupUsersByUsername::new(["@rustlang", "TwitterDev"]);
+        assert_eq!(lookup.usernames_param().unwrap(), "rustlang,TwitterDev");
+
+        assert!(LookupUsersByUsername::new(["rust lang"]).usernames_param().is_err());
+
+        let usernames = vec!["user"; LookupUsersByUsername::MAX_USERNAMES + 1];
+        assert!(LookupUsersByUsername::new(usernames).usernames_param().is_err());
+    }
+
+    #[test]
+    fn test_search_meta() {
+        let mut meta = json!({
+            "newest_id": "1600000000000000001",
+            "oldest_id": "1600000000000000000",
+            "result_count": 2,
+            "next_token": "b26v89c19zqg8o3fpz"
+        });
+        let meta = meta.as_object_mut().unwrap();
+
+        assert_eq!(meta_u64(meta, "result_count"), Some(2));
+        assert_eq!(meta_tweet_id(meta, "newest_id"), Some(TweetId(1600000000000000001)));
+        assert_eq!(meta_tweet_id(meta, "oldest_id"), Some(TweetId(1600000000000000000)));
+        assert_eq!(meta_tweet_id(meta, "result_count"), None);
+        assert_eq!(
+            meta_pagination_token(meta, "next_token").unwrap().as_str(),
+            "b26v89c19zqg8o3fpz"
+        );
+        assert!(meta_pagination_token(meta, "next_token").is_none());
+    }
+
+    #[test]
+    fn test_count_bucket() {
+        let json = r#"{
+            "end": "2021-05-25T21:00:00.000Z",
+            "start": "2021-05-25T20:00:00.000Z",
+            "tweet_count": 1265
+        }"#;
+        let bucket = serde_json::from_str::<TweetCountBucket>(json).unwrap();
+        assert_eq!(bucket.start, Utc.with_ymd_and_hms(2021, 5, 25, 20, 0, 0).unwrap());
+        assert_eq!(bucket.end, Utc.with_ymd_and_hms(2021, 5, 25, 21, 0, 0).unwrap());
+        assert_eq!(bucket.tweet_count, 1265);
+    }
+}
@@ -1,9 +1,22 @@
 use std::borrow::Cow;
 
 use libshire::encoding::url::percent_encode;
-use reqwest::{RequestBuilder, header::{CONTENT_TYPE, HeaderValue}};
+use reqwest::{
+    header::{CONTENT_TYPE, HeaderValue},
+    multipart::{Form, Part},
+    RequestBuilder,
+};
 use serde::Serialize;
 
+/// Percent-encodes the key or value of a request parameter.
+///
+/// Parameters must be encoded identically when they are sent and when they are included in an
+/// OAuth 1.0a signature, otherwise the signature will be rejected. Both should therefore go
+/// through this function.
+pub(crate) fn encode_param(s: &str) -> Cow<str> {
+    percent_encode(s)
+}
+
 pub trait RequestData {
     fn has_params(&self) -> bool;
     fn for_each_param<'s, F: FnMut(&'s str, &'s str)>(&'s self, f: F);
@@ -21,6 +34,7 @@ impl RequestData for () {
         builder.build()
     }
 }
+#[derive(Clone, Copy)]
 pub struct QueryData<'a> {
     params: &'a [(&'a str, &'a str)],
 }
@@ -47,6 +61,7 @@ impl<'a> RequestData for QueryData<'a> {
     }
 }
 
+#[derive(Clone, Copy)]
 pub struct FormData<'a> {
     params: &'a [(Cow<'a, str>, Cow<'a, str>)],
 }
@@ -74,18 +89,31 @@ impl<'a> RequestData for FormData<'a> {
             if !buf.is_empty() {
                 buf.push('&');
             }
-            buf.push_str(&percent_encode(key.as_ref()));
+            buf.push_str(&encode_param(key));
             buf.push('=');
-            buf.push_str(&percent_encode(val.as_ref()));
+            buf.push_str(&encode_param(val));
         }
 
-        builder
-            .header(
+        let mut request = builder.build()?;
+
+        // GET requests have no body, so their parameters are sent in the query string instead.
+        if request.method() == reqwest::Method::GET {
+            if !buf.is_empty() {
+                let query = match request.url().query() {
+                    Some(query) if !query.is_empty() => format!("{}&{}", query, buf),
+                    _ => buf,
+                };
+                request.url_mut().set_query(Some(&query));
+            }
+        } else {
+            request.headers_mut().insert(
                 CONTENT_TYPE,
                 HeaderValue::from_static("application/x-www-form-urlencoded"),
-            )
-            .body(buf)
-            .build()
+            );
+            *request.body_mut() = Some(buf.into());
+        }
+
+        Ok(request)
     }
 }
 
@@ -99,6 +127,15 @@ impl<'a, B: ?Sized> JsonData<'a, B> {
     }
 }
 
+// Implemented manually, as deriving would require `B: Clone`.
+impl<'a, B: ?Sized> Clone for JsonData<'a, B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, B: ?Sized> Copy for JsonData<'a, B> {}
+
 impl<'a, B> RequestData for JsonData<'a, B>
 where
     B: Serialize + ?Sized,
@@ -113,3 +150,129 @@ where
         builder.json(self.json_body).build()
     }
 }
+
+/// A `multipart/form-data` body consisting of text parameters and a single binary part, as used by
+/// the media upload endpoint.
+///
+/// OAuth 1.0a signatures do not include the parameters of multipart bodies, so `for_each_param`
+/// yields nothing.
+#[derive(Clone, Copy)]
+pub struct MultipartData<'a> {
+    params: &'a [(Cow<'a, str>, Cow<'a, str>)],
+    file_name: &'a str,
+    file: &'a [u8],
+}
+
+impl<'a> MultipartData<'a> {
+    pub fn new(
+        params: &'a [(Cow<'a, str>, Cow<'a, str>)],
+        file_name: &'a str,
+        file: &'a [u8]
+    ) -> Self
+    {
+        Self { params, file_name, file }
+    }
+}
+
+impl<'a> RequestData for MultipartData<'a> {
+    fn has_params(&self) -> bool {
+        false
+    }
+
+    fn for_each_param<'s, F: FnMut(&'s str, &'s str)>(&'s self, _: F) {}
+
+    fn build_http_request(self, builder: RequestBuilder) -> reqwest::Result<reqwest::Request> {
+        let form = self.params.iter().fold(Form::new(), |form, (key, val)| {
+            form.text(key.clone().into_owned(), val.clone().into_owned())
+        });
+
+        let form = form.part(self.file_name.to_owned(), Part::bytes(self.file.to_vec()));
+
+        builder.multipart(form).build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use reqwest::header::CONTENT_TYPE;
+    use serde_json::json;
+
+    use super::{FormData, JsonData, RequestData};
+
+    #[test]
+    fn test_form_data_get_query() {
+        let params = [
+            (Cow::Borrowed("ids"), Cow::Borrowed("1,2")),
+            (Cow::Borrowed("tweet.fields"), Cow::Borrowed("author_id")),
+        ];
+        let client = reqwest::Client::new();
+
+        let request = FormData::new(&params)
+            .build_http_request(client.get("https://api.twitter.com/2/tweets"))
+            .unwrap();
+
+        assert_eq!(request.url().query(), Some("ids=1%2C2&tweet.fields=author_id"));
+        assert!(request.body().is_none());
+        assert!(request.headers().get(CONTENT_TYPE).is_none());
+
+        // Parameters are appended to any query string already in the URL.
+        let request = FormData::new(&params)
+            .build_http_request(client.get("https://api.twitter.com/2/tweets?a=b"))
+            .unwrap();
+
+        assert_eq!(request.url().query(), Some("a=b&ids=1%2C2&tweet.fields=author_id"));
+
+        // With no parameters, the URL is left as it is.
+        let request = FormData::new(&[])
+            .build_http_request(client.get("https://api.twitter.com/2/users/me"))
+            .unwrap();
+
+        assert_eq!(request.url().as_str(), "https://api.twitter.com/2/users/me");
+    }
+
+    #[test]
+    fn test_form_data_body() {
+        let params = [
+            (Cow::Borrowed("ids"), Cow::Borrowed("1,2")),
+            (Cow::Borrowed("tweet.fields"), Cow::Borrowed("author_id")),
+        ];
+        let client = reqwest::Client::new();
+        let url = "https://api.twitter.com/2/tweets";
+
+        for builder in [client.post(url), client.put(url), client.delete(url)] {
+            let request = FormData::new(&params).build_http_request(builder).unwrap();
+
+            assert_eq!(request.url().query(), None);
+            assert_eq!(
+                request.headers().get(CONTENT_TYPE).unwrap(),
+                "application/x-www-form-urlencoded"
+            );
+            assert_eq!(
+                request.body().and_then(|body| body.as_bytes()),
+                Some(&b"ids=1%2C2&tweet.fields=author_id"[..])
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_data_methods() {
+        let body = json!({ "hidden": true });
+        let client = reqwest::Client::new();
+        let url = "https://api.twitter.com/2/tweets/1/hidden";
+
+        for builder in [client.put(url), client.patch(url), client.delete(url)] {
+            let request = JsonData::new(&body).build_http_request(builder).unwrap();
+
+            assert_eq!(
+                request.headers().get(CONTENT_TYPE).unwrap(),
+                "application/json"
+            );
+            assert_eq!(
+                request.body().and_then(|body| body.as_bytes()),
+                Some(&br#"{"hidden":true}"#[..])
+            );
+        }
+    }
+}
@@ -1,6 +1,8 @@
-use enumscribe::ScribeStaticStr;
+use std::{error, fmt};
 
-#[derive(ScribeStaticStr, Clone, Copy, Debug)]
+use enumscribe::{EnumDeserialize, EnumSerialize, ScribeStaticStr, TryUnscribe};
+
+#[derive(ScribeStaticStr, TryUnscribe, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum TweetField {
     #[enumscribe(str = "attachments")]
     Attachments,
@@ -12,6 +14,10 @@ pub enum TweetField {
     ConversationId,
     #[enumscribe(str = "created_at")]
     CreatedAt,
+    #[enumscribe(str = "edit_controls")]
+    EditControls,
+    #[enumscribe(str = "edit_history_tweet_ids")]
+    EditHistoryTweetIds,
     #[enumscribe(str = "entities")]
     Entities,
     #[enumscribe(str = "geo")]
@@ -22,6 +28,8 @@ pub enum TweetField {
     Lang,
     #[enumscribe(str = "non_public_metrics")]
     NonPublicMetrics,
+    #[enumscribe(str = "note_tweet")]
+    NoteTweet,
     #[enumscribe(str = "organic_metrics")]
     OrganicMetrics,
     #[enumscribe(str = "possibly_sensitive")]
@@ -40,7 +48,7 @@ pub enum TweetField {
     Withheld,
 }
 
-#[derive(ScribeStaticStr, Clone, Copy, Debug)]
+#[derive(ScribeStaticStr, TryUnscribe, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum UserField {
     #[enumscribe(str = "created_at")]
     CreatedAt,
@@ -66,7 +74,7 @@ pub enum UserField {
     Withheld,
 }
 
-#[derive(ScribeStaticStr, Clone, Copy, Debug)]
+#[derive(ScribeStaticStr, TryUnscribe, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum MediaField {
     #[enumscribe(str = "url")]
     Url,
@@ -92,7 +100,83 @@ pub enum MediaField {
     Variants,
 }
 
-#[derive(ScribeStaticStr, Clone, Copy, Debug)]
+#[derive(ScribeStaticStr, TryUnscribe, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PollField {
+    #[enumscribe(str = "duration_minutes")]
+    DurationMinutes,
+    #[enumscribe(str = "end_datetime")]
+    EndDatetime,
+    #[enumscribe(str = "voting_status")]
+    VotingStatus,
+}
+
+#[derive(ScribeStaticStr, TryUnscribe, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PlaceField {
+    #[enumscribe(str = "contained_within")]
+    ContainedWithin,
+    #[enumscribe(str = "country")]
+    Country,
+    #[enumscribe(str = "country_code")]
+    CountryCode,
+    #[enumscribe(str = "geo")]
+    Geo,
+    #[enumscribe(str = "name")]
+    Name,
+    #[enumscribe(str = "place_type")]
+    PlaceType,
+}
+
+#[derive(ScribeStaticStr, TryUnscribe, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ListField {
+    #[enumscribe(str = "created_at")]
+    CreatedAt,
+    #[enumscribe(str = "description")]
+    Description,
+    #[enumscribe(str = "follower_count")]
+    FollowerCount,
+    #[enumscribe(str = "member_count")]
+    MemberCount,
+    #[enumscribe(str = "owner_id")]
+    OwnerId,
+    #[enumscribe(str = "private")]
+    Private,
+}
+
+#[derive(ScribeStaticStr, TryUnscribe, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SpaceField {
+    #[enumscribe(str = "created_at")]
+    CreatedAt,
+    #[enumscribe(str = "creator_id")]
+    CreatorId,
+    #[enumscribe(str = "ended_at")]
+    EndedAt,
+    #[enumscribe(str = "host_ids")]
+    HostIds,
+    #[enumscribe(str = "invited_user_ids")]
+    InvitedUserIds,
+    #[enumscribe(str = "is_ticketed")]
+    IsTicketed,
+    #[enumscribe(str = "lang")]
+    Lang,
+    #[enumscribe(str = "participant_count")]
+    ParticipantCount,
+    #[enumscribe(str = "scheduled_start")]
+    ScheduledStart,
+    #[enumscribe(str = "speaker_ids")]
+    SpeakerIds,
+    #[enumscribe(str = "started_at")]
+    StartedAt,
+    #[enumscribe(str = "state")]
+    State,
+    #[enumscribe(str = "subscriber_count")]
+    SubscriberCount,
+    #[enumscribe(str = "title")]
+    Title,
+    #[enumscribe(str = "updated_at")]
+    UpdatedAt,
+}
+
+#[derive(ScribeStaticStr, TryUnscribe, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum TweetPayloadExpansion {
     #[enumscribe(str = "author_id")]
     AuthorId,
@@ -110,14 +194,116 @@ pub enum TweetPayloadExpansion {
     EntitiesMentionsUsername,
 }
 
-#[derive(ScribeStaticStr, Clone, Copy, Debug)]
+#[derive(ScribeStaticStr, TryUnscribe, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum UserPayloadExpansion {
     #[enumscribe(str = "pinned_tweet_id")]
     PinnedTweetId,
 }
 
-#[derive(ScribeStaticStr, Clone, Copy, Debug)]
+#[derive(ScribeStaticStr, TryUnscribe, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum IncludedReferencedTweetExpansion {
     #[enumscribe(str = "referenced_tweets.id.author_id")]
     AuthorId,
 }
+
+/// The size of the time buckets returned by the Tweet counts endpoints.
+#[derive(
+    ScribeStaticStr, EnumSerialize, EnumDeserialize, Clone, Copy, PartialEq, Eq, Hash, Debug
+)]
+pub enum Granularity {
+    #[enumscribe(str = "minute")]
+    Minute,
+    #[enumscribe(str = "hour")]
+    Hour,
+    #[enumscribe(str = "day")]
+    Day,
+}
+
+/// The order of search results.
+#[derive(
+    ScribeStaticStr, EnumSerialize, EnumDeserialize, Clone, Copy, PartialEq, Eq, Hash, Debug
+)]
+pub enum SortOrder {
+    /// Most recent Tweets first.
+    #[enumscribe(str = "recency")]
+    Recency,
+    /// Most relevant Tweets first, according to Twitter.
+    #[enumscribe(str = "relevancy")]
+    Relevancy,
+}
+
+/// The states of Spaces to include in search results.
+#[derive(
+    ScribeStaticStr, EnumSerialize, EnumDeserialize, Clone, Copy, PartialEq, Eq, Hash, Debug
+)]
+pub enum SpaceStateFilter {
+    #[enumscribe(str = "live")]
+    Live,
+    #[enumscribe(str = "scheduled")]
+    Scheduled,
+    /// Both live and scheduled Spaces.
+    #[enumscribe(str = "all")]
+    All,
+}
+
+/// An error indicating that a string is not a recognised field or expansion name.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UnknownOptionError(Box<str>);
+
+impl UnknownOptionError {
+    /// The string which was not recognised.
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for UnknownOptionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown field or expansion: \"{}\"", self.0)
+    }
+}
+
+impl error::Error for UnknownOptionError {}
+
+macro_rules! impl_try_from_str {
+    ($($option:ty),*) => {
+        $(
+            impl<'a> TryFrom<&'a str> for $option {
+                type Error = UnknownOptionError;
+
+                fn try_from(name: &'a str) -> Result<Self, Self::Error> {
+                    Self::try_unscribe(name).ok_or_else(|| UnknownOptionError(name.into()))
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_str!(
+    TweetField,
+    UserField,
+    MediaField,
+    PollField,
+    ListField,
+    SpaceField,
+    TweetPayloadExpansion,
+    UserPayloadExpansion,
+    IncludedReferencedTweetExpansion
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{TweetField, TweetPayloadExpansion, UserField};
+
+    #[test]
+    fn test_try_from_str() {
+        assert!(matches!(TweetField::try_from("author_id"), Ok(TweetField::AuthorId)));
+        assert!(matches!(TweetField::try_from("note_tweet"), Ok(TweetField::NoteTweet)));
+        assert!(matches!(UserField::try_from("pinned_tweet_id"), Ok(UserField::PinnedTweetId)));
+        assert!(matches!(
+            TweetPayloadExpansion::try_from("referenced_tweets.id"),
+            Ok(TweetPayloadExpansion::ReferencedTweetsId)
+        ));
+        assert_eq!(TweetField::try_from("not_a_field").unwrap_err().name(), "not_a_field");
+    }
+}
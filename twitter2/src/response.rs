@@ -1,7 +1,13 @@
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::{Value, Map};
 
-use crate::{ media::Media, tweet::Tweet, user::User };
+use crate::{
+    media::{Media, MediaKey},
+    place::Place,
+    poll::Poll,
+    tweet::{Tweet, TweetId},
+    user::{User, UserId},
+};
 
 #[derive(Deserialize, Debug)]
 pub(crate) struct ApiV2Response<T> {
@@ -19,25 +25,374 @@ pub(crate) struct ApiV2Response<T> {
     pub meta: Map<String, Value>,
 }
 
-#[derive(Deserialize, Debug)]
+impl<T> ApiV2Response<T> {
+    /// Returns the response's errors. If the errors list is empty, the top-level problem fields
+    /// are used instead: some errors, such as a 403 from an endpoint the app does not have access
+    /// to, are described by a problem object rather than an errors list.
+    pub(crate) fn into_errors(self) -> Box<[ResponseError]> {
+        if self.errors.is_empty() {
+            self.problem_error().into_iter().collect()
+        } else {
+            self.errors
+        }
+    }
+
+    /// Converts the top-level problem fields of the response, if there are any, into a
+    /// `ResponseError`.
+    pub(crate) fn problem_error(&self) -> Option<ResponseError> {
+        let message = self.detail.as_ref().or(self.title.as_ref())?;
+
+        Some(ResponseError {
+            parameters: None,
+            code: None,
+            message: Some(message.clone()),
+            error_type: self.response_type.clone(),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ResponseError {
     pub parameters: Option<ErrorParameters>,
     pub code: Option<u32>,
     pub message: Option<Box<str>>,
+    /// A URI identifying the kind of problem, such as
+    /// `https://api.twitter.com/2/problems/resource-not-found`.
+    #[serde(rename = "type")]
+    pub error_type: Option<Box<str>>,
 }
 
-#[derive(Deserialize, Debug)]
+impl ResponseError {
+    /// Returns `true` if the error's type is the Twitter problem with the given name, such as
+    /// `resource-not-found`.
+    pub(crate) fn is_problem(&self, name: &str) -> bool {
+        self.error_type
+            .as_deref()
+            .and_then(|error_type| error_type.strip_prefix("https://api.twitter.com/2/problems/"))
+            .map_or(false, |problem| problem == name)
+    }
+}
+
+/// The request parameters that an error relates to, as echoed back by Twitter. The field and
+/// expansion names can be converted back into their typed equivalents (such as
+/// [`TweetField`](crate::request_options::TweetField)) using `TryFrom<&str>`.
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ErrorParameters {
     #[serde(default)]
     pub expansions: Box<[Box<str>]>,
+    #[serde(rename = "tweet.fields", default)]
+    pub tweet_fields: Box<[Box<str>]>,
+    #[serde(rename = "user.fields", default)]
+    pub user_fields: Box<[Box<str>]>,
+    #[serde(rename = "media.fields", default)]
+    pub media_fields: Box<[Box<str>]>,
+    #[serde(default)]
+    pub pagination_token: Box<[Box<str>]>,
 }
 
-#[derive(Deserialize, Default, Debug)]
+// Twitter has been known to use both singular and plural names for the includes keys, so the
+// alternative names are accepted too. Otherwise, the includes would silently be left empty.
+#[derive(Serialize, Deserialize, Default, Debug)]
 pub struct Includes {
-    #[serde(default)]
+    #[serde(default, alias = "tweet")]
     pub tweets: Box<[Tweet]>,
-    #[serde(default)]
+    #[serde(default, alias = "user")]
     pub users: Box<[User]>,
-    #[serde(default)]
+    #[serde(default, alias = "medias")]
     pub media: Box<[Media]>,
+    #[serde(default, alias = "poll")]
+    pub polls: Box<[Poll]>,
+    #[serde(default, alias = "place")]
+    pub places: Box<[Place]>,
+}
+
+impl Includes {
+    /// Finds the included object of type `T` with the given id, such as a [`Tweet`] by its
+    /// [`TweetId`] or a [`Media`] by its [`MediaKey`].
+    pub fn get<T>(&self, id: &T::Id) -> Option<&T>
+    where
+        T: IncludeItem,
+    {
+        T::items(self).iter().find(|item| item.include_id() == id)
+    }
+}
+
+/// An object which can be returned in the [`Includes`] of a response, and referred to by its id.
+pub trait IncludeItem: Sized {
+    type Id: PartialEq + ?Sized;
+
+    fn include_id(&self) -> &Self::Id;
+
+    /// The included objects of this type.
+    fn items(includes: &Includes) -> &[Self];
+}
+
+impl IncludeItem for Tweet {
+    type Id = TweetId;
+
+    fn include_id(&self) -> &Self::Id {
+        &self.id
+    }
+
+    fn items(includes: &Includes) -> &[Self] {
+        &includes.tweets
+    }
+}
+
+impl IncludeItem for User {
+    type Id = UserId;
+
+    fn include_id(&self) -> &Self::Id {
+        &self.id
+    }
+
+    fn items(includes: &Includes) -> &[Self] {
+        &includes.users
+    }
+}
+
+impl IncludeItem for Media {
+    type Id = MediaKey;
+
+    fn include_id(&self) -> &Self::Id {
+        &self.media_key
+    }
+
+    fn items(includes: &Includes) -> &[Self] {
+        &includes.media
+    }
+}
+
+impl IncludeItem for Poll {
+    type Id = str;
+
+    fn include_id(&self) -> &Self::Id {
+        &self.id
+    }
+
+    fn items(includes: &Includes) -> &[Self] {
+        &includes.polls
+    }
+}
+
+impl IncludeItem for Place {
+    type Id = str;
+
+    fn include_id(&self) -> &Self::Id {
+        &self.id
+    }
+
+    fn items(includes: &Includes) -> &[Self] {
+        &includes.places
+    }
+}
+
+/// A lightweight model of a Tweet, user or other object which only deserialises its `id`,
+/// skipping the rest of the object. This is useful when only the ids in a response are needed,
+/// such as when polling for the newest Tweet.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct IdOnly<I> {
+    pub id: I,
+}
+
+/// A response payload which may be either a single object or an array of objects, such as the
+/// `data` of single and batch lookup endpoints. Either way, the objects are collected into a
+/// `Vec`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct OneOrMany<T>(pub Vec<T>);
+
+impl<T> OneOrMany<T> {
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> From<OneOrMany<T>> for Vec<T> {
+    fn from(one_or_many: OneOrMany<T>) -> Self {
+        one_or_many.0
+    }
+}
+
+impl<'de, T> Deserialize<'de> for OneOrMany<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OneOrManyRepr<T> {
+            Many(Vec<T>),
+            One(T),
+        }
+
+        match OneOrManyRepr::deserialize(deserializer)? {
+            OneOrManyRepr::Many(many) => Ok(Self(many)),
+            OneOrManyRepr::One(one) => Ok(Self(vec![one])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        media::Media,
+        place::Place,
+        poll::Poll,
+        tweet::{Tweet, TweetId},
+        user::{User, UserId},
+    };
+
+    use super::{ApiV2Response, IdOnly, Includes, OneOrMany};
+
+    #[test]
+    fn test_one_or_many_object() {
+        let json = r#"{"data":{"id":"1212092628029698048","text":"hello"}}"#;
+        let response = serde_json::from_str::<ApiV2Response<OneOrMany<Tweet>>>(json).unwrap();
+        let tweets = response.data.unwrap().into_vec();
+        assert_eq!(tweets.len(), 1);
+        assert_eq!(tweets[0].id, TweetId(1212092628029698048));
+        assert_eq!(&*tweets[0].text, "hello");
+    }
+
+    #[test]
+    fn test_one_or_many_array() {
+        let json = r#"{"data":[{"id":"1","text":"foo"},{"id":"2","text":"bar"}]}"#;
+        let response = serde_json::from_str::<ApiV2Response<OneOrMany<Tweet>>>(json).unwrap();
+        let tweets = response.data.unwrap().into_vec();
+        assert_eq!(tweets.len(), 2);
+        assert_eq!(tweets[0].id, TweetId(1));
+        assert_eq!(tweets[1].id, TweetId(2));
+        assert_eq!(&*tweets[1].text, "bar");
+    }
+
+    #[test]
+    fn test_includes_aliases() {
+        let json = r#"{
+            "tweet": [{"id": "1", "text": "foo"}],
+            "user": [{"id": "2", "name": "Name", "username": "username"}],
+            "medias": [{"media_key": "3_1234", "type": "photo"}]
+        }"#;
+
+        let includes = serde_json::from_str::<Includes>(json).unwrap();
+        assert_eq!(includes.tweets.len(), 1);
+        assert_eq!(includes.tweets[0].id, TweetId(1));
+        assert_eq!(includes.users.len(), 1);
+        assert_eq!(includes.users[0].id, UserId(2));
+        assert_eq!(includes.media.len(), 1);
+    }
+
+    #[test]
+    fn test_problem_error() {
+        let json = r#"{
+            "client_id": "1",
+            "reason": "client-not-enrolled",
+            "title": "Client Forbidden",
+            "detail": "This request must be made using an approved developer account.",
+            "type": "https://api.twitter.com/2/problems/client-forbidden"
+        }"#;
+        let response = serde_json::from_str::<ApiV2Response<Box<[Tweet]>>>(json).unwrap();
+        assert!(response.errors.is_empty());
+        let error = response.problem_error().unwrap();
+        assert_eq!(
+            error.message.as_deref(),
+            Some("This request must be made using an approved developer account.")
+        );
+
+        let response = serde_json::from_str::<ApiV2Response<Box<[Tweet]>>>("{}").unwrap();
+        assert!(response.problem_error().is_none());
+    }
+
+    #[test]
+    fn test_includes_get() {
+        let json = r#"{
+            "tweets": [{"id": "1", "text": "foo"}, {"id": "2", "text": "bar"}],
+            "users": [{"id": "3", "name": "Name", "username": "username"}],
+            "media": [{"media_key": "3_1234", "type": "photo"}],
+            "polls": [{"id": "1199786642468413448", "options": []}],
+            "places": [{"id": "01a9a39529b27f36", "full_name": "Manhattan, NY"}]
+        }"#;
+
+        let includes = serde_json::from_str::<Includes>(json).unwrap();
+        assert_eq!(&*includes.get::<Tweet>(&TweetId(2)).unwrap().text, "bar");
+        assert!(includes.get::<Tweet>(&TweetId(3)).is_none());
+        assert!(includes.get::<User>(&UserId(3)).is_some());
+        assert!(includes.get::<Media>(&"3_1234".parse().unwrap()).is_some());
+        assert!(includes.get::<Poll>("1199786642468413448").is_some());
+        assert!(includes.get::<Place>("01a9a39529b27f36").is_some());
+
+        let tweet = serde_json::from_str::<Tweet>(r#"{
+            "id": "4",
+            "text": "baz",
+            "geo": {"place_id": "01a9a39529b27f36"}
+        }"#).unwrap();
+        assert_eq!(&*tweet.place(&includes).unwrap().full_name, "Manhattan, NY");
+    }
+
+    #[test]
+    fn test_includes_round_trip() {
+        let json = r#"{
+            "tweets": [{
+                "id": "1",
+                "text": "foo #bar",
+                "author_id": "2",
+                "created_at": "2019-06-04T23:12:08.000Z",
+                "edit_history_tweet_ids": ["1"],
+                "entities": {"hashtags": [{"start": 4, "end": 8, "tag": "bar"}]},
+                "geo": {"place_id": "01a9a39529b27f36"},
+                "lang": "en",
+                "public_metrics": {
+                    "retweet_count": 1,
+                    "reply_count": 2,
+                    "like_count": 3,
+                    "quote_count": 4,
+                    "impression_count": 5
+                },
+                "referenced_tweets": [{"type": "quoted", "id": "3"}],
+                "reply_settings": "mentionedUsers",
+                "withheld": {"copyright": false, "country_codes": ["DE"], "scope": "tweet"}
+            }],
+            "users": [{"id": "2", "name": "Name", "username": "username", "protected": false}],
+            "media": [{"media_key": "3_1234", "type": "photo", "width": 640, "height": 480}],
+            "polls": [{
+                "id": "1199786642468413448",
+                "voting_status": "open",
+                "options": [{"position": 1, "label": "yes", "votes": 7}]
+            }],
+            "places": [{
+                "id": "01a9a39529b27f36",
+                "full_name": "Manhattan, NY",
+                "geo": {"type": "Feature", "bbox": [-74.0, 40.6, -73.9, 40.8], "properties": {}}
+            }]
+        }"#;
+
+        let includes = serde_json::from_str::<Includes>(json).unwrap();
+        let serialized = serde_json::to_string(&includes).unwrap();
+        let round_tripped = serde_json::from_str::<Includes>(&serialized).unwrap();
+
+        // Ids are serialised as strings, as Twitter sends them.
+        let value = serde_json::to_value(&includes).unwrap();
+        assert_eq!(value["tweets"][0]["id"], "1");
+        assert_eq!(value["tweets"][0]["author_id"], "2");
+        assert_eq!(value["media"][0]["media_key"], "3_1234");
+        assert_eq!(serde_json::to_value(&round_tripped).unwrap(), value);
+    }
+
+    #[test]
+    fn test_id_only() {
+        let json = r#"{"data":[
+            {"id":"2","text":"foo","entities":{"hashtags":[{"start":0,"end":4,"tag":"rust"}]}},
+            {"id":"1","text":"bar","public_metrics":{"like_count":3}}
+        ]}"#;
+        let response = serde_json::from_str::<ApiV2Response<Box<[IdOnly<TweetId>]>>>(json)
+            .unwrap();
+        let ids = response.data.unwrap().iter().map(|tweet| tweet.id).collect::<Vec<_>>();
+        assert_eq!(ids, [TweetId(2), TweetId(1)]);
+
+        let user = serde_json::from_str::<IdOnly<UserId>>(r#"{"id":"3","username":"u"}"#)
+            .unwrap();
+        assert_eq!(user.id, UserId(3));
+    }
 }
@@ -0,0 +1,129 @@
+use std::{error, fmt, str};
+
+use chrono::{DateTime, Utc};
+use enumscribe::{EnumDeserialize, EnumSerialize, ScribeStaticStr};
+use serde::{Deserialize, Serialize};
+
+use crate::user::UserId;
+
+/// The id of a Space. Unlike Tweet and user ids, Space ids are alphanumeric strings such as
+/// `1DXxyRYNejbKM`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[serde(transparent)]
+pub struct SpaceId(Box<str>);
+
+impl SpaceId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SpaceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl str::FromStr for SpaceId {
+    type Err = InvalidSpaceIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            Ok(Self(s.into()))
+        } else {
+            Err(InvalidSpaceIdError(s.into()))
+        }
+    }
+}
+
+/// An error indicating that a string is not a valid Space id.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InvalidSpaceIdError(Box<str>);
+
+impl fmt::Display for InvalidSpaceIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid space id: \"{}\"", self.0)
+    }
+}
+
+impl error::Error for InvalidSpaceIdError {}
+
+/// A Space, a live audio conversation. Only the id and state are returned by default; the other
+/// fields must be requested.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Space {
+    pub id: SpaceId,
+    pub state: SpaceState,
+    pub title: Option<Box<str>>,
+    pub creator_id: Option<UserId>,
+    #[serde(default)]
+    pub host_ids: Box<[UserId]>,
+    #[serde(default)]
+    pub speaker_ids: Box<[UserId]>,
+    #[serde(default)]
+    pub invited_user_ids: Box<[UserId]>,
+    pub participant_count: Option<u64>,
+    pub subscriber_count: Option<u64>,
+    pub is_ticketed: Option<bool>,
+    pub lang: Option<Box<str>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+#[derive(
+    ScribeStaticStr, EnumSerialize, EnumDeserialize, Clone, Copy, PartialEq, Eq, Hash, Debug
+)]
+pub enum SpaceState {
+    #[enumscribe(str = "live")]
+    Live,
+    #[enumscribe(str = "scheduled")]
+    Scheduled,
+    #[enumscribe(str = "ended")]
+    Ended,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::user::UserId;
+
+    use super::{Space, SpaceId, SpaceState};
+
+    #[test]
+    fn test_space_id() {
+        let id = "1DXxyRYNejbKM".parse::<SpaceId>().unwrap();
+        assert_eq!(id.as_str(), "1DXxyRYNejbKM");
+        assert_eq!(id.to_string(), "1DXxyRYNejbKM");
+        assert!("".parse::<SpaceId>().is_err());
+        assert!("1DXxy/RYNejbKM".parse::<SpaceId>().is_err());
+    }
+
+    #[test]
+    fn test_space() {
+        let json = r#"{
+            "id": "1DXxyRYNejbKM",
+            "state": "live",
+            "title": "Rust hangout",
+            "host_ids": ["2244994945"],
+            "participant_count": 12,
+            "started_at": "2021-07-04T23:12:08.000Z"
+        }"#;
+
+        let space = serde_json::from_str::<Space>(json).unwrap();
+        assert_eq!(space.id.as_str(), "1DXxyRYNejbKM");
+        assert_eq!(space.state, SpaceState::Live);
+        assert_eq!(space.title.as_deref(), Some("Rust hangout"));
+        assert_eq!(&*space.host_ids, &[UserId(2244994945)]);
+        assert!(space.speaker_ids.is_empty());
+        assert_eq!(space.participant_count, Some(12));
+        assert_eq!(
+            space.started_at,
+            Some(Utc.with_ymd_and_hms(2021, 7, 4, 23, 12, 8).unwrap())
+        );
+        assert_eq!(space.ended_at, None);
+    }
+}
@@ -0,0 +1,466 @@
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_util::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::{sync::mpsc::{self, error::TrySendError}, task::JoinHandle};
+
+use crate::{
+    client::{Error, ErrorKind, ErrorRepr},
+    limit::LimitInfo,
+    response::{Includes, ResponseError},
+    tweet::Tweet,
+};
+
+/// A Tweet received from a streaming endpoint, along with the objects resolved by the requested
+/// expansions.
+#[derive(Debug)]
+pub struct StreamedTweet {
+    pub tweet: Tweet,
+    pub includes: Includes,
+    /// The filtered stream rules which the Tweet matched. Always empty for the sampled stream.
+    pub matching_rules: Box<[MatchingRule]>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MatchingRule {
+    pub id: Box<str>,
+    pub tag: Option<Box<str>>,
+}
+
+/// An asynchronous stream of Tweets from one of Twitter's streaming endpoints.
+///
+/// Twitter periodically sends blank lines to keep the connection alive; these are skipped rather
+/// than being yielded. The stream ends when Twitter closes the connection. If the connection is
+/// lost or Twitter sends an error, the error is yielded as the stream's final item. Either way,
+/// the caller should reconnect if it wants to keep receiving Tweets.
+pub struct TweetStream {
+    inner: Pin<Box<dyn Stream<Item = Result<StreamedTweet, Error>> + Send>>,
+    limit_info: LimitInfo,
+}
+
+impl TweetStream {
+    pub(crate) fn new(response: reqwest::Response, limit_info: LimitInfo) -> Self {
+        let state = StreamState {
+            chunks: response.bytes_stream(),
+            lines: LineBuffer::new(),
+            finished: false,
+        };
+
+        let inner = stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(line) = state.lines.next_line() {
+                    match parse_line(&line) {
+                        Some(item) => {
+                            // Twitter sends errors immediately before disconnecting, so stop
+                            // reading once one is received.
+                            if item.is_err() {
+                                state.finish();
+                            }
+                            return Some((item, state));
+                        },
+                        None => continue,
+                    }
+                }
+
+                if state.finished {
+                    return None;
+                }
+
+                match state.chunks.next().await {
+                    Some(Ok(chunk)) => state.lines.push(&chunk),
+                    Some(Err(err)) => {
+                        state.finish();
+                        let err = ErrorRepr {
+                            kind: ErrorKind::from_reqwest(err),
+                            limit_info: None,
+                        }.boxed();
+                        return Some((Err(err), state));
+                    },
+                    None => {
+                        state.finished = true;
+                        state.lines.finish();
+                    },
+                }
+            }
+        });
+
+        Self::from_stream(inner, limit_info)
+    }
+
+    fn from_stream<S>(inner: S, limit_info: LimitInfo) -> Self
+    where
+        S: Stream<Item = Result<StreamedTweet, Error>> + Send + 'static,
+    {
+        Self {
+            inner: Box::pin(inner),
+            limit_info,
+        }
+    }
+
+    /// Rate limit information from the response which opened the stream.
+    pub fn limit_info(&self) -> &LimitInfo {
+        &self.limit_info
+    }
+
+    /// Reads the stream ahead of the consumer on a background task, buffering at most `capacity`
+    /// Tweets. `overflow` determines what happens when the consumer falls behind and the buffer
+    /// fills up; see [`Overflow`] for the trade-offs.
+    ///
+    /// The background task is spawned with [`tokio::spawn`], so this must be called from within
+    /// a Tokio runtime. The task stops when the returned stream is dropped.
+    pub fn buffered(self, capacity: usize, overflow: Overflow) -> BufferedTweetStream {
+        let limit_info = self.limit_info.clone();
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let task = tokio::spawn(read_ahead(self, sender, overflow));
+
+        BufferedTweetStream {
+            receiver,
+            task,
+            limit_info,
+        }
+    }
+}
+
+impl Stream for TweetStream {
+    type Item = Result<StreamedTweet, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl fmt::Debug for TweetStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TweetStream")
+            .field("limit_info", &self.limit_info)
+            .finish_non_exhaustive()
+    }
+}
+
+/// What a [`BufferedTweetStream`] does when its buffer is full.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Overflow {
+    /// Stop reading from the connection until the consumer catches up. No Tweets are lost, but
+    /// Twitter disconnects consumers which fall too far behind, so this is best suited to
+    /// consumers which are only occasionally slow.
+    Backpressure,
+    /// Keep reading from the connection, discarding Tweets which arrive while the buffer is full.
+    /// The number of Tweets discarded is reported by a [`StreamEvent::Lagged`] in the position
+    /// of the missing Tweets. Errors are never discarded.
+    DropNewest,
+}
+
+/// An item of a [`BufferedTweetStream`].
+#[derive(Debug)]
+pub enum StreamEvent {
+    Tweet(Box<StreamedTweet>),
+    /// The given number of Tweets were discarded because the buffer was full. Only sent when
+    /// using [`Overflow::DropNewest`].
+    Lagged(u64),
+}
+
+/// A [`TweetStream`] which is read ahead of the consumer into a bounded buffer by a background
+/// task. Created by [`TweetStream::buffered`].
+pub struct BufferedTweetStream {
+    receiver: mpsc::Receiver<Result<StreamEvent, Error>>,
+    task: JoinHandle<()>,
+    limit_info: LimitInfo,
+}
+
+impl BufferedTweetStream {
+    /// Rate limit information from the response which opened the stream.
+    pub fn limit_info(&self) -> &LimitInfo {
+        &self.limit_info
+    }
+}
+
+impl Stream for BufferedTweetStream {
+    type Item = Result<StreamEvent, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl Drop for BufferedTweetStream {
+    fn drop(&mut self) {
+        // The task may be waiting for the next Tweet rather than sending one, in which case it
+        // would not notice that the receiver has been dropped until a Tweet arrives.
+        self.task.abort();
+    }
+}
+
+impl fmt::Debug for BufferedTweetStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferedTweetStream")
+            .field("limit_info", &self.limit_info)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The sending half of a buffered stream was closed because the consumer dropped the stream.
+struct Closed;
+
+async fn read_ahead(
+    mut stream: TweetStream,
+    sender: mpsc::Sender<Result<StreamEvent, Error>>,
+    overflow: Overflow,
+) {
+    let mut dropped = 0;
+
+    while let Some(item) = stream.next().await {
+        let item = item.map(|tweet| StreamEvent::Tweet(Box::new(tweet)));
+
+        let result = if overflow == Overflow::DropNewest && item.is_ok() {
+            try_send_or_drop(&sender, item, &mut dropped)
+        } else {
+            send_waiting(&sender, item, &mut dropped).await
+        };
+
+        if result.is_err() {
+            return;
+        }
+    }
+
+    if dropped > 0 {
+        sender.send(Ok(StreamEvent::Lagged(dropped))).await.ok();
+    }
+}
+
+/// Sends the item if there is room in the buffer, preceded by a lag event if any items have been
+/// dropped since the last one was sent. Otherwise, drops the item and adds it to the count.
+fn try_send_or_drop(
+    sender: &mpsc::Sender<Result<StreamEvent, Error>>,
+    item: Result<StreamEvent, Error>,
+    dropped: &mut u64,
+) -> Result<(), Closed>
+{
+    if *dropped > 0 {
+        match sender.try_send(Ok(StreamEvent::Lagged(*dropped))) {
+            Ok(()) => *dropped = 0,
+            Err(TrySendError::Full(_)) => {
+                *dropped += 1;
+                return Ok(());
+            },
+            Err(TrySendError::Closed(_)) => return Err(Closed),
+        }
+    }
+
+    match sender.try_send(item) {
+        Ok(()) => Ok(()),
+        Err(TrySendError::Full(_)) => {
+            *dropped += 1;
+            Ok(())
+        },
+        Err(TrySendError::Closed(_)) => Err(Closed),
+    }
+}
+
+/// Sends the item, preceded by a lag event if any items have been dropped since the last one was
+/// sent, waiting for room in the buffer.
+async fn send_waiting(
+    sender: &mpsc::Sender<Result<StreamEvent, Error>>,
+    item: Result<StreamEvent, Error>,
+    dropped: &mut u64,
+) -> Result<(), Closed>
+{
+    if *dropped > 0 {
+        sender.send(Ok(StreamEvent::Lagged(*dropped))).await.map_err(|_| Closed)?;
+        *dropped = 0;
+    }
+
+    sender.send(item).await.map_err(|_| Closed)
+}
+
+struct StreamState<S> {
+    chunks: S,
+    lines: LineBuffer,
+    finished: bool,
+}
+
+impl<S> StreamState<S> {
+    /// Stops reading from the connection and discards any lines which have not been parsed yet.
+    fn finish(&mut self) {
+        self.finished = true;
+        self.lines = LineBuffer::new();
+    }
+}
+
+/// Splits the bytes of a newline-delimited body into lines, which may be split across chunks.
+struct LineBuffer {
+    buf: Vec<u8>,
+}
+
+impl LineBuffer {
+    fn new() -> Self {
+        Self { buf: Vec::new() }
+    }
+
+    fn push(&mut self, chunk: &[u8]) {
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// Removes and returns the next complete line, without its line terminator.
+    fn next_line(&mut self) -> Option<Vec<u8>> {
+        let end = self.buf.iter().position(|&b| b == b'\n')?;
+        let mut line = self.buf.drain(..=end).collect::<Vec<u8>>();
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        Some(line)
+    }
+
+    /// Terminates the last line, so that it is returned by `next_line` even if the body did not
+    /// end with a newline.
+    fn finish(&mut self) {
+        if !self.buf.is_empty() {
+            self.buf.push(b'\n');
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct StreamLine {
+    data: Option<Tweet>,
+    #[serde(default)]
+    includes: Includes,
+    #[serde(default)]
+    errors: Box<[ResponseError]>,
+    #[serde(default)]
+    matching_rules: Box<[MatchingRule]>,
+}
+
+/// Parses a line of a streaming response, returning `None` if it is a keep-alive heartbeat.
+fn parse_line(line: &[u8]) -> Option<Result<StreamedTweet, Error>> {
+    if line.iter().all(u8::is_ascii_whitespace) {
+        return None;
+    }
+
+    let line = match serde_json::from_slice::<StreamLine>(line) {
+        Ok(line) => line,
+        Err(err) => {
+            return Some(Err(ErrorRepr {
+                kind: ErrorKind::InvalidResponse(err),
+                limit_info: None,
+            }.boxed()));
+        },
+    };
+
+    match line.data {
+        Some(tweet) => Some(Ok(StreamedTweet {
+            tweet,
+            includes: line.includes,
+            matching_rules: line.matching_rules,
+        })),
+
+        // A line without a Tweet carries errors, such as an operational disconnect message. The
+        // connection itself succeeded, so the status is always 200.
+        None => Some(Err(ErrorRepr {
+            kind: ErrorKind::ErrorResponse {
+                status: reqwest::StatusCode::OK,
+                errors: line.errors,
+            },
+            limit_info: None,
+        }.boxed())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{stream, StreamExt};
+
+    use crate::{client::ErrorKind, limit::LimitInfo, response::Includes, tweet::TweetId};
+
+    use super::{parse_line, LineBuffer, Overflow, StreamEvent, StreamedTweet, TweetStream};
+
+    #[test]
+    fn test_line_buffer() {
+        let mut lines = LineBuffer::new();
+        lines.push(b"{\"a\":");
+        assert_eq!(lines.next_line(), None);
+        lines.push(b"1}\r\n\r\n{\"b\"");
+        assert_eq!(lines.next_line().as_deref(), Some(&b"{\"a\":1}"[..]));
+        assert_eq!(lines.next_line().as_deref(), Some(&b""[..]));
+        assert_eq!(lines.next_line(), None);
+        lines.push(b":2}");
+        lines.finish();
+        assert_eq!(lines.next_line().as_deref(), Some(&b"{\"b\":2}"[..]));
+        assert_eq!(lines.next_line(), None);
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert!(parse_line(b"").is_none());
+        assert!(parse_line(b"  ").is_none());
+
+        let line = br#"{
+            "data": {"id": "1", "text": "hello"},
+            "matching_rules": [{"id": "1166916266197536768", "tag": "rust"}]
+        }"#;
+        let streamed = parse_line(line).unwrap().unwrap();
+        assert_eq!(streamed.tweet.id, TweetId(1));
+        assert_eq!(streamed.matching_rules[0].tag.as_deref(), Some("rust"));
+
+        let line = br#"{"errors": [{"title": "operational-disconnect"}]}"#;
+        let err = parse_line(line).unwrap().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::ErrorResponse { .. }));
+
+        assert!(matches!(
+            parse_line(b"{not json").unwrap().unwrap_err().kind(),
+            ErrorKind::InvalidResponse(_)
+        ));
+    }
+
+    fn test_stream(len: u64) -> TweetStream {
+        let tweets = (1..=len).map(|id| {
+            let json = format!(r#"{{"id": "{}", "text": "hello"}}"#, id);
+            Ok(StreamedTweet {
+                tweet: serde_json::from_str(&json).unwrap(),
+                includes: Includes::default(),
+                matching_rules: Box::new([]),
+            })
+        });
+
+        TweetStream::from_stream(stream::iter(tweets.collect::<Vec<_>>()), LimitInfo::empty())
+    }
+
+    fn describe_event(event: StreamEvent) -> String {
+        match event {
+            StreamEvent::Tweet(streamed) => format!("tweet {}", streamed.tweet.id.0),
+            StreamEvent::Lagged(dropped) => format!("lagged {}", dropped),
+        }
+    }
+
+    #[test]
+    fn test_buffered_backpressure() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let events = runtime.block_on(async {
+            test_stream(5)
+                .buffered(2, Overflow::Backpressure)
+                .map(|event| describe_event(event.unwrap()))
+                .collect::<Vec<_>>()
+                .await
+        });
+        assert_eq!(events, ["tweet 1", "tweet 2", "tweet 3", "tweet 4", "tweet 5"]);
+    }
+
+    #[test]
+    fn test_buffered_drop_newest() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let events = runtime.block_on(async {
+            // The background task reads the whole stream before the consumer receives anything,
+            // so everything after the first two Tweets is dropped.
+            test_stream(5)
+                .buffered(2, Overflow::DropNewest)
+                .map(|event| describe_event(event.unwrap()))
+                .collect::<Vec<_>>()
+                .await
+        });
+        assert_eq!(events, ["tweet 1", "tweet 2", "lagged 3"]);
+    }
+}
@@ -0,0 +1,172 @@
+use std::{iter::Peekable, ops::RangeInclusive, str::CharIndices};
+
+/// The maximum weighted length of a Tweet's text, as measured by [`tweet_length`].
+pub const MAX_TWEET_LENGTH: usize = 280;
+
+/// The length every URL counts as, regardless of its actual length, since Twitter shortens all
+/// URLs with t.co.
+pub const URL_LENGTH: usize = 23;
+
+// Weights are scaled by 100, following Twitter's configuration.
+const WEIGHT_SCALE: usize = 100;
+const DEFAULT_WEIGHT: usize = 200;
+const LIGHT_WEIGHT: usize = 100;
+
+/// The code point ranges which count as a single character. Everything else, including CJK
+/// characters and emoji, counts as two.
+const LIGHT_RANGES: [RangeInclusive<u32>; 4] = [
+    0x0000..=0x10ff,
+    0x2000..=0x200d,
+    0x2010..=0x201f,
+    0x2032..=0x2037,
+];
+
+const ZERO_WIDTH_JOINER: char = '\u{200d}';
+
+/// Returns the length of the given Tweet text as Twitter counts it, following version 3 of
+/// Twitter's counting rules:
+///
+/// - Latin script and general punctuation count as one character.
+/// - Other characters, such as CJK characters and emoji, count as two.
+/// - An emoji sequence joined with zero-width joiners, modified with a skin tone or variation
+///   selector, or a pair of regional indicators forming a flag, counts as a single emoji.
+/// - Every `http://` or `https://` URL counts as [`URL_LENGTH`] characters.
+///
+/// URLs without a scheme, which Twitter also links, are counted character by character. The text
+/// is not Unicode-normalised before counting.
+pub fn tweet_length(text: &str) -> usize {
+    let mut weight = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if let Some(url_len) = url_len_at(text, i) {
+            weight += URL_LENGTH * WEIGHT_SCALE;
+            skip_bytes(&mut chars, i + url_len);
+            continue;
+        }
+
+        if is_emoji_modifier(c) {
+            continue;
+        }
+
+        if c == ZERO_WIDTH_JOINER {
+            // Joined characters form a single emoji along with the preceding character.
+            if i > 0 && chars.peek().is_some() {
+                chars.next();
+                continue;
+            }
+        }
+
+        // A pair of regional indicators forms a single flag emoji.
+        if is_regional_indicator(c)
+            && chars.peek().map_or(false, |&(_, next)| is_regional_indicator(next))
+        {
+            chars.next();
+        }
+
+        weight += char_weight(c);
+    }
+
+    weight / WEIGHT_SCALE
+}
+
+fn char_weight(c: char) -> usize {
+    let c = u32::from(c);
+    if LIGHT_RANGES.iter().any(|range| range.contains(&c)) {
+        LIGHT_WEIGHT
+    } else {
+        DEFAULT_WEIGHT
+    }
+}
+
+/// Variation selectors, skin tone modifiers, the combining keycap and tag characters, which all
+/// modify the preceding character rather than counting on their own.
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(c, '\u{fe00}'..='\u{fe0f}'
+        | '\u{1f3fb}'..='\u{1f3ff}'
+        | '\u{20e3}'
+        | '\u{e0020}'..='\u{e007f}')
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c, '\u{1f1e6}'..='\u{1f1ff}')
+}
+
+/// If a URL starts at byte index `i` of `text`, returns its length in bytes. The URL must start
+/// at the beginning of a word and ends at the next whitespace, excluding any trailing
+/// punctuation.
+fn url_len_at(text: &str, i: usize) -> Option<usize> {
+    const SCHEMES: [&str; 2] = ["http://", "https://"];
+
+    let at_word_start = text[..i]
+        .chars()
+        .next_back()
+        .map_or(true, |c| c.is_whitespace() || matches!(c, '(' | '"' | '\''));
+
+    if !at_word_start {
+        return None;
+    }
+
+    let rest = &text[i..];
+
+    let scheme_len = SCHEMES.iter().find_map(|scheme| {
+        rest.get(..scheme.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(scheme))
+            .map(|_| scheme.len())
+    })?;
+
+    let url = rest.split(char::is_whitespace).next().unwrap_or_default();
+    let url = url.trim_end_matches(|c: char| {
+        matches!(c, '.' | ',' | '!' | '?' | ':' | ';' | ')' | '"' | '\'')
+    });
+
+    // A scheme with nothing after it is not linked.
+    if url.len() <= scheme_len {
+        return None;
+    }
+
+    Some(url.len())
+}
+
+fn skip_bytes(chars: &mut Peekable<CharIndices>, end: usize) {
+    while chars.next_if(|&(i, _)| i < end).is_some() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tweet_length, MAX_TWEET_LENGTH};
+
+    #[test]
+    fn test_tweet_length_weights() {
+        assert_eq!(tweet_length(""), 0);
+        assert_eq!(tweet_length("hello, world"), 12);
+        assert_eq!(tweet_length("café — “quoted”"), 15);
+        assert_eq!(tweet_length("日本語"), 6);
+        assert_eq!(tweet_length("a日"), 3);
+        assert_eq!(tweet_length(&"a".repeat(MAX_TWEET_LENGTH)), MAX_TWEET_LENGTH);
+        assert_eq!(tweet_length(&"日".repeat(140)), MAX_TWEET_LENGTH);
+    }
+
+    #[test]
+    fn test_tweet_length_emoji() {
+        assert_eq!(tweet_length("🦀"), 2);
+        assert_eq!(tweet_length("👍🏽"), 2);
+        assert_eq!(tweet_length("❤️"), 2);
+        assert_eq!(tweet_length("👩‍👩‍👧‍👦"), 2);
+        assert_eq!(tweet_length("🇬🇧🇫🇷"), 4);
+        assert_eq!(tweet_length("hi 🦀!"), 6);
+    }
+
+    #[test]
+    fn test_tweet_length_urls() {
+        assert_eq!(tweet_length("https://example.com"), 23);
+        assert_eq!(
+            tweet_length("see https://example.com/a/very/long/path/which/is/longer/than/23"),
+            27
+        );
+        assert_eq!(tweet_length("(http://example.com)."), 26);
+        assert_eq!(tweet_length("HTTPS://EXAMPLE.COM"), 23);
+        assert_eq!(tweet_length("xhttps://example.com"), 20);
+        assert_eq!(tweet_length("https://"), 8);
+    }
+}
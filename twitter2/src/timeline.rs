@@ -1,2 +1,259 @@
-#[derive(Clone, PartialEq, Eq, Debug)]
+use futures_util::{future::BoxFuture, stream, Stream};
+use serde::{Deserialize, Serialize};
+
+use crate::{client::Error, AsyncClient};
+
+/// A token returned by a paginated endpoint, which can be passed back to it to retrieve the next or
+/// previous page of results.
+///
+/// Tokens can be stored (for example, by serialising them) and used to resume pagination later.
+/// Twitter may stop accepting a token after some time, in which case the request fails with an
+/// error for which [`Error::is_invalid_pagination_token`](crate::client::Error::is_invalid_pagination_token)
+/// returns `true`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(transparent)]
 pub struct PaginationToken(pub Box<str>);
+
+impl PaginationToken {
+    pub fn new<S>(token: S) -> Self
+    where
+        S: Into<Box<str>>,
+    {
+        Self(token.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A request for an endpoint which returns its results in pages, linked together by
+/// [`PaginationToken`]s.
+pub trait PaginatedRequest<A>: Sized {
+    type Response: PaginatedResponse;
+
+    /// Returns a copy of the request which retrieves the page identified by `token`.
+    fn with_pagination_token(self, token: PaginationToken) -> Self;
+
+    /// Makes the request, returning a single page of results.
+    fn execute_page<'a>(
+        &'a self,
+        client: &'a AsyncClient<A>
+    ) -> BoxFuture<'a, Result<Self::Response, Error>>;
+}
+
+/// A single page of results returned by a [`PaginatedRequest`].
+pub trait PaginatedResponse {
+    /// The token for the page after this one, or `None` if this is the last page.
+    fn next_token(&self) -> Option<&PaginationToken>;
+}
+
+/// Requests each page of a paginated endpoint in turn, following the `next_token` returned with
+/// each page until there are no more pages.
+///
+/// ```ignore
+/// let mut pages = Paginator::new(&client, UserFollowers::new(user_id));
+/// while let Some(page) = pages.next_page().await {
+///     let page = page?;
+///     // ...
+/// }
+/// ```
+pub struct Paginator<'c, A, B> {
+    client: &'c AsyncClient<A>,
+    next: Option<B>,
+}
+
+impl<'c, A, B> Paginator<'c, A, B>
+where
+    B: PaginatedRequest<A>,
+{
+    /// Creates a paginator which starts from the page that `request` would retrieve.
+    pub fn new(client: &'c AsyncClient<A>, request: B) -> Self {
+        Self {
+            client,
+            next: Some(request),
+        }
+    }
+
+    /// Retrieves the next page, or returns `None` once the last page has been retrieved.
+    ///
+    /// If the request fails, its error is returned. If the error is
+    /// [retryable](Error::is_retryable), such as a rate limit or a server error, the same page is
+    /// requested again on the next call. Otherwise, retrying would fail in the same way, so the
+    /// paginator finishes and the next call returns `None`.
+    pub async fn next_page(&mut self) -> Option<Result<B::Response, Error>> {
+        let request = self.next.take()?;
+
+        let response = match request.execute_page(self.client).await {
+            Ok(response) => response,
+            Err(err) => {
+                if err.is_retryable() {
+                    self.next = Some(request);
+                }
+                return Some(Err(err));
+            },
+        };
+
+        self.next = response
+            .next_token()
+            .cloned()
+            .map(|token| request.with_pagination_token(token));
+
+        Some(Ok(response))
+    }
+
+    /// Returns `true` if there are no more pages to retrieve.
+    pub fn is_finished(&self) -> bool {
+        self.next.is_none()
+    }
+
+    /// Returns the request which will be made for the next page, if there is one. This can be
+    /// serialised to resume pagination later.
+    pub fn next_request(&self) -> Option<&B> {
+        self.next.as_ref()
+    }
+
+    /// Converts the paginator into a stream which yields each page in turn, as returned by
+    /// [`next_page`](Self::next_page). After a retryable error is yielded, polling the stream
+    /// again retries the failed page; after any other error, the stream ends.
+    pub fn into_stream(self) -> impl Stream<Item = Result<B::Response, Error>> + 'c
+    where
+        A: 'c,
+        B: 'c,
+    {
+        stream::unfold(self, |mut pages| async move {
+            let page = pages.next_page().await?;
+            Some((page, pages))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use futures_util::{future::{self, BoxFuture}, StreamExt};
+    use reqwest::StatusCode;
+
+    use crate::{
+        auth::BearerToken,
+        client::{Error, ErrorKind, ErrorRepr},
+        AsyncClient,
+    };
+
+    use super::{PaginatedRequest, PaginatedResponse, PaginationToken, Paginator};
+
+    struct FakeRequest<'f> {
+        page: u32,
+        fail_once: &'f AtomicBool,
+        fail_status: StatusCode,
+    }
+
+    struct FakeResponse {
+        page: u32,
+        next_token: Option<PaginationToken>,
+    }
+
+    impl PaginatedResponse for FakeResponse {
+        fn next_token(&self) -> Option<&PaginationToken> {
+            self.next_token.as_ref()
+        }
+    }
+
+    impl<'f, A> PaginatedRequest<A> for FakeRequest<'f> {
+        type Response = FakeResponse;
+
+        fn with_pagination_token(self, token: PaginationToken) -> Self {
+            Self {
+                page: token.as_str().parse().unwrap(),
+                ..self
+            }
+        }
+
+        fn execute_page<'a>(
+            &'a self,
+            _client: &'a AsyncClient<A>
+        ) -> BoxFuture<'a, Result<Self::Response, Error>>
+        {
+            let result = if self.page == 1 && self.fail_once.swap(false, Ordering::SeqCst) {
+                Err(ErrorRepr {
+                    kind: ErrorKind::ErrorResponse {
+                        status: self.fail_status,
+                        errors: Box::new([]),
+                    },
+                    limit_info: None,
+                }.boxed())
+            } else {
+                Ok(FakeResponse {
+                    page: self.page,
+                    next_token: Some(self.page + 1)
+                        .filter(|&page| page < 3)
+                        .map(|page| PaginationToken::new(page.to_string())),
+                })
+            };
+
+            Box::pin(future::ready(result))
+        }
+    }
+
+    fn collect_pages(fail_status: StatusCode) -> Vec<Option<u32>> {
+        let client = AsyncClient::new(BearerToken::new("token"), None).unwrap();
+        let fail_once = AtomicBool::new(true);
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let mut paginator = Paginator::new(&client, FakeRequest {
+                page: 0,
+                fail_once: &fail_once,
+                fail_status,
+            });
+
+            let mut pages = Vec::new();
+            while let Some(page) = paginator.next_page().await {
+                pages.push(page.map(|page| page.page).ok());
+            }
+            assert!(paginator.is_finished());
+            pages
+        })
+    }
+
+    fn stream_pages(fail_status: StatusCode) -> Vec<Option<u32>> {
+        let client = AsyncClient::new(BearerToken::new("token"), None).unwrap();
+        let fail_once = AtomicBool::new(true);
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(
+            Paginator::new(&client, FakeRequest {
+                page: 0,
+                fail_once: &fail_once,
+                fail_status,
+            })
+            .into_stream()
+            .map(|page| page.map(|page| page.page).ok())
+            .collect::<Vec<_>>()
+        )
+    }
+
+    #[test]
+    fn test_paginator() {
+        // A failed request for page 1 which may succeed later is retried by the following call.
+        assert_eq!(
+            collect_pages(StatusCode::SERVICE_UNAVAILABLE),
+            [Some(0), None, Some(1), Some(2)]
+        );
+
+        // A request which would fail again is not retried.
+        assert_eq!(collect_pages(StatusCode::UNAUTHORIZED), [Some(0), None]);
+    }
+
+    #[test]
+    fn test_paginator_stream() {
+        assert_eq!(
+            stream_pages(StatusCode::TOO_MANY_REQUESTS),
+            [Some(0), None, Some(1), Some(2)]
+        );
+
+        // The stream ends after an error which retrying would not fix.
+        assert_eq!(stream_pages(StatusCode::BAD_REQUEST), [Some(0), None]);
+    }
+}
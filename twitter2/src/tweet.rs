@@ -8,7 +8,13 @@ use serde::{Deserialize, Serialize};
 use crate::{
     entity::{Annotation, Tag, TweetMention, Url},
     id::IdU64,
-    media::MediaKey, user::UserId,
+    lang::Lang,
+    media::MediaKey,
+    open_enum::open_enum,
+    place::Place,
+    response::Includes,
+    user::UserId,
+    withheld::Withheld,
 };
 
 #[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
@@ -48,25 +54,117 @@ pub struct Tweet {
     #[serde(default)]
     pub attachments: TweetAttachments,
     pub author_id: Option<UserId>,
-    // context_annotations:
+    #[serde(default)]
+    pub context_annotations: Box<[ContextAnnotation]>,
     pub conversation_id: Option<TweetId>,
     pub created_at: Option<DateTime<Utc>>,
+    pub edit_controls: Option<TweetEditControls>,
+    /// The ids of every version of the Tweet, oldest first, including this one. A Tweet which has
+    /// never been edited has only its own id here.
+    #[serde(default)]
+    pub edit_history_tweet_ids: Box<[TweetId]>,
     #[serde(default)]
     pub entities: TweetEntities,
-    // geo:
+    pub geo: Option<TweetGeo>,
     pub in_reply_to_user_id: Option<UserId>,
-    // FIXME: parse language
-    pub lang: Option<InliningString23>,
-    // non_public_metrics:
-    // organic_metrics:
+    pub lang: Option<Lang>,
+    pub note_tweet: Option<NoteTweet>,
+    pub non_public_metrics: Option<TweetNonPublicMetrics>,
+    pub organic_metrics: Option<TweetOrganicMetrics>,
     pub possibly_sensitive: Option<bool>,
-    // promoted_metrics:
+    pub promoted_metrics: Option<TweetPromotedMetrics>,
     pub public_metrics: Option<TweetPublicMetrics>,
     #[serde(default)]
     pub referenced_tweets: Box<[ReferencedTweet]>,
     pub reply_settings: Option<ReplySettings>,
     pub source: Option<InliningString23>,
-    // withheld:
+    pub withheld: Option<Withheld>,
+}
+
+impl Tweet {
+    /// The full text of the Tweet. For Tweets longer than 280 characters, `text` is truncated and
+    /// the full text is in `note_tweet`, which is only returned if the `note_tweet` Tweet field
+    /// was requested.
+    pub fn full_text(&self) -> &str {
+        match &self.note_tweet {
+            Some(note_tweet) => &note_tweet.text,
+            None => &self.text,
+        }
+    }
+
+    /// The number of times the Tweet has been viewed, taken from `non_public_metrics` if it was
+    /// requested and otherwise from `public_metrics`. Returns `None` if neither was requested.
+    pub fn impression_count(&self) -> Option<u64> {
+        self.non_public_metrics
+            .as_ref()
+            .map(|metrics| metrics.impression_count)
+            .or_else(|| self.public_metrics.as_ref().map(|metrics| metrics.impression_count))
+    }
+
+    /// Finds the place the Tweet is tagged with in the includes of the response the Tweet came
+    /// from. The place is only included if the `geo.place_id` expansion was requested.
+    pub fn place<'a>(&self, includes: &'a Includes) -> Option<&'a Place> {
+        self.geo
+            .as_ref()
+            .and_then(|geo| geo.place_id.as_deref())
+            .and_then(|place_id| includes.get::<Place>(place_id))
+    }
+
+    /// The entities of the Tweet's [`full_text`](Self::full_text). The offsets of these entities
+    /// refer to the full text rather than the truncated `text`.
+    pub fn full_entities(&self) -> &TweetEntities {
+        match &self.note_tweet {
+            Some(note_tweet) => &note_tweet.entities,
+            None => &self.entities,
+        }
+    }
+}
+
+/// Whether and for how long a Tweet can be edited.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TweetEditControls {
+    pub edits_remaining: u32,
+    pub is_edit_eligible: bool,
+    pub editable_until: DateTime<Utc>,
+}
+
+impl TweetEditControls {
+    /// Returns `true` if the Tweet can still be edited at the current time.
+    pub fn can_edit(&self) -> bool {
+        self.is_edit_eligible && self.edits_remaining > 0 && Utc::now() < self.editable_until
+    }
+}
+
+/// The full content of a Tweet which is longer than 280 characters.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NoteTweet {
+    pub text: Box<str>,
+    #[serde(default)]
+    pub entities: TweetEntities,
+}
+
+/// A topic which Twitter has inferred that a Tweet is about, such as a particular sports team
+/// within the "Sports Team" domain.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContextAnnotation {
+    pub domain: ContextAnnotationDomain,
+    pub entity: ContextAnnotationEntity,
+}
+
+/// The category of a [`ContextAnnotation`], such as "Sports Team" or "Brand".
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContextAnnotationDomain {
+    pub id: Box<str>,
+    pub name: Box<str>,
+    pub description: Option<Box<str>>,
+}
+
+/// The specific topic of a [`ContextAnnotation`] within its domain.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContextAnnotationEntity {
+    pub id: Box<str>,
+    pub name: Box<str>,
+    pub description: Option<Box<str>>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -82,6 +180,45 @@ pub struct TweetPublicMetrics {
     pub reply_count: u64,
     pub like_count: u64,
     pub quote_count: u64,
+    /// The number of times the Tweet has been viewed. This is zero for Tweets retrieved before
+    /// Twitter began returning it as a public metric.
+    #[serde(default)]
+    pub impression_count: u64,
+}
+
+/// Metrics which are only available to the Tweet's author, using user-context authentication.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TweetNonPublicMetrics {
+    pub impression_count: u64,
+    /// The number of clicks on links in the Tweet. Only returned if the Tweet contains a link.
+    pub url_link_clicks: Option<u64>,
+    pub user_profile_clicks: u64,
+}
+
+/// Metrics for the Tweet in an organic context, excluding any engagement while it was promoted.
+/// Only available to the Tweet's author, using user-context authentication.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TweetOrganicMetrics {
+    pub impression_count: u64,
+    pub like_count: u64,
+    pub reply_count: u64,
+    pub retweet_count: u64,
+    /// The number of clicks on links in the Tweet. Only returned if the Tweet contains a link.
+    pub url_link_clicks: Option<u64>,
+    pub user_profile_clicks: u64,
+}
+
+/// Metrics for the Tweet while it was promoted as an ad. Only available to the Tweet's author,
+/// using user-context authentication.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TweetPromotedMetrics {
+    pub impression_count: u64,
+    pub like_count: u64,
+    pub reply_count: u64,
+    pub retweet_count: u64,
+    /// The number of clicks on links in the Tweet. Only returned if the Tweet contains a link.
+    pub url_link_clicks: Option<u64>,
+    pub user_profile_clicks: u64,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug)]
@@ -98,6 +235,24 @@ pub struct TweetEntities {
     pub urls: Box<[Url]>,
 }
 
+impl TweetEntities {
+    /// Returns an iterator over the annotations whose probability is at least `min_probability`.
+    pub fn confident_annotations(&self, min_probability: f64)
+        -> impl Iterator<Item = &Annotation>
+    {
+        self.annotations
+            .iter()
+            .filter(move |annotation| annotation.probability() >= min_probability)
+    }
+}
+
+/// The location a Tweet is tagged with.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TweetGeo {
+    /// The id of the [`Place`] the Tweet is tagged with.
+    pub place_id: Option<InliningString23>,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct TweetAttachments {
     #[serde(default)]
@@ -116,14 +271,16 @@ pub enum ReferenceType {
     Retweeted,
 }
 
-#[derive(EnumSerialize, EnumDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
-pub enum ReplySettings {
-    #[enumscribe(str = "everyone")]
-    Everyone,
-    #[enumscribe(str = "mentionedUsers")]
-    MentionedUsers,
-    #[enumscribe(str = "followers")]
-    Followers,
+open_enum! {
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub enum ReplySettings("a reply settings string") {
+        Everyone = "everyone",
+        MentionedUsers = "mentionedUsers",
+        Followers = "followers";
+        /// A reply setting not yet known to this library. This is serialised as the contained
+        /// string, so a value read from the API can be passed back to it unchanged.
+        Other,
+    }
 }
 
 impl ReplySettings {
@@ -137,3 +294,368 @@ impl Default for ReplySettings {
         Self::Everyone
     }
 }
+
+#[cfg(feature = "test-util")]
+impl Tweet {
+    /// Returns a builder which can be used to construct a `Tweet` directly, for use as a test
+    /// fixture.
+    #[must_use]
+    pub fn builder(id: TweetId, text: &str) -> TweetBuilder {
+        TweetBuilder::new(id, text)
+    }
+}
+
+#[cfg(feature = "test-util")]
+pub struct TweetBuilder {
+    tweet: Tweet,
+}
+
+#[cfg(feature = "test-util")]
+impl TweetBuilder {
+    #[must_use]
+    pub fn new(id: TweetId, text: &str) -> Self {
+        Self {
+            tweet: Tweet {
+                id,
+                text: text.into(),
+                attachments: TweetAttachments::default(),
+                author_id: None,
+                context_annotations: Box::default(),
+                conversation_id: None,
+                created_at: None,
+                edit_controls: None,
+                edit_history_tweet_ids: Box::default(),
+                entities: TweetEntities::default(),
+                geo: None,
+                in_reply_to_user_id: None,
+                lang: None,
+                note_tweet: None,
+                non_public_metrics: None,
+                organic_metrics: None,
+                possibly_sensitive: None,
+                promoted_metrics: None,
+                public_metrics: None,
+                referenced_tweets: Box::default(),
+                reply_settings: None,
+                source: None,
+                withheld: None,
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn media_keys<I>(self, media_keys: I) -> Self
+    where
+        I: IntoIterator<Item = MediaKey>,
+    {
+        Self {
+            tweet: Tweet {
+                attachments: TweetAttachments {
+                    media_keys: media_keys.into_iter().collect(),
+                    ..self.tweet.attachments
+                },
+                ..self.tweet
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn author_id(self, author_id: UserId) -> Self {
+        Self {
+            tweet: Tweet {
+                author_id: Some(author_id),
+                ..self.tweet
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn conversation_id(self, conversation_id: TweetId) -> Self {
+        Self {
+            tweet: Tweet {
+                conversation_id: Some(conversation_id),
+                ..self.tweet
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn created_at(self, created_at: DateTime<Utc>) -> Self {
+        Self {
+            tweet: Tweet {
+                created_at: Some(created_at),
+                ..self.tweet
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn in_reply_to_user_id(self, in_reply_to_user_id: UserId) -> Self {
+        Self {
+            tweet: Tweet {
+                in_reply_to_user_id: Some(in_reply_to_user_id),
+                ..self.tweet
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn lang(self, lang: Lang) -> Self {
+        Self {
+            tweet: Tweet {
+                lang: Some(lang),
+                ..self.tweet
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn note_tweet(self, text: &str) -> Self {
+        Self {
+            tweet: Tweet {
+                note_tweet: Some(NoteTweet {
+                    text: text.into(),
+                    entities: TweetEntities::default(),
+                }),
+                ..self.tweet
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn possibly_sensitive(self, possibly_sensitive: bool) -> Self {
+        Self {
+            tweet: Tweet {
+                possibly_sensitive: Some(possibly_sensitive),
+                ..self.tweet
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn public_metrics(self, public_metrics: TweetPublicMetrics) -> Self {
+        Self {
+            tweet: Tweet {
+                public_metrics: Some(public_metrics),
+                ..self.tweet
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn referenced_tweet(self, reference_type: ReferenceType, id: TweetId) -> Self {
+        let mut referenced_tweets = Vec::from(self.tweet.referenced_tweets);
+        referenced_tweets.push(ReferencedTweet { reference_type, id });
+        Self {
+            tweet: Tweet {
+                referenced_tweets: referenced_tweets.into_boxed_slice(),
+                ..self.tweet
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn reply_settings(self, reply_settings: ReplySettings) -> Self {
+        Self {
+            tweet: Tweet {
+                reply_settings: Some(reply_settings),
+                ..self.tweet
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn source(self, source: &str) -> Self {
+        Self {
+            tweet: Tweet {
+                source: Some(InliningString23::new(source)),
+                ..self.tweet
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn build(self) -> Tweet {
+        self.tweet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::lang::Lang;
+
+    use super::{Tweet, TweetId};
+
+    #[test]
+    fn test_created_at() {
+        let json = r#"{"id": "1", "text": "foo", "created_at": "2019-06-04T23:12:08.000Z"}"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        assert_eq!(
+            tweet.created_at,
+            Some(Utc.with_ymd_and_hms(2019, 6, 4, 23, 12, 8).unwrap())
+        );
+
+        let json = r#"{"id": "1", "text": "foo", "created_at": null}"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        assert_eq!(tweet.created_at, None);
+
+        let json = r#"{"id": "1", "text": "foo"}"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        assert_eq!(tweet.created_at, None);
+    }
+
+    #[test]
+    fn test_lang() {
+        let json = r#"{"id": "1", "text": "foo", "lang": "en"}"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        assert_eq!(tweet.lang, Lang::new("en"));
+
+        let json = r#"{"id": "1", "text": "foo", "lang": "und"}"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        assert!(tweet.lang.unwrap().is_undetermined());
+    }
+
+    #[test]
+    fn test_context_annotations() {
+        let json = r#"{
+            "id": "1",
+            "text": "foo",
+            "context_annotations": [{
+                "domain": {
+                    "id": "65",
+                    "name": "Interests and Hobbies Vertical",
+                    "description": "Top level interests and hobbies groupings"
+                },
+                "entity": {"id": "847544972781826048", "name": "Programming"}
+            }]
+        }"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        let annotation = &tweet.context_annotations[0];
+        assert_eq!(&*annotation.domain.id, "65");
+        assert_eq!(&*annotation.entity.name, "Programming");
+        assert_eq!(annotation.entity.description, None);
+
+        let json = r#"{"id": "1", "text": "foo"}"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        assert!(tweet.context_annotations.is_empty());
+    }
+
+    #[test]
+    fn test_private_metrics() {
+        let json = r#"{
+            "id": "1",
+            "text": "foo",
+            "non_public_metrics": {"impression_count": 956, "user_profile_clicks": 3},
+            "organic_metrics": {
+                "impression_count": 956,
+                "like_count": 49,
+                "reply_count": 2,
+                "retweet_count": 9,
+                "url_link_clicks": 17,
+                "user_profile_clicks": 3
+            }
+        }"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        let non_public_metrics = tweet.non_public_metrics.as_ref().unwrap();
+        assert_eq!(non_public_metrics.impression_count, 956);
+        assert_eq!(non_public_metrics.url_link_clicks, None);
+        let organic_metrics = tweet.organic_metrics.as_ref().unwrap();
+        assert_eq!(organic_metrics.like_count, 49);
+        assert_eq!(organic_metrics.url_link_clicks, Some(17));
+        assert!(tweet.promoted_metrics.is_none());
+        assert_eq!(tweet.impression_count(), Some(956));
+    }
+
+    #[test]
+    fn test_public_metrics_impression_count() {
+        let json = r#"{
+            "id": "1",
+            "text": "foo",
+            "public_metrics": {
+                "retweet_count": 1,
+                "reply_count": 2,
+                "like_count": 3,
+                "quote_count": 4,
+                "impression_count": 500
+            }
+        }"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        assert_eq!(tweet.public_metrics.as_ref().unwrap().impression_count, 500);
+        assert_eq!(tweet.impression_count(), Some(500));
+
+        let json = r#"{
+            "id": "1",
+            "text": "foo",
+            "public_metrics": {
+                "retweet_count": 1,
+                "reply_count": 2,
+                "like_count": 3,
+                "quote_count": 4
+            }
+        }"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        assert_eq!(tweet.public_metrics.unwrap().impression_count, 0);
+
+        let json = r#"{"id": "1", "text": "foo"}"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        assert_eq!(tweet.impression_count(), None);
+    }
+
+    #[test]
+    fn test_full_text() {
+        let json = r#"{
+            "id": "1",
+            "text": "short",
+            "entities": {"hashtags": [{"start": 0, "end": 5, "tag": "short"}]}
+        }"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        assert_eq!(tweet.full_text(), "short");
+        assert_eq!(tweet.full_entities().hashtags.len(), 1);
+
+        let json = r#"{
+            "id": "1",
+            "text": "a long Tweet which is trunc…",
+            "note_tweet": {
+                "text": "a long Tweet which is truncated #here",
+                "entities": {"hashtags": [{"start": 32, "end": 37, "tag": "here"}]}
+            }
+        }"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        assert_eq!(tweet.full_text(), "a long Tweet which is truncated #here");
+        let tag = &tweet.full_entities().hashtags[0];
+        assert_eq!(tag.text(tweet.full_text()), Some("#here"));
+    }
+
+    #[test]
+    fn test_edit_history() {
+        let json = r#"{
+            "id": "1587911540770222081",
+            "text": "edited",
+            "edit_history_tweet_ids": ["1587911540770222080", "1587911540770222081"],
+            "edit_controls": {
+                "edits_remaining": 4,
+                "is_edit_eligible": true,
+                "editable_until": "2022-11-02T21:36:30.000Z"
+            }
+        }"#;
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        assert_eq!(
+            &*tweet.edit_history_tweet_ids,
+            &[TweetId(1587911540770222080), TweetId(1587911540770222081)]
+        );
+        let edit_controls = tweet.edit_controls.unwrap();
+        assert_eq!(edit_controls.edits_remaining, 4);
+        assert!(edit_controls.is_edit_eligible);
+        assert_eq!(
+            edit_controls.editable_until,
+            Utc.with_ymd_and_hms(2022, 11, 2, 21, 36, 30).unwrap()
+        );
+        assert!(!edit_controls.can_edit());
+
+        let tweet = serde_json::from_str::<Tweet>(r#"{"id": "1", "text": "foo"}"#).unwrap();
+        assert!(tweet.edit_history_tweet_ids.is_empty());
+        assert!(tweet.edit_controls.is_none());
+    }
+}
@@ -0,0 +1,418 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    num::ParseIntError,
+    str,
+    time::{Duration, Instant},
+};
+
+use enumscribe::{EnumDeserialize, EnumSerialize, ScribeStaticStr};
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+
+use crate::{
+    auth::{Scope, UserAuth},
+    client::{AsyncClient, Error, ErrorKind, ErrorRepr, Method, Request},
+    id::IdU64,
+    limit::LimitInfo,
+    request_data::{FormData, MultipartData, QueryData},
+};
+
+const UPLOAD_URL: &str = "https://upload.twitter.com/1.1/media/upload.json";
+const SCOPES: &[Scope] = &[Scope::MediaWrite];
+
+/// The id of a piece of media uploaded with [`MediaUploader`], which can be attached to a Tweet.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[serde(from = "IdU64", into = "IdU64")]
+pub struct MediaUploadId(pub u64);
+
+impl From<IdU64> for MediaUploadId {
+    fn from(IdU64(id): IdU64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<MediaUploadId> for IdU64 {
+    fn from(MediaUploadId(id): MediaUploadId) -> Self {
+        Self(id)
+    }
+}
+
+impl fmt::Display for MediaUploadId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        <u64 as fmt::Display>::fmt(&self.0, f)
+    }
+}
+
+impl str::FromStr for MediaUploadId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// The intended use of uploaded media. Videos and GIFs uploaded with a category are processed
+/// asynchronously by Twitter after the upload finishes.
+#[derive(ScribeStaticStr, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MediaCategory {
+    #[enumscribe(str = "tweet_image")]
+    TweetImage,
+    #[enumscribe(str = "tweet_gif")]
+    TweetGif,
+    #[enumscribe(str = "tweet_video")]
+    TweetVideo,
+    #[enumscribe(str = "amplify_video")]
+    AmplifyVideo,
+}
+
+/// The progress of an upload, reported to the callback passed to
+/// [`MediaUploader::upload_with_progress`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UploadProgress {
+    /// Some of the media's bytes have been sent.
+    Uploading { uploaded_bytes: usize, total_bytes: usize },
+    /// All of the media has been sent and Twitter is processing it.
+    Processing { progress_percent: Option<u8> },
+}
+
+/// Uploads media in chunks using the v1.1 chunked upload endpoint, which is needed for videos and
+/// GIFs and can also be used for images.
+#[derive(Clone, Debug)]
+pub struct MediaUploader<'a> {
+    data: &'a [u8],
+    media_type: &'a str,
+    media_category: Option<MediaCategory>,
+    chunk_size: usize,
+    processing_timeout: Duration,
+}
+
+impl<'a> MediaUploader<'a> {
+    pub const DEFAULT_CHUNK_SIZE: usize = 1024 * 1024;
+    pub const MAX_CHUNK_SIZE: usize = 5 * 1024 * 1024;
+    pub const DEFAULT_PROCESSING_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+    /// Creates a new uploader for the given media, where `media_type` is its MIME type, such as
+    /// `video/mp4`.
+    pub fn new(data: &'a [u8], media_type: &'a str) -> Self {
+        Self {
+            data,
+            media_type,
+            media_category: None,
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+            processing_timeout: Self::DEFAULT_PROCESSING_TIMEOUT,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn media_category(self, media_category: MediaCategory) -> Self {
+        Self { media_category: Some(media_category), ..self }
+    }
+
+    /// Sets the number of bytes sent in each `APPEND` request. The size is clamped to the range
+    /// 1 to [`Self::MAX_CHUNK_SIZE`].
+    #[inline]
+    #[must_use]
+    pub fn chunk_size(self, chunk_size: usize) -> Self {
+        Self { chunk_size: chunk_size.clamp(1, Self::MAX_CHUNK_SIZE), ..self }
+    }
+
+    /// Sets how long to wait for Twitter to finish processing the media after it has been sent,
+    /// after which the upload fails. By default, this is
+    /// [`Self::DEFAULT_PROCESSING_TIMEOUT`].
+    #[inline]
+    #[must_use]
+    pub fn processing_timeout(self, processing_timeout: Duration) -> Self {
+        Self { processing_timeout, ..self }
+    }
+
+    pub async fn upload<A>(&self, client: &AsyncClient<A>) -> Result<UploadedMedia, Error>
+    where
+        A: UserAuth,
+    {
+        self.upload_with_progress(client, |_| ()).await
+    }
+
+    /// Uploads the media, calling `progress` after each chunk is sent and each time the status of
+    /// asynchronous processing is checked. Returns once the media is ready to be attached to a
+    /// Tweet, or an error if processing has not finished within the
+    /// [`processing_timeout`](Self::processing_timeout).
+    pub async fn upload_with_progress<A, F>(
+        &self,
+        client: &AsyncClient<A>,
+        mut progress: F
+    ) -> Result<UploadedMedia, Error>
+    where
+        A: UserAuth,
+        F: FnMut(UploadProgress),
+    {
+        let total_bytes = self.data.len();
+
+        let (init, _) = self.init(client).await?;
+        let media_id = init.media_id;
+
+        let media_id_str = media_id.to_string();
+        let mut uploaded_bytes = 0;
+
+        for (segment_index, chunk) in self.data.chunks(self.chunk_size).enumerate() {
+            let segment_index = segment_index.to_string();
+
+            let params = [
+                (Cow::Borrowed("command"), Cow::Borrowed("APPEND")),
+                (Cow::Borrowed("media_id"), Cow::Borrowed(&*media_id_str)),
+                (Cow::Borrowed("segment_index"), Cow::Borrowed(&*segment_index)),
+            ];
+
+            client.apiv1_request::<_, IgnoredAny>(
+                Request::new_with_data(
+                    Method::Post,
+                    UPLOAD_URL,
+                    MultipartData::new(&params, "media", chunk)
+                )
+                .with_scopes(SCOPES)
+            ).await?;
+
+            uploaded_bytes += chunk.len();
+            progress(UploadProgress::Uploading { uploaded_bytes, total_bytes });
+        }
+
+        let params = [
+            (Cow::Borrowed("command"), Cow::Borrowed("FINALIZE")),
+            (Cow::Borrowed("media_id"), Cow::Borrowed(&*media_id_str)),
+        ];
+
+        let (finalized, mut limit_info) = upload_response(
+            client.apiv1_request::<_, UploadResponse>(
+                Request::new_with_data(Method::Post, UPLOAD_URL, FormData::new(&params))
+                    .with_scopes(SCOPES)
+            ).await?
+        )?;
+
+        let deadline = Instant::now() + self.processing_timeout;
+        let mut processing_info = finalized.processing_info;
+
+        while let Some(info) = processing_info {
+            match info.state {
+                ProcessingState::Succeeded => break,
+                ProcessingState::Failed => return Err(processing_error(info, limit_info)),
+                ProcessingState::Pending | ProcessingState::InProgress => {
+                    progress(UploadProgress::Processing {
+                        progress_percent: info.progress_percent,
+                    });
+
+                    let wait = match status_check_wait(&info, deadline) {
+                        Some(wait) => wait,
+                        None => {
+                            return Err(ErrorRepr {
+                                kind: ErrorKind::Custom(format!(
+                                    "media processing did not finish within {} seconds",
+                                    self.processing_timeout.as_secs()
+                                ).into()),
+                                limit_info: Some(limit_info),
+                            }.boxed());
+                        },
+                    };
+
+                    tokio::time::sleep(wait).await;
+
+                    let status = client.media_status(media_id).await?;
+                    processing_info = status.processing_info;
+                    limit_info = status.limit_info;
+                },
+            }
+        }
+
+        Ok(UploadedMedia {
+            media_id,
+            limit_info,
+        })
+    }
+
+    async fn init<A>(&self, client: &AsyncClient<A>) -> Result<(UploadResponse, LimitInfo), Error>
+    where
+        A: UserAuth,
+    {
+        let total_bytes = self.data.len().to_string();
+
+        let mut params = vec![
+            (Cow::Borrowed("command"), Cow::Borrowed("INIT")),
+            (Cow::Borrowed("total_bytes"), Cow::Owned(total_bytes)),
+            (Cow::Borrowed("media_type"), Cow::Borrowed(self.media_type)),
+        ];
+
+        if let Some(media_category) = self.media_category {
+            params.push((Cow::Borrowed("media_category"), Cow::Borrowed(media_category.scribe())));
+        }
+
+        upload_response(
+            client.apiv1_request::<_, UploadResponse>(
+                Request::new_with_data(Method::Post, UPLOAD_URL, FormData::new(&params))
+                    .with_scopes(SCOPES)
+            ).await?
+        )
+    }
+}
+
+fn upload_response(
+    (data, limit_info): (Option<UploadResponse>, LimitInfo)
+) -> Result<(UploadResponse, LimitInfo), Error>
+{
+    match data {
+        Some(data) => Ok((data, limit_info)),
+        None => Err(ErrorRepr {
+            kind: ErrorKind::NoData,
+            limit_info: Some(limit_info),
+        }.boxed()),
+    }
+}
+
+/// Returns how long to wait before checking the status of media which is still being processed,
+/// or `None` if the check would be after the deadline.
+fn status_check_wait(info: &ProcessingInfo, deadline: Instant) -> Option<Duration> {
+    let wait = Duration::from_secs(info.check_after_secs.unwrap_or(1));
+
+    if wait > deadline.saturating_duration_since(Instant::now()) {
+        None
+    } else {
+        Some(wait)
+    }
+}
+
+fn processing_error(info: ProcessingInfo, limit_info: LimitInfo) -> Error {
+    let message = match info.error {
+        Some(ProcessingError { message: Some(message), .. }) => {
+            format!("media processing failed: {}", message)
+        },
+        Some(ProcessingError { name: Some(name), .. }) => {
+            format!("media processing failed: {}", name)
+        },
+        _ => "media processing failed".to_owned(),
+    };
+
+    ErrorRepr {
+        kind: ErrorKind::Custom(message.into()),
+        limit_info: Some(limit_info),
+    }.boxed()
+}
+
+impl<A: UserAuth> AsyncClient<A> {
+    /// Checks the status of asynchronous processing for media uploaded with a media category.
+    pub async fn media_status(&self, media_id: MediaUploadId) -> Result<MediaStatus, Error> {
+        let media_id = media_id.to_string();
+        let params = [("command", "STATUS"), ("media_id", &*media_id)];
+
+        let (data, limit_info) = upload_response(
+            self.apiv1_request::<_, UploadResponse>(
+                Request::new_with_data(Method::Get, UPLOAD_URL, QueryData::new(&params))
+                    .with_scopes(SCOPES)
+            ).await?
+        )?;
+
+        Ok(MediaStatus {
+            media_id: data.media_id,
+            processing_info: data.processing_info,
+            limit_info,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct UploadedMedia {
+    pub media_id: MediaUploadId,
+    pub limit_info: LimitInfo,
+}
+
+#[derive(Debug)]
+pub struct MediaStatus {
+    pub media_id: MediaUploadId,
+    pub processing_info: Option<ProcessingInfo>,
+    pub limit_info: LimitInfo,
+}
+
+#[derive(Deserialize, Debug)]
+struct UploadResponse {
+    #[serde(rename = "media_id_string")]
+    media_id: MediaUploadId,
+    processing_info: Option<ProcessingInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProcessingInfo {
+    pub state: ProcessingState,
+    pub check_after_secs: Option<u64>,
+    pub progress_percent: Option<u8>,
+    pub error: Option<ProcessingError>,
+}
+
+#[derive(
+    ScribeStaticStr, EnumSerialize, EnumDeserialize, Clone, Copy, PartialEq, Eq, Hash, Debug
+)]
+pub enum ProcessingState {
+    #[enumscribe(str = "pending")]
+    Pending,
+    #[enumscribe(str = "in_progress")]
+    InProgress,
+    #[enumscribe(str = "failed")]
+    Failed,
+    #[enumscribe(str = "succeeded")]
+    Succeeded,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProcessingError {
+    pub code: Option<u32>,
+    pub name: Option<Box<str>>,
+    pub message: Option<Box<str>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{
+        status_check_wait,
+        MediaUploadId,
+        ProcessingInfo,
+        ProcessingState,
+        UploadResponse,
+    };
+
+    #[test]
+    fn test_upload_response() {
+        let json = r#"{
+            "media_id": 710511363345354753,
+            "media_id_string": "710511363345354753",
+            "expires_after_secs": 86400,
+            "processing_info": {
+                "state": "in_progress",
+                "check_after_secs": 10,
+                "progress_percent": 8
+            }
+        }"#;
+
+        let resp = serde_json::from_str::<UploadResponse>(json).unwrap();
+        assert_eq!(resp.media_id, MediaUploadId(710511363345354753));
+        let info = resp.processing_info.unwrap();
+        assert_eq!(info.state, ProcessingState::InProgress);
+        assert_eq!(info.check_after_secs, Some(10));
+        assert_eq!(info.progress_percent, Some(8));
+        assert!(info.error.is_none());
+    }
+
+    #[test]
+    fn test_status_check_wait() {
+        let info = ProcessingInfo {
+            state: ProcessingState::InProgress,
+            check_after_secs: Some(10),
+            progress_percent: None,
+            error: None,
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        assert_eq!(status_check_wait(&info, deadline), Some(Duration::from_secs(10)));
+
+        // A check which would be after the deadline fails the upload rather than waiting.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        assert_eq!(status_check_wait(&info, deadline), None);
+    }
+}
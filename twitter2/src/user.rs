@@ -6,7 +6,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     entity::{Tag, Url, UserMention},
-    id::IdU64, tweet::TweetId,
+    id::IdU64, tweet::{Tweet, TweetId},
+    response::Includes,
+    withheld::Withheld,
 };
 
 #[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
@@ -54,7 +56,21 @@ pub struct User {
     pub public_metrics: Option<UserPublicMetrics>,
     pub url: Option<Box<str>>,
     pub verified: Option<bool>,
-    // withheld:
+    pub withheld: Option<Withheld>,
+}
+
+impl User {
+    /// Returns `true` if the user's public metrics were returned. They may be missing even if they
+    /// were requested, for example if the user is protected.
+    pub fn metrics_available(&self) -> bool {
+        self.public_metrics.is_some()
+    }
+
+    /// Finds the user's pinned Tweet in the includes of the response the user came from. The
+    /// pinned Tweet is only included if the `pinned_tweet_id` expansion was requested.
+    pub fn pinned_tweet<'a>(&self, includes: &'a Includes) -> Option<&'a Tweet> {
+        self.pinned_tweet_id.and_then(|id| includes.get::<Tweet>(&id))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -142,3 +158,202 @@ impl UserDescriptionEntities {
         &self.urls
     }
 }
+
+#[cfg(feature = "test-util")]
+impl User {
+    /// Returns a builder which can be used to construct a `User` directly, for use as a test
+    /// fixture.
+    #[must_use]
+    pub fn builder(id: UserId, name: &str, username: &str) -> UserBuilder {
+        UserBuilder::new(id, name, username)
+    }
+}
+
+#[cfg(feature = "test-util")]
+pub struct UserBuilder {
+    user: User,
+}
+
+#[cfg(feature = "test-util")]
+impl UserBuilder {
+    #[must_use]
+    pub fn new(id: UserId, name: &str, username: &str) -> Self {
+        Self {
+            user: User {
+                id,
+                name: InliningString23::new(name),
+                username: InliningString23::new(username),
+                created_at: None,
+                description: None,
+                entities: None,
+                location: None,
+                pinned_tweet_id: None,
+                profile_image_url: None,
+                protected: None,
+                public_metrics: None,
+                url: None,
+                verified: None,
+                withheld: None,
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn created_at(self, created_at: DateTime<Utc>) -> Self {
+        Self {
+            user: User {
+                created_at: Some(created_at),
+                ..self.user
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn description(self, description: &str) -> Self {
+        Self {
+            user: User {
+                description: Some(description.into()),
+                ..self.user
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn location(self, location: &str) -> Self {
+        Self {
+            user: User {
+                location: Some(location.into()),
+                ..self.user
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn pinned_tweet_id(self, pinned_tweet_id: TweetId) -> Self {
+        Self {
+            user: User {
+                pinned_tweet_id: Some(pinned_tweet_id),
+                ..self.user
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn profile_image_url(self, profile_image_url: &str) -> Self {
+        Self {
+            user: User {
+                profile_image_url: Some(profile_image_url.into()),
+                ..self.user
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn protected(self, protected: bool) -> Self {
+        Self {
+            user: User {
+                protected: Some(protected),
+                ..self.user
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn public_metrics(
+        self,
+        followers_count: u64,
+        following_count: u64,
+        tweet_count: u64,
+        listed_count: u64,
+    ) -> Self
+    {
+        Self {
+            user: User {
+                public_metrics: Some(UserPublicMetrics {
+                    followers_count,
+                    following_count,
+                    tweet_count,
+                    listed_count,
+                }),
+                ..self.user
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn url(self, url: &str) -> Self {
+        Self {
+            user: User {
+                url: Some(url.into()),
+                ..self.user
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn verified(self, verified: bool) -> Self {
+        Self {
+            user: User {
+                verified: Some(verified),
+                ..self.user
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn build(self) -> User {
+        self.user
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::response::ApiV2Response;
+
+    use super::User;
+
+    #[test]
+    fn test_metrics_unavailable() {
+        let json = r#"{"id": "2", "name": "Name", "username": "username", "protected": true}"#;
+        let user = serde_json::from_str::<User>(json).unwrap();
+        assert!(!user.metrics_available());
+
+        let json = r#"{
+            "id": "2",
+            "name": "Name",
+            "username": "username",
+            "public_metrics": {
+                "followers_count": 1,
+                "following_count": 2,
+                "tweet_count": 3,
+                "listed_count": 4
+            }
+        }"#;
+        let user = serde_json::from_str::<User>(json).unwrap();
+        assert!(user.metrics_available());
+        assert_eq!(user.public_metrics.unwrap().tweet_count(), 3);
+    }
+
+    #[test]
+    fn test_pinned_tweet() {
+        let json = r#"{
+            "data": {"id": "2", "name": "Name", "username": "username", "pinned_tweet_id": "5"},
+            "includes": {"tweets": [{"id": "4", "text": "foo"}, {"id": "5", "text": "pinned"}]}
+        }"#;
+        let response = serde_json::from_str::<ApiV2Response<User>>(json).unwrap();
+        let user = response.data.unwrap();
+        let pinned = user.pinned_tweet(&response.includes).unwrap();
+        assert_eq!(&*pinned.text, "pinned");
+
+        let user = serde_json::from_str::<User>(r#"{"id": "2", "name": "n", "username": "u"}"#)
+            .unwrap();
+        assert!(user.pinned_tweet(&response.includes).is_none());
+    }
+
+    #[test]
+    fn test_created_at_null() {
+        let json = r#"{"id": "2", "name": "Name", "username": "username", "created_at": null}"#;
+        let user = serde_json::from_str::<User>(json).unwrap();
+        assert_eq!(user.created_at, None);
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use crate::user::UserId;
+
+/// A bounded cache of username to user id mappings, which evicts the least recently used entry
+/// when it is full. Usernames are case-insensitive, so they are stored in lowercase.
+#[derive(Debug)]
+pub(crate) struct UsernameCache {
+    entries: HashMap<Box<str>, CacheEntry>,
+    capacity: usize,
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    user_id: UserId,
+    last_used: u64,
+}
+
+impl UsernameCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity,
+            clock: 0,
+        }
+    }
+
+    pub(crate) fn get(&mut self, username: &str) -> Option<UserId> {
+        let now = self.tick();
+        let entry = self.entries.get_mut(&*username.to_ascii_lowercase())?;
+        entry.last_used = now;
+        Some(entry.user_id)
+    }
+
+    pub(crate) fn insert(&mut self, username: &str, user_id: UserId) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let username = username.to_ascii_lowercase().into_boxed_str();
+
+        if !self.entries.contains_key(&username) && self.entries.len() >= self.capacity {
+            self.evict_least_recently_used();
+        }
+
+        let last_used = self.tick();
+        self.entries.insert(username, CacheEntry { user_id, last_used });
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(username, _)| username.clone());
+
+        if let Some(oldest) = oldest {
+            self.entries.remove(&oldest);
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::user::UserId;
+
+    use super::UsernameCache;
+
+    #[test]
+    fn test_username_cache_eviction() {
+        let mut cache = UsernameCache::new(2);
+        cache.insert("a", UserId(1));
+        cache.insert("b", UserId(2));
+        assert_eq!(cache.get("A"), Some(UserId(1)));
+
+        // "b" is now the least recently used entry.
+        cache.insert("c", UserId(3));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(UserId(1)));
+        assert_eq!(cache.get("c"), Some(UserId(3)));
+
+        // Replacing an existing entry does not evict anything.
+        cache.insert("C", UserId(4));
+        assert_eq!(cache.get("a"), Some(UserId(1)));
+        assert_eq!(cache.get("c"), Some(UserId(4)));
+    }
+
+    #[test]
+    fn test_username_cache_zero_capacity() {
+        let mut cache = UsernameCache::new(0);
+        cache.insert("a", UserId(1));
+        assert_eq!(cache.get("a"), None);
+    }
+}
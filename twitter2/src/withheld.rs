@@ -0,0 +1,79 @@
+use libshire::strings::InliningString23;
+use serde::{Deserialize, Serialize};
+
+use crate::open_enum::open_enum;
+
+/// Information about content which has been withheld in some countries.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Withheld {
+    /// Whether the content was withheld due to a DMCA copyright notice. Only present for Tweets.
+    #[serde(default)]
+    pub copyright: bool,
+    /// The ISO 3166-1 alpha-2 codes of the countries the content is withheld in.
+    #[serde(default)]
+    pub country_codes: Box<[InliningString23]>,
+    /// Whether a single Tweet or the whole account is withheld.
+    pub scope: Option<WithheldScope>,
+}
+
+open_enum! {
+    #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+    pub enum WithheldScope("a withheld scope string") {
+        Tweet = "tweet",
+        User = "user";
+        /// A scope not yet known to this library.
+        Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tweet::Tweet, user::User};
+
+    use super::WithheldScope;
+
+    #[test]
+    fn test_withheld_tweet() {
+        let json = r#"{
+            "id": "1067094924124872705",
+            "text": "withheld",
+            "withheld": {
+                "copyright": false,
+                "country_codes": ["DE", "FR"],
+                "scope": "tweet"
+            }
+        }"#;
+
+        let tweet = serde_json::from_str::<Tweet>(json).unwrap();
+        let withheld = tweet.withheld.unwrap();
+        assert!(!withheld.copyright);
+        assert_eq!(
+            withheld.country_codes.iter().map(|code| &**code).collect::<Vec<_>>(),
+            ["DE", "FR"]
+        );
+        assert_eq!(withheld.scope, Some(WithheldScope::Tweet));
+    }
+
+    #[test]
+    fn test_withheld_user() {
+        let json = r#"{
+            "id": "2244994945",
+            "name": "Name",
+            "username": "username",
+            "withheld": {
+                "country_codes": ["TR"],
+                "scope": "user"
+            }
+        }"#;
+
+        let user = serde_json::from_str::<User>(json).unwrap();
+        assert_eq!(user.withheld.unwrap().scope, Some(WithheldScope::User));
+    }
+
+    #[test]
+    fn test_withheld_scope_other() {
+        let scope = serde_json::from_str::<WithheldScope>(r#""space""#).unwrap();
+        assert_eq!(scope, WithheldScope::Other("space".into()));
+        assert_eq!(serde_json::to_string(&scope).unwrap(), r#""space""#);
+    }
+}